@@ -271,6 +271,61 @@ pub mod docs_update_queries {
 #[cynic::schema("github")]
 mod schema {}
 
+pub mod open_pull_requests {
+    use super::queries::{DateTime, LabelConnection, PageInfo, User, UserConnection};
+    use super::schema;
+
+    #[derive(cynic::QueryVariables, Debug, Clone)]
+    pub struct Arguments<'a> {
+        pub repository_owner: &'a str,
+        pub repository_name: &'a str,
+        pub after: Option<String>,
+    }
+
+    #[derive(cynic::QueryFragment, Debug)]
+    #[cynic(variables = "Arguments")]
+    pub struct Query {
+        #[arguments(owner: $repository_owner, name: $repository_name)]
+        pub repository: Option<Repository>,
+    }
+
+    #[derive(cynic::QueryFragment, Debug)]
+    #[cynic(variables = "Arguments")]
+    pub struct Repository {
+        #[arguments(states: "OPEN", first: 100, after: $after)]
+        pub pull_requests: PullRequestConnection,
+    }
+
+    #[derive(cynic::QueryFragment, Debug)]
+    pub struct PullRequestConnection {
+        pub page_info: PageInfo,
+        #[cynic(flatten)]
+        pub nodes: Vec<PullRequest>,
+    }
+
+    #[derive(cynic::QueryFragment, Debug)]
+    pub struct PullRequest {
+        pub number: i32,
+        pub title: String,
+        pub author: Option<Author>,
+        pub is_draft: bool,
+        pub updated_at: DateTime,
+        #[arguments(first = 100)]
+        pub labels: Option<LabelConnection>,
+        #[arguments(first = 100)]
+        pub assignees: UserConnection,
+    }
+
+    #[derive(cynic::InlineFragments, Debug)]
+    #[cynic(graphql_type = "Actor")]
+    pub enum Author {
+        User(User),
+
+        #[cynic(fallback)]
+        Other,
+    }
+}
+
 pub mod project_items {
     use super::queries::{Date, PageInfo, Uri};
     use super::schema;
@@ -4,6 +4,7 @@ use crate::token::Tokenizer;
 use regex::Regex;
 
 pub mod assign;
+pub mod behind;
 pub mod close;
 pub mod concern;
 pub mod nominate;
@@ -11,6 +12,7 @@ pub mod note;
 pub mod ping;
 pub mod prioritize;
 pub mod relabel;
+pub mod relnotes;
 pub mod second;
 pub mod shortcut;
 pub mod transfer;
@@ -28,6 +30,8 @@ pub enum Command<'a> {
     Note(Result<note::NoteCommand, Error<'a>>),
     Concern(Result<concern::ConcernCommand, Error<'a>>),
     Transfer(Result<transfer::TransferCommand, Error<'a>>),
+    Behind(Result<behind::BehindCommand, Error<'a>>),
+    Relnotes(Result<relnotes::RelnotesCommand, Error<'a>>),
 }
 
 #[derive(Debug)]
@@ -139,6 +143,16 @@ impl<'a> Input<'a> {
             Command::Transfer,
             &original_tokenizer,
         ));
+        success.extend(parse_single_command(
+            behind::BehindCommand::parse,
+            Command::Behind,
+            &original_tokenizer,
+        ));
+        success.extend(parse_single_command(
+            relnotes::RelnotesCommand::parse,
+            Command::Relnotes,
+            &original_tokenizer,
+        ));
 
         if success.len() > 1 {
             panic!(
@@ -215,6 +229,8 @@ impl<'a> Command<'a> {
             Command::Note(r) => r.is_ok(),
             Command::Concern(r) => r.is_ok(),
             Command::Transfer(r) => r.is_ok(),
+            Command::Behind(r) => r.is_ok(),
+            Command::Relnotes(r) => r.is_ok(),
         }
     }
 
@@ -323,7 +339,9 @@ fn review_commands() {
         assert_eq!(
             input.next(),
             Some(Command::Assign(Ok(assign::AssignCommand::RequestReview {
-                name: name.to_string()
+                name: name.to_string(),
+                others: vec![],
+                shadow: None,
             })))
         );
         assert_eq!(input.next(), None);
@@ -5,7 +5,20 @@
 //! The grammar is as follows:
 //!
 //! ```text
-//! Command: `@bot claim`, `@bot release-assignment`, or `@bot assign @user`.
+//! Command: `@bot claim [--force]`, `@bot release-assignment`, or `@bot assign @user`.
+//! Review: `r? @user [shadow @user]`.
+//! Decline: `@bot not-me`.
+//! Review proposal: `@bot accept` or `@bot pass`.
+//! Suggestion: `@bot suggest-reviewer`.
+//! Rollup: `@bot assign rollup`.
+//! Next: `@bot assign next`.
+//! Away: `@bot away "message" [until YYYY-MM-DD]` or `@bot back`.
+//! Vacation: `@bot vacation YYYY-MM-DD YYYY-MM-DD` or `@bot vacation end`.
+//! Owners: `@bot owners`.
+//! Workqueue: `@bot work show`, `@bot work capacity [@user] N|unlimited`,
+//! `@bot work rotation on|off`, or `@bot work team <team>`.
+//! Owners of a path: `@bot assign? path/to/file.rs`.
+//! Config: `@bot config show assign`.
 //! ```
 
 use crate::error::Error;
@@ -14,14 +27,66 @@ use std::fmt;
 
 #[derive(PartialEq, Eq, Debug)]
 pub enum AssignCommand {
-    /// Corresponds to `@bot claim`.
-    Claim,
+    /// Corresponds to `@bot claim [--force]`.
+    ///
+    /// With `--force`, a reviewer can claim a PR beyond their review capacity.
+    Claim { force: bool },
     /// Corresponds to `@bot release-assignment` or `@bot unclaim`.
     ReleaseAssignment,
+    /// Corresponds to `@bot not-me`.
+    NotMe,
+    /// Corresponds to `@bot accept`, to accept a review proposal.
+    Accept,
+    /// Corresponds to `@bot pass`, to decline a review proposal.
+    Pass,
+    /// Corresponds to `@bot suggest-reviewer`.
+    SuggestReview,
     /// Corresponds to `@bot assign @user`.
     AssignUser { username: String },
-    /// Corresponds to `r? [@]user`.
-    RequestReview { name: String },
+    /// Corresponds to `r? [@]user [@user...] [shadow [@]user]`.
+    ///
+    /// The `others` reviewers are assigned together with `name`. The
+    /// optional shadow reviewer observes the review, e.g. to learn from it,
+    /// without being assigned.
+    RequestReview {
+        name: String,
+        others: Vec<String>,
+        shadow: Option<String>,
+    },
+    /// Corresponds to `@bot away "message" [until date]`.
+    Away {
+        message: String,
+        until: Option<String>,
+    },
+    /// Corresponds to `@bot back`.
+    Back,
+    /// Corresponds to `@bot vacation start end`.
+    Vacation { start: String, end: String },
+    /// Corresponds to `@bot vacation end`.
+    VacationEnd,
+    /// Corresponds to `@bot owners`.
+    Owners,
+    /// Corresponds to `@bot assign? path`.
+    PathOwners { path: String },
+    /// Corresponds to `@bot assign rollup`.
+    Rollup,
+    /// Corresponds to `@bot assign next`.
+    Next,
+    /// Corresponds to `@bot config show assign`.
+    ShowConfig,
+    /// Corresponds to `@bot work show`.
+    ShowWork,
+    /// Corresponds to `@bot work capacity [@user] N|unlimited`.
+    ///
+    /// `capacity` is `None` for an unlimited capacity.
+    SetCapacity {
+        user: Option<String>,
+        capacity: Option<u32>,
+    },
+    /// Corresponds to `@bot work rotation on|off`.
+    SetRotation { on_rotation: bool },
+    /// Corresponds to `@bot work team <team>`.
+    ShowTeamWork { team: String },
 }
 
 #[derive(PartialEq, Eq, Debug)]
@@ -29,6 +94,15 @@ pub enum ParseError {
     ExpectedEnd,
     MentionUser,
     NoUser,
+    NoAwayMessage,
+    NoAwayDate,
+    NoVacationDates,
+    NoConfigSection,
+    NoShadow,
+    NoPath,
+    InvalidCapacity,
+    NoRotationMode,
+    NoTeam,
 }
 
 impl std::error::Error for ParseError {}
@@ -39,6 +113,22 @@ impl fmt::Display for ParseError {
             ParseError::MentionUser => write!(f, "user should start with @"),
             ParseError::ExpectedEnd => write!(f, "expected end of command"),
             ParseError::NoUser => write!(f, "specify user to assign to"),
+            ParseError::NoAwayMessage => write!(f, "specify a quoted away message"),
+            ParseError::NoAwayDate => write!(f, "specify the date after `until`"),
+            ParseError::NoVacationDates => {
+                write!(f, "specify the first and last days of the vacation")
+            }
+            ParseError::NoConfigSection => {
+                write!(f, "only `config show assign` is supported")
+            }
+            ParseError::NoShadow => write!(f, "specify the user shadowing the review"),
+            ParseError::NoPath => write!(f, "specify the path to look up after `assign?`"),
+            ParseError::InvalidCapacity => write!(
+                f,
+                "specify the review capacity as a non-negative number or `unlimited`"
+            ),
+            ParseError::NoRotationMode => write!(f, "specify `on` or `off` after `work rotation`"),
+            ParseError::NoTeam => write!(f, "specify the team name after `work team`"),
         }
     }
 }
@@ -48,15 +138,48 @@ impl AssignCommand {
         let mut toks = input.clone();
         if let Some(Token::Word("claim")) = toks.peek_token()? {
             toks.next_token()?;
+            let force = toks.eat_token(Token::Word("--force"))?;
             if let Some(Token::Dot) | Some(Token::EndOfLine) = toks.peek_token()? {
                 toks.next_token()?;
                 *input = toks;
-                return Ok(Some(AssignCommand::Claim));
+                return Ok(Some(AssignCommand::Claim { force }));
             } else {
                 return Err(toks.error(ParseError::ExpectedEnd));
             }
         } else if let Some(Token::Word("assign")) = toks.peek_token()? {
             toks.next_token()?;
+            if let Some(Token::Question) = toks.peek_token()? {
+                toks.next_token()?;
+                // Paths contain dots, so the rest of the line is taken as is.
+                let path = toks.take_line()?.trim().trim_matches('`');
+                if path.is_empty() {
+                    return Err(toks.error(ParseError::NoPath));
+                }
+                *input = toks;
+                return Ok(Some(AssignCommand::PathOwners {
+                    path: path.to_owned(),
+                }));
+            }
+            if let Some(Token::Word("rollup")) = toks.peek_token()? {
+                toks.next_token()?;
+                if let Some(Token::Dot) | Some(Token::EndOfLine) = toks.peek_token()? {
+                    toks.next_token()?;
+                    *input = toks;
+                    return Ok(Some(AssignCommand::Rollup));
+                } else {
+                    return Err(toks.error(ParseError::ExpectedEnd));
+                }
+            }
+            if let Some(Token::Word("next")) = toks.peek_token()? {
+                toks.next_token()?;
+                if let Some(Token::Dot) | Some(Token::EndOfLine) = toks.peek_token()? {
+                    toks.next_token()?;
+                    *input = toks;
+                    return Ok(Some(AssignCommand::Next));
+                } else {
+                    return Err(toks.error(ParseError::ExpectedEnd));
+                }
+            }
             if let Some(Token::Word(user)) = toks.next_token()? {
                 if user.starts_with('@') && user.len() != 1 {
                     Ok(Some(AssignCommand::AssignUser {
@@ -77,6 +200,153 @@ impl AssignCommand {
             } else {
                 return Err(toks.error(ParseError::ExpectedEnd));
             }
+        } else if let Some(Token::Word("not-me")) = toks.peek_token()? {
+            toks.next_token()?;
+            if let Some(Token::Dot) | Some(Token::EndOfLine) = toks.peek_token()? {
+                toks.next_token()?;
+                *input = toks;
+                return Ok(Some(AssignCommand::NotMe));
+            } else {
+                return Err(toks.error(ParseError::ExpectedEnd));
+            }
+        } else if let Some(Token::Word("suggest-reviewer")) = toks.peek_token()? {
+            toks.next_token()?;
+            if let Some(Token::Dot) | Some(Token::EndOfLine) = toks.peek_token()? {
+                toks.next_token()?;
+                *input = toks;
+                return Ok(Some(AssignCommand::SuggestReview));
+            } else {
+                return Err(toks.error(ParseError::ExpectedEnd));
+            }
+        } else if let Some(Token::Word(word @ ("accept" | "pass"))) = toks.peek_token()? {
+            toks.next_token()?;
+            if let Some(Token::Dot) | Some(Token::EndOfLine) = toks.peek_token()? {
+                toks.next_token()?;
+                *input = toks;
+                return Ok(Some(if word == "accept" {
+                    AssignCommand::Accept
+                } else {
+                    AssignCommand::Pass
+                }));
+            } else {
+                return Err(toks.error(ParseError::ExpectedEnd));
+            }
+        } else if let Some(Token::Word("away")) = toks.peek_token()? {
+            toks.next_token()?;
+            let message = match toks.next_token()? {
+                Some(Token::Quote(message)) if !message.trim().is_empty() => message.to_owned(),
+                _ => return Err(toks.error(ParseError::NoAwayMessage)),
+            };
+            let until = if let Some(Token::Word("until")) = toks.peek_token()? {
+                toks.next_token()?;
+                match toks.next_token()? {
+                    Some(Token::Word(date)) => Some(date.to_owned()),
+                    _ => return Err(toks.error(ParseError::NoAwayDate)),
+                }
+            } else {
+                None
+            };
+            *input = toks;
+            return Ok(Some(AssignCommand::Away { message, until }));
+        } else if let Some(Token::Word("vacation")) = toks.peek_token()? {
+            toks.next_token()?;
+            let command = match (toks.next_token()?, toks.peek_token()?) {
+                (Some(Token::Word("end")), Some(Token::Dot) | Some(Token::EndOfLine)) => {
+                    AssignCommand::VacationEnd
+                }
+                (Some(Token::Word(start)), Some(Token::Word(end))) => {
+                    toks.next_token()?;
+                    AssignCommand::Vacation {
+                        start: start.to_owned(),
+                        end: end.to_owned(),
+                    }
+                }
+                _ => return Err(toks.error(ParseError::NoVacationDates)),
+            };
+            if let Some(Token::Dot) | Some(Token::EndOfLine) = toks.peek_token()? {
+                toks.next_token()?;
+                *input = toks;
+                return Ok(Some(command));
+            } else {
+                return Err(toks.error(ParseError::ExpectedEnd));
+            }
+        } else if let Some(Token::Word("back")) = toks.peek_token()? {
+            toks.next_token()?;
+            if let Some(Token::Dot) | Some(Token::EndOfLine) = toks.peek_token()? {
+                toks.next_token()?;
+                *input = toks;
+                return Ok(Some(AssignCommand::Back));
+            } else {
+                return Err(toks.error(ParseError::ExpectedEnd));
+            }
+        } else if let Some(Token::Word("owners")) = toks.peek_token()? {
+            toks.next_token()?;
+            if let Some(Token::Dot) | Some(Token::EndOfLine) = toks.peek_token()? {
+                toks.next_token()?;
+                *input = toks;
+                return Ok(Some(AssignCommand::Owners));
+            } else {
+                return Err(toks.error(ParseError::ExpectedEnd));
+            }
+        } else if let Some(Token::Word("work")) = toks.peek_token()? {
+            toks.next_token()?;
+            let command = match toks.next_token()? {
+                Some(Token::Word("show")) => AssignCommand::ShowWork,
+                Some(Token::Word("capacity")) => {
+                    let mut user = None;
+                    if let Some(Token::Word(name)) = toks.peek_token()? {
+                        if let Some(name) = name.strip_prefix('@') {
+                            user = Some(name.to_owned());
+                            toks.next_token()?;
+                        }
+                    }
+                    let capacity = match toks.next_token()? {
+                        Some(Token::Word("unlimited")) => None,
+                        Some(Token::Word(capacity)) => match capacity.parse::<u32>() {
+                            // The capacity is stored as a signed 32-bit integer.
+                            Ok(capacity) if i32::try_from(capacity).is_ok() => Some(capacity),
+                            _ => return Err(toks.error(ParseError::InvalidCapacity)),
+                        },
+                        _ => return Err(toks.error(ParseError::InvalidCapacity)),
+                    };
+                    AssignCommand::SetCapacity { user, capacity }
+                }
+                Some(Token::Word("rotation")) => match toks.next_token()? {
+                    Some(Token::Word("on")) => AssignCommand::SetRotation { on_rotation: true },
+                    Some(Token::Word("off")) => AssignCommand::SetRotation { on_rotation: false },
+                    _ => return Err(toks.error(ParseError::NoRotationMode)),
+                },
+                Some(Token::Word("team")) => match toks.next_token()? {
+                    Some(Token::Word(team)) => AssignCommand::ShowTeamWork {
+                        team: team.to_owned(),
+                    },
+                    _ => return Err(toks.error(ParseError::NoTeam)),
+                },
+                _ => return Ok(None),
+            };
+            if let Some(Token::Dot) | Some(Token::EndOfLine) = toks.peek_token()? {
+                toks.next_token()?;
+                *input = toks;
+                return Ok(Some(command));
+            } else {
+                return Err(toks.error(ParseError::ExpectedEnd));
+            }
+        } else if let Some(Token::Word("config")) = toks.peek_token()? {
+            toks.next_token()?;
+            let section = match toks.next_token()? {
+                Some(Token::Word("show")) => toks.next_token()?,
+                _ => None,
+            };
+            if let Some(Token::Word("assign")) = section {
+                if let Some(Token::Dot) | Some(Token::EndOfLine) = toks.peek_token()? {
+                    toks.next_token()?;
+                    *input = toks;
+                    return Ok(Some(AssignCommand::ShowConfig));
+                } else {
+                    return Err(toks.error(ParseError::ExpectedEnd));
+                }
+            }
+            return Err(toks.error(ParseError::NoConfigSection));
         } else {
             return Ok(None);
         }
@@ -90,7 +360,33 @@ impl AssignCommand {
                 if name.is_empty() {
                     return Err(input.error(ParseError::NoUser));
                 }
-                Ok(Some(AssignCommand::RequestReview { name }))
+                // Additional reviewers need an `@`, so that `r? user` can be
+                // followed by regular text.
+                let mut others = Vec::new();
+                while let Ok(Some(Token::Word(other))) = input.peek_token() {
+                    match other.strip_prefix('@') {
+                        Some(other) if !other.is_empty() => {
+                            others.push(other.to_string());
+                            input.next_token()?;
+                        }
+                        _ => break,
+                    }
+                }
+                let mut shadow = None;
+                if let Ok(Some(Token::Word("shadow"))) = input.peek_token() {
+                    input.next_token()?;
+                    match input.next_token()? {
+                        Some(Token::Word(user)) if !user.trim_start_matches('@').is_empty() => {
+                            shadow = Some(user.trim_start_matches('@').to_string());
+                        }
+                        _ => return Err(input.error(ParseError::NoShadow)),
+                    }
+                }
+                Ok(Some(AssignCommand::RequestReview {
+                    name,
+                    others,
+                    shadow,
+                }))
             }
             _ => Err(input.error(ParseError::NoUser)),
         }
@@ -108,12 +404,35 @@ mod tests {
 
     #[test]
     fn test_1() {
-        assert_eq!(parse("claim."), Ok(Some(AssignCommand::Claim)),);
+        assert_eq!(
+            parse("claim."),
+            Ok(Some(AssignCommand::Claim { force: false })),
+        );
     }
 
     #[test]
     fn test_2() {
-        assert_eq!(parse("claim"), Ok(Some(AssignCommand::Claim)),);
+        assert_eq!(
+            parse("claim"),
+            Ok(Some(AssignCommand::Claim { force: false })),
+        );
+    }
+
+    #[test]
+    fn claim_force() {
+        assert_eq!(
+            parse("claim --force"),
+            Ok(Some(AssignCommand::Claim { force: true })),
+        );
+        use std::error::Error;
+        assert_eq!(
+            parse("claim --forced")
+                .unwrap_err()
+                .source()
+                .unwrap()
+                .downcast_ref(),
+            Some(&ParseError::ExpectedEnd),
+        );
     }
 
     #[test]
@@ -159,13 +478,59 @@ mod tests {
             assert_eq!(
                 parse_review(input),
                 Ok(Some(AssignCommand::RequestReview {
-                    name: name.to_string()
+                    name: name.to_string(),
+                    others: vec![],
+                    shadow: None,
                 })),
                 "failed on {input}"
             );
         }
     }
 
+    #[test]
+    fn review_with_shadow() {
+        assert_eq!(
+            parse_review("@mentor shadow @newbie"),
+            Ok(Some(AssignCommand::RequestReview {
+                name: "mentor".to_string(),
+                others: vec![],
+                shadow: Some("newbie".to_string()),
+            })),
+        );
+        use std::error::Error;
+        for input in ["@mentor shadow", "@mentor shadow @"] {
+            assert_eq!(
+                parse_review(input)
+                    .unwrap_err()
+                    .source()
+                    .unwrap()
+                    .downcast_ref(),
+                Some(&ParseError::NoShadow),
+                "failed on {input}"
+            )
+        }
+    }
+
+    #[test]
+    fn review_several_names() {
+        assert_eq!(
+            parse_review("@alice @bob rust-lang/compiler"),
+            Ok(Some(AssignCommand::RequestReview {
+                name: "alice".to_string(),
+                others: vec!["bob".to_string()],
+                shadow: None,
+            })),
+        );
+        assert_eq!(
+            parse_review("alice @bob @carol shadow @newbie"),
+            Ok(Some(AssignCommand::RequestReview {
+                name: "alice".to_string(),
+                others: vec!["bob".to_string(), "carol".to_string()],
+                shadow: Some("newbie".to_string()),
+            })),
+        );
+    }
+
     #[test]
     fn review_names_errs() {
         use std::error::Error;
@@ -186,4 +551,259 @@ mod tests {
     fn unclaim() {
         assert_eq!(parse("unclaim"), Ok(Some(AssignCommand::ReleaseAssignment)));
     }
+
+    #[test]
+    fn not_me() {
+        assert_eq!(parse("not-me"), Ok(Some(AssignCommand::NotMe)));
+        assert_eq!(parse("not-me."), Ok(Some(AssignCommand::NotMe)));
+    }
+
+    #[test]
+    fn away() {
+        assert_eq!(
+            parse(r#"away "back Monday""#),
+            Ok(Some(AssignCommand::Away {
+                message: "back Monday".to_owned(),
+                until: None,
+            }))
+        );
+        assert_eq!(
+            parse(r#"away "on vacation" until 2025-06-02"#),
+            Ok(Some(AssignCommand::Away {
+                message: "on vacation".to_owned(),
+                until: Some("2025-06-02".to_owned()),
+            }))
+        );
+    }
+
+    #[test]
+    fn away_errs() {
+        use std::error::Error;
+        for (input, error) in [
+            ("away", ParseError::NoAwayMessage),
+            ("away back Monday", ParseError::NoAwayMessage),
+            (r#"away """#, ParseError::NoAwayMessage),
+            (r#"away "back Monday" until"#, ParseError::NoAwayDate),
+        ] {
+            assert_eq!(
+                parse(input).unwrap_err().source().unwrap().downcast_ref(),
+                Some(&error),
+                "failed on {input}"
+            );
+        }
+    }
+
+    #[test]
+    fn vacation() {
+        assert_eq!(
+            parse("vacation 2025-08-01 2025-08-14"),
+            Ok(Some(AssignCommand::Vacation {
+                start: "2025-08-01".to_owned(),
+                end: "2025-08-14".to_owned(),
+            }))
+        );
+        assert_eq!(parse("vacation end"), Ok(Some(AssignCommand::VacationEnd)));
+        use std::error::Error;
+        for (input, error) in [
+            ("vacation", ParseError::NoVacationDates),
+            ("vacation 2025-08-01", ParseError::NoVacationDates),
+            (
+                "vacation 2025-08-01 2025-08-14 2025-08-15",
+                ParseError::ExpectedEnd,
+            ),
+        ] {
+            assert_eq!(
+                parse(input).unwrap_err().source().unwrap().downcast_ref(),
+                Some(&error),
+                "failed on {input}"
+            );
+        }
+    }
+
+    #[test]
+    fn back() {
+        assert_eq!(parse("back"), Ok(Some(AssignCommand::Back)));
+    }
+
+    #[test]
+    fn show_work() {
+        assert_eq!(parse("work show"), Ok(Some(AssignCommand::ShowWork)));
+        assert_eq!(parse("work show."), Ok(Some(AssignCommand::ShowWork)));
+        assert_eq!(parse("work on it"), Ok(None));
+    }
+
+    #[test]
+    fn set_rotation() {
+        assert_eq!(
+            parse("work rotation off"),
+            Ok(Some(AssignCommand::SetRotation { on_rotation: false }))
+        );
+        assert_eq!(
+            parse("work rotation on."),
+            Ok(Some(AssignCommand::SetRotation { on_rotation: true }))
+        );
+        use std::error::Error;
+        assert_eq!(
+            parse("work rotation maybe")
+                .unwrap_err()
+                .source()
+                .unwrap()
+                .downcast_ref(),
+            Some(&ParseError::NoRotationMode),
+        );
+    }
+
+    #[test]
+    fn show_team_work() {
+        assert_eq!(
+            parse("work team compiler"),
+            Ok(Some(AssignCommand::ShowTeamWork {
+                team: "compiler".to_owned()
+            }))
+        );
+        assert_eq!(
+            parse("work team rust-lang/types."),
+            Ok(Some(AssignCommand::ShowTeamWork {
+                team: "rust-lang/types".to_owned()
+            }))
+        );
+        use std::error::Error;
+        assert_eq!(
+            parse("work team")
+                .unwrap_err()
+                .source()
+                .unwrap()
+                .downcast_ref(),
+            Some(&ParseError::NoTeam),
+        );
+    }
+
+    #[test]
+    fn set_capacity() {
+        assert_eq!(
+            parse("work capacity 5"),
+            Ok(Some(AssignCommand::SetCapacity {
+                user: None,
+                capacity: Some(5),
+            }))
+        );
+        assert_eq!(
+            parse("work capacity @octocat unlimited."),
+            Ok(Some(AssignCommand::SetCapacity {
+                user: Some("octocat".to_owned()),
+                capacity: None,
+            }))
+        );
+        use std::error::Error;
+        for input in [
+            "work capacity",
+            "work capacity -1",
+            "work capacity lots",
+            "work capacity 2147483648",
+        ] {
+            assert_eq!(
+                parse(input).unwrap_err().source().unwrap().downcast_ref(),
+                Some(&ParseError::InvalidCapacity),
+                "failed on {input}"
+            );
+        }
+    }
+
+    #[test]
+    fn suggest_reviewer() {
+        assert_eq!(
+            parse("suggest-reviewer"),
+            Ok(Some(AssignCommand::SuggestReview))
+        );
+        assert_eq!(
+            parse("suggest-reviewer."),
+            Ok(Some(AssignCommand::SuggestReview))
+        );
+    }
+
+    #[test]
+    fn review_proposal() {
+        assert_eq!(parse("accept"), Ok(Some(AssignCommand::Accept)));
+        assert_eq!(parse("pass."), Ok(Some(AssignCommand::Pass)));
+        use std::error::Error;
+        assert_eq!(
+            parse("pass on this")
+                .unwrap_err()
+                .source()
+                .unwrap()
+                .downcast_ref(),
+            Some(&ParseError::ExpectedEnd),
+        );
+    }
+
+    #[test]
+    fn path_owners() {
+        for input in [
+            "assign? compiler/rustc_middle/src/lib.rs",
+            "assign? `compiler/rustc_middle/src/lib.rs`",
+        ] {
+            assert_eq!(
+                parse(input),
+                Ok(Some(AssignCommand::PathOwners {
+                    path: "compiler/rustc_middle/src/lib.rs".to_owned(),
+                })),
+                "failed on {input}"
+            );
+        }
+        use std::error::Error;
+        assert_eq!(
+            parse("assign?")
+                .unwrap_err()
+                .source()
+                .unwrap()
+                .downcast_ref(),
+            Some(&ParseError::NoPath),
+        );
+    }
+
+    #[test]
+    fn owners() {
+        assert_eq!(parse("owners"), Ok(Some(AssignCommand::Owners)));
+        assert_eq!(parse("owners."), Ok(Some(AssignCommand::Owners)));
+    }
+
+    #[test]
+    fn rollup() {
+        assert_eq!(parse("assign rollup"), Ok(Some(AssignCommand::Rollup)));
+        assert_eq!(parse("assign rollup."), Ok(Some(AssignCommand::Rollup)));
+        assert_eq!(
+            parse("assign @rollup"),
+            Ok(Some(AssignCommand::AssignUser {
+                username: "rollup".to_owned()
+            }))
+        );
+    }
+
+    #[test]
+    fn next() {
+        assert_eq!(parse("assign next"), Ok(Some(AssignCommand::Next)));
+        assert_eq!(parse("assign next."), Ok(Some(AssignCommand::Next)));
+        assert_eq!(
+            parse("assign @next"),
+            Ok(Some(AssignCommand::AssignUser {
+                username: "next".to_owned()
+            }))
+        );
+    }
+
+    #[test]
+    fn show_config() {
+        use std::error::Error;
+        assert_eq!(
+            parse("config show assign"),
+            Ok(Some(AssignCommand::ShowConfig))
+        );
+        for input in ["config", "config show", "config show ping"] {
+            assert_eq!(
+                parse(input).unwrap_err().source().unwrap().downcast_ref(),
+                Some(&ParseError::NoConfigSection),
+                "failed on {input}"
+            );
+        }
+    }
 }
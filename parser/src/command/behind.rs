@@ -0,0 +1,124 @@
+//! The behind-upstream command parser.
+//!
+//! Snoozes or re-enables the warning about a PR being based on an old upstream commit,
+//! or overrides the age threshold of the warning for a PR.
+//!
+//! The grammar is as follows:
+//!
+//! ```text
+//! Command: `@bot behind snooze`, `@bot behind unsnooze` or `@bot behind threshold <days>`.
+//! ```
+
+use crate::error::Error;
+use crate::token::{Token, Tokenizer};
+use std::fmt;
+
+#[derive(PartialEq, Eq, Debug)]
+pub enum BehindCommand {
+    /// Corresponds to `@bot behind snooze`.
+    Snooze,
+    /// Corresponds to `@bot behind unsnooze`.
+    Unsnooze,
+    /// Corresponds to `@bot behind threshold <days>`.
+    Threshold(u32),
+}
+
+#[derive(PartialEq, Eq, Debug)]
+pub enum ParseError {
+    ExpectedAction,
+    ExpectedDays,
+    ExpectedEnd,
+}
+
+impl std::error::Error for ParseError {}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ParseError::ExpectedAction => {
+                write!(f, "expected `snooze`, `unsnooze` or `threshold`")
+            }
+            ParseError::ExpectedDays => write!(f, "expected a number of days"),
+            ParseError::ExpectedEnd => write!(f, "expected end of command"),
+        }
+    }
+}
+
+impl BehindCommand {
+    pub fn parse<'a>(input: &mut Tokenizer<'a>) -> Result<Option<Self>, Error<'a>> {
+        let mut toks = input.clone();
+        if let Some(Token::Word("behind")) = toks.peek_token()? {
+            toks.next_token()?;
+            let command = match toks.next_token()? {
+                Some(Token::Word("snooze")) => BehindCommand::Snooze,
+                Some(Token::Word("unsnooze")) => BehindCommand::Unsnooze,
+                Some(Token::Word("threshold")) => match toks.next_token()? {
+                    Some(Token::Word(days)) => match days.parse() {
+                        Ok(days) => BehindCommand::Threshold(days),
+                        Err(_) => return Err(toks.error(ParseError::ExpectedDays)),
+                    },
+                    _ => return Err(toks.error(ParseError::ExpectedDays)),
+                },
+                _ => return Err(toks.error(ParseError::ExpectedAction)),
+            };
+            if let Some(Token::Dot) | Some(Token::EndOfLine) = toks.peek_token()? {
+                toks.next_token()?;
+                *input = toks;
+                Ok(Some(command))
+            } else {
+                Err(toks.error(ParseError::ExpectedEnd))
+            }
+        } else {
+            Ok(None)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse<'a>(input: &'a str) -> Result<Option<BehindCommand>, Error<'a>> {
+        let mut toks = Tokenizer::new(input);
+        BehindCommand::parse(&mut toks)
+    }
+
+    #[test]
+    fn snooze() {
+        assert_eq!(parse("behind snooze"), Ok(Some(BehindCommand::Snooze)));
+        assert_eq!(parse("behind unsnooze."), Ok(Some(BehindCommand::Unsnooze)));
+    }
+
+    #[test]
+    fn threshold() {
+        assert_eq!(
+            parse("behind threshold 30"),
+            Ok(Some(BehindCommand::Threshold(30)))
+        );
+    }
+
+    #[test]
+    fn errors() {
+        use std::error::Error;
+        for (input, error) in [
+            ("behind", ParseError::ExpectedAction),
+            ("behind schedule", ParseError::ExpectedAction),
+            ("behind snooze now", ParseError::ExpectedEnd),
+            ("behind threshold", ParseError::ExpectedDays),
+            ("behind threshold soon", ParseError::ExpectedDays),
+            ("behind threshold -1", ParseError::ExpectedDays),
+            ("behind threshold 30 days", ParseError::ExpectedEnd),
+        ] {
+            assert_eq!(
+                parse(input).unwrap_err().source().unwrap().downcast_ref(),
+                Some(&error),
+                "failed on {input}"
+            );
+        }
+    }
+
+    #[test]
+    fn other_command() {
+        assert_eq!(parse("close"), Ok(None));
+    }
+}
@@ -0,0 +1,107 @@
+//! The release notes command parser.
+//!
+//! Replaces the release notes text of the tracking issue of an issue or PR.
+//!
+//! The grammar is as follows:
+//!
+//! ```text
+//! Command: `@bot relnotes-text`, followed by a fenced code block with the text.
+//! ```
+
+use crate::error::Error;
+use crate::token::{Token, Tokenizer};
+use std::fmt;
+
+#[derive(PartialEq, Eq, Debug)]
+pub struct RelnotesCommand {
+    /// The content of the fenced code block.
+    pub text: String,
+}
+
+#[derive(PartialEq, Eq, Debug)]
+pub enum ParseError {
+    ExpectedText,
+}
+
+impl std::error::Error for ParseError {}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ParseError::ExpectedText => {
+                write!(f, "expected the release notes text in a fenced code block")
+            }
+        }
+    }
+}
+
+impl RelnotesCommand {
+    pub fn parse<'a>(input: &mut Tokenizer<'a>) -> Result<Option<Self>, Error<'a>> {
+        let mut toks = input.clone();
+        if let Some(Token::Word("relnotes-text")) = toks.peek_token()? {
+            toks.next_token()?;
+            match toks.take_fenced_block() {
+                Some(text) => {
+                    *input = toks;
+                    Ok(Some(RelnotesCommand {
+                        text: text.to_string(),
+                    }))
+                }
+                None => Err(toks.error(ParseError::ExpectedText)),
+            }
+        } else {
+            Ok(None)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse<'a>(input: &'a str) -> Result<Option<RelnotesCommand>, Error<'a>> {
+        let mut toks = Tokenizer::new(input);
+        RelnotesCommand::parse(&mut toks)
+    }
+
+    #[test]
+    fn text() {
+        assert_eq!(
+            parse("relnotes-text\n```markdown\n# Libraries\n- [Add `foo`](url)\n```\nThanks!"),
+            Ok(Some(RelnotesCommand {
+                text: "# Libraries\n- [Add `foo`](url)".to_string()
+            }))
+        );
+    }
+
+    #[test]
+    fn longer_fence() {
+        assert_eq!(
+            parse("relnotes-text\r\n````\r\n```rust\r\nfoo();\r\n```\r\n````"),
+            Ok(Some(RelnotesCommand {
+                text: "```rust\r\nfoo();\r\n```".to_string()
+            }))
+        );
+    }
+
+    #[test]
+    fn missing_text() {
+        use std::error::Error;
+        for input in [
+            "relnotes-text",
+            "relnotes-text please",
+            "relnotes-text\n```\nunterminated",
+        ] {
+            assert_eq!(
+                parse(input).unwrap_err().source().unwrap().downcast_ref(),
+                Some(&ParseError::ExpectedText),
+                "failed on {input}"
+            );
+        }
+    }
+
+    #[test]
+    fn other_command() {
+        assert_eq!(parse("relnotes"), Ok(None));
+    }
+}
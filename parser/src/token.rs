@@ -177,6 +177,35 @@ impl<'a> Tokenizer<'a> {
         Ok(self.str_from(start))
     }
 
+    /// Takes a fenced code block starting on one of the next lines, returning
+    /// its content without the fences. Returns `None`, without consuming
+    /// anything, if there is no such block.
+    pub fn take_fenced_block(&mut self) -> Option<&'a str> {
+        let start = self.cur_pos();
+        let rest = &self.input[start..];
+        let block = rest.trim_start();
+        let fence_len = block.len() - block.trim_start_matches('`').len();
+        if fence_len < 3 {
+            return None;
+        }
+        // The first line may hold an info string, e.g. `markdown`.
+        let content_start = block.find('\n')? + 1;
+        let content = &block[content_start..];
+        let mut content_len = 0;
+        for line in content.split_inclusive('\n') {
+            let line_fence = line.trim();
+            if line_fence.len() >= fence_len && line_fence.chars().all(|c| c == '`') {
+                let end = start + (rest.len() - block.len()) + content_start + content_len;
+                while self.cur_pos() < end + line.len() {
+                    self.advance();
+                }
+                return Some(content[..content_len].trim_end_matches(['\r', '\n']));
+            }
+            content_len += line.len();
+        }
+        None
+    }
+
     pub fn peek_token(&mut self) -> Result<Option<Token<'a>>, Error<'a>> {
         self.clone().next_token()
     }
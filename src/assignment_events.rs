@@ -0,0 +1,73 @@
+//! Read-only endpoint listing the latest reviewer assignments of a repository
+//! as JSON, so that they can be fed to external dashboards.
+//!
+//! The events are only kept for a while, see [`AssignmentEventsPruneJob`].
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use axum::{
+    Json,
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+};
+use serde::Deserialize;
+
+use crate::{
+    db::assignment_events::{delete_assignment_events_before, get_recent_assignment_events},
+    handlers::Context,
+    jobs::Job,
+    utils::AppError,
+};
+
+/// Number of events returned when no `limit` is given.
+const DEFAULT_LIMIT: u32 = 100;
+
+/// Maximum number of events returned at once.
+const MAX_LIMIT: u32 = 1000;
+
+/// Number of days the assignment events are kept.
+const RETENTION_DAYS: i64 = 180;
+
+#[derive(Deserialize)]
+pub struct AssignmentEventsQuery {
+    limit: Option<u32>,
+}
+
+pub async fn assignment_events(
+    Path((owner, repo)): Path<(String, String)>,
+    Query(query): Query<AssignmentEventsQuery>,
+    State(ctx): State<Arc<Context>>,
+) -> axum::response::Result<Response, AppError> {
+    // Private repositories must not be revealed by this public endpoint.
+    if !ctx.team.is_public_bot_repo(&owner, &repo).await? {
+        return Ok((
+            StatusCode::NOT_FOUND,
+            format!("triagebot is not used on the public repository `{owner}/{repo}`"),
+        )
+            .into_response());
+    }
+    let limit = query.limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT);
+    let events =
+        get_recent_assignment_events(&*ctx.db.get().await, &format!("{owner}/{repo}"), limit)
+            .await?;
+    Ok(Json(events).into_response())
+}
+
+/// Deletes the assignment events older than [`RETENTION_DAYS`].
+pub struct AssignmentEventsPruneJob;
+
+#[async_trait]
+impl Job for AssignmentEventsPruneJob {
+    fn name(&self) -> &'static str {
+        "assignment_events_prune"
+    }
+
+    async fn run(&self, ctx: &Context, _metadata: &serde_json::Value) -> anyhow::Result<()> {
+        let cutoff = chrono::Utc::now() - chrono::Duration::days(RETENTION_DAYS);
+        let deleted = delete_assignment_events_before(&*ctx.db.get().await, cutoff).await?;
+        tracing::info!("pruned {deleted} assignment events older than {RETENTION_DAYS} days");
+        Ok(())
+    }
+}
@@ -48,6 +48,7 @@ pub(crate) struct Config {
     pub(crate) no_mentions: Option<NoMentionsConfig>,
     pub(crate) behind_upstream: Option<BehindUpstreamConfig>,
     pub(crate) backport: Option<BackportConfig>,
+    pub(crate) relnotes: Option<RelnotesConfig>,
 }
 
 #[derive(PartialEq, Eq, Debug, serde::Deserialize)]
@@ -90,11 +91,11 @@ pub(crate) struct PingTeamConfig {
     pub(crate) label: Option<String>,
 }
 
-#[derive(PartialEq, Eq, Debug, serde::Deserialize)]
+#[derive(PartialEq, Eq, Debug, serde::Deserialize, serde::Serialize)]
 #[serde(deny_unknown_fields)]
 pub(crate) struct AssignReviewPrefsConfig {}
 
-#[derive(PartialEq, Eq, Debug, serde::Deserialize)]
+#[derive(PartialEq, Eq, Debug, serde::Deserialize, serde::Serialize)]
 #[serde(rename_all = "kebab-case")]
 #[serde(deny_unknown_fields)]
 pub(crate) struct AssignCustomMessages {
@@ -106,25 +107,81 @@ pub(crate) struct AssignCustomMessages {
     pub(crate) auto_assign_no_one: String,
 }
 
-#[derive(PartialEq, Eq, Debug, serde::Deserialize)]
+#[derive(PartialEq, Eq, Debug, serde::Deserialize, serde::Serialize)]
 #[serde(deny_unknown_fields)]
 pub(crate) struct AssignConfig {
+    /// Set to `false` to disable the automatic assignment of PRs and the
+    /// welcome messages, without removing the `[assign]` section.
+    #[serde(default = "default_true")]
+    pub(crate) enabled: bool,
+    /// If not empty, PRs are only assigned automatically in these
+    /// repositories (e.g. `rust-lang/rust`), so that forks and mirrors
+    /// sharing the same config are left alone.
+    #[serde(default)]
+    pub(crate) enabled_repos: Vec<String>,
+    /// The PR events that trigger the automatic assignment.
+    #[serde(default = "default_assign_triggers")]
+    pub(crate) trigger_on: Vec<AssignTrigger>,
     /// If enabled, then posts a warning comment if the PR is opened against a
     /// different branch than the default (usually master or main).
     #[serde(default)]
     pub(crate) warn_non_default_branch: WarnNonDefaultBranchConfig,
     /// A URL to include in the welcome message.
     pub(crate) contributing_url: Option<String>,
-    /// Ad-hoc groups that can be referred to in `owners`.
+    /// Ad-hoc groups that can be referred to in `owners`. Each group is either
+    /// a list of members, or a `{ members = [...], ignore_capacity = true }` table.
+    #[serde(default)]
+    pub(crate) adhoc_groups: HashMap<String, AdhocGroup>,
+    /// Aliases of the names that can be requested with `r?` or used in
+    /// `owners`, e.g. `libs = "rust-lang/libs-api"`. They are resolved
+    /// before the teams, groups and users.
     #[serde(default)]
-    pub(crate) adhoc_groups: HashMap<String, Vec<String>>,
+    pub(crate) aliases: HashMap<String, String>,
+    /// Named reviewer rotations that can be referred to in `owners` (or `r?`)
+    /// as `rotation:<name>`, each with its own selection mode.
+    #[serde(default)]
+    pub(crate) rotations: HashMap<String, RotationConfig>,
     /// Users to assign when a new PR is opened.
     /// The key is a gitignore-style path, and the value is a list of
     /// usernames, team names, or ad-hoc groups.
+    /// Team names can be prefixed with the organization (e.g. `rust-lang/wg-llvm`),
+    /// and are expanded into their members, whose capacity is then checked
+    /// like for any other candidate.
+    /// An empty list or `ghost` means that PRs mostly modifying that path
+    /// should not be auto-assigned.
     #[serde(default)]
     pub(crate) owners: HashMap<String, Vec<String>>,
+    /// Users to assign based on the extension of the modified files (e.g. `md`).
+    /// The changes of a file count both for its longest `owners` path and for
+    /// its extension.
+    #[serde(default)]
+    pub(crate) extension_owners: HashMap<String, Vec<String>>,
+    /// Reviewers to assign to PRs with a given label (e.g. `A-diagnostics`),
+    /// regardless of the modified files.
+    #[serde(default)]
+    pub(crate) label_owners: HashMap<String, Vec<String>>,
+    /// PRs with any of these labels (e.g. `S-blocked`) are not assigned when
+    /// they are opened, but once the last of them is removed.
+    #[serde(default)]
+    pub(crate) blocking_labels: Vec<String>,
+    /// PRs with this label (e.g. `rollup`) are never assigned nor welcomed
+    /// automatically, like with `r? ghost`.
+    pub(crate) no_assign_label: Option<String>,
+    /// Name of the owners files committed in the repository (e.g. `OWNERS`).
+    /// If set, the reviewers listed in the nearest such file of a modified
+    /// file take precedence over `owners` for that subtree.
+    pub(crate) owners_file: Option<String>,
+    /// Whether to also read the `owners` patterns from the `CODEOWNERS` file
+    /// of the repository. Patterns set in `owners` take precedence.
+    #[serde(default)]
+    pub(crate) use_codeowners: bool,
     #[serde(default)]
     pub(crate) users_on_vacation: HashSet<String>,
+    /// Relative chances of reviewers to be picked among the valid candidates,
+    /// e.g. `alice = 3` makes `alice` three times more likely to be picked
+    /// than reviewers without a weight, which default to 1.
+    #[serde(default)]
+    pub(crate) reviewer_weights: HashMap<String, u32>,
     /// Should review preferences be taken into account when deciding who to assign to a PR?
     #[serde(default)]
     pub(crate) review_prefs: Option<AssignReviewPrefsConfig>,
@@ -132,9 +189,166 @@ pub(crate) struct AssignConfig {
     #[serde(default)]
     #[serde(alias = "custom_welcome_messages")]
     pub(crate) custom_messages: Option<AssignCustomMessages>,
+    /// How to choose a reviewer among the valid candidates.
+    #[serde(default)]
+    pub(crate) selection_mode: ReviewerSelectionMode,
+    /// When the PR author is a member of a team owning the modified files,
+    /// pick a reviewer from the other matched owners or the fallback group.
+    #[serde(default)]
+    pub(crate) cross_team_review: bool,
+    /// Also pick reviewers from the subteams of the requested rust-lang teams,
+    /// e.g. from `types` when requesting `compiler`.
+    #[serde(default)]
+    pub(crate) expand_subteams: bool,
+    /// Set to `false` to forbid PR authors from requesting their own review
+    /// with `r?`. A reviewer is then picked from the modified files instead.
+    #[serde(default = "default_true")]
+    pub(crate) allow_self_review: bool,
+    /// What to do when the PR body contains several `r?` commands.
+    #[serde(default)]
+    pub(crate) multiple_review_requests: MultipleReviewRequests,
+    /// Mention in the welcome message how many PRs the automatically chosen
+    /// reviewer is currently assigned to.
+    #[serde(default)]
+    pub(crate) welcome_assigned_pr_count: bool,
+    /// Mention the size of the PR diff in the welcome message.
+    #[serde(default)]
+    pub(crate) welcome_diff_size: bool,
+    /// How to welcome PRs that were assigned automatically.
+    #[serde(default)]
+    pub(crate) welcome_style: WelcomeStyle,
+    /// Only post the full welcome message on the first PR of a new
+    /// contributor in the repository, and a short one on their next PRs.
+    #[serde(default)]
+    pub(crate) welcome_once: bool,
+    /// Authors of PRs that are never welcomed, typically bots. `*` matches
+    /// any characters, e.g. `*[bot]`.
+    #[serde(default)]
+    pub(crate) ignore_authors: Vec<String>,
+    /// Don't assign the PRs of `ignore_authors` automatically either.
+    #[serde(default)]
+    pub(crate) ignore_authors_assignment: bool,
+    /// If set, PRs whose most modified `owners` area accounts for less than
+    /// this percentage of the changes are not assigned automatically. The
+    /// `T-*` labels of all the modified areas are added instead, and the areas
+    /// are listed in a comment.
+    pub(crate) multi_area_threshold_percent: Option<u32>,
+    /// Maximum number of `T-*` labels added to a PR spanning several areas.
+    /// The labels of the most modified areas are kept, the other teams are
+    /// only mentioned in the comment.
+    pub(crate) max_team_labels: Option<usize>,
+    /// If set, reviewers with an assigned PR that wasn't updated for this many
+    /// days are not assigned new PRs. Requires `review_prefs`.
+    pub(crate) stale_review_days: Option<u32>,
+    /// Review capacity of the reviewers without review preferences, which
+    /// otherwise have an unlimited capacity. Requires `review_prefs`.
+    pub(crate) default_capacity: Option<u32>,
+    /// If a PR modifies more than this many lines, reviewers are selected by
+    /// the number of modified files they own, without counting lines.
+    pub(crate) max_diff_lines: Option<u32>,
+    /// Reviewers picked automatically are not assigned directly, they are
+    /// asked to confirm with `@rustbot accept` first, or to `@rustbot pass`.
+    #[serde(default)]
+    pub(crate) require_acceptance: bool,
+    /// Number of days after which a reviewer who did not answer a review
+    /// proposal is replaced. Defaults to 3 days.
+    pub(crate) acceptance_timeout_days: Option<u32>,
+    /// If set, PRs waiting on review whose reviewer has neither commented nor
+    /// been assigned for this many days are reassigned to another reviewer
+    /// picked from the modified files. The repository must also be listed in
+    /// the `AUTO_REASSIGN_REPOS` environment variable of the bot.
+    pub(crate) auto_reassign_days: Option<u32>,
+    /// Label of the PRs waiting on review, e.g. `S-waiting-on-review`. If set,
+    /// only the PRs with this label are reassigned with `auto_reassign_days`,
+    /// otherwise all the open non-draft PRs are.
+    pub(crate) waiting_on_review_label: Option<String>,
+}
+
+#[derive(PartialEq, Eq, Debug, Default, Clone, Copy, serde::Deserialize, serde::Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub(crate) enum ReviewerSelectionMode {
+    /// Pick a random reviewer.
+    #[default]
+    Random,
+    /// Pick the reviewer who has been the longest without any activity on
+    /// the pull requests assigned to them.
+    LeastRecentlyActive,
+    /// Assign the members of the requested teams strictly in turn, regardless
+    /// of their review capacity. Reviewers off rotation are still skipped.
+    StrictAlternate,
+    /// Pick the reviewer with the fewest assigned PRs. Requires `review_prefs`,
+    /// reviewers without review preferences are considered to have none.
+    LeastAssigned,
+    /// Pick the reviewer who was assigned a PR the least recently. Unlike
+    /// `strict-alternate`, the review capacity is still taken into account.
+    RoundRobin,
+    /// Pick a random reviewer, with a probability inversely proportional to
+    /// their number of assigned PRs plus one. Requires `review_prefs`,
+    /// reviewers without review preferences are considered to have none.
+    LoadBalance,
+}
+
+#[derive(PartialEq, Eq, Debug, serde::Deserialize, serde::Serialize)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct RotationConfig {
+    /// Usernames, team names, or ad-hoc groups of the rotation.
+    pub(crate) reviewers: Vec<String>,
+    /// How to choose a reviewer among the valid candidates of the rotation.
+    #[serde(default)]
+    pub(crate) selection_mode: ReviewerSelectionMode,
+}
+
+#[derive(PartialEq, Eq, Debug, Clone, Copy, serde::Deserialize, serde::Serialize)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum AssignTrigger {
+    /// A PR is opened. Draft PRs are only assigned if they contain an `r?`
+    /// command.
+    Opened,
+    /// A draft PR is marked as ready for review, and has no assignee.
+    ReadyForReview,
+    /// A closed PR is reopened, and has no assignee.
+    Reopened,
+}
+
+fn default_assign_triggers() -> Vec<AssignTrigger> {
+    vec![AssignTrigger::Opened, AssignTrigger::ReadyForReview]
+}
+
+#[derive(PartialEq, Eq, Debug, Default, Clone, Copy, serde::Deserialize, serde::Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub(crate) enum WelcomeStyle {
+    /// Post a welcome comment.
+    #[default]
+    Comment,
+    /// Only add a reaction to the PR, as a receipt of the assignment.
+    Reaction,
+    /// Assign silently.
+    None,
+}
+
+#[derive(PartialEq, Eq, Debug, Default, Clone, Copy, serde::Deserialize, serde::Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub(crate) enum MultipleReviewRequests {
+    /// Use the first `r?` command.
+    #[default]
+    FirstWins,
+    /// Use the last `r?` command.
+    LastWins,
+    /// Ignore all of them, and ask the PR author to pick one.
+    Error,
 }
 
 impl AssignConfig {
+    /// Whether PRs of the `repo` repository should be assigned automatically.
+    pub(crate) fn is_enabled_for(&self, repo: &str) -> bool {
+        self.enabled
+            && (self.enabled_repos.is_empty()
+                || self
+                    .enabled_repos
+                    .iter()
+                    .any(|enabled| enabled.eq_ignore_ascii_case(repo)))
+    }
+
     pub(crate) fn is_on_vacation(&self, user: &str) -> bool {
         let name_lower = user.to_lowercase();
         self.users_on_vacation
@@ -142,14 +356,76 @@ impl AssignConfig {
             .any(|vacationer| name_lower == vacationer.to_lowercase())
     }
 
+    /// Returns the `reviewer_weights` entry of `user`, or 1 if there is none.
+    pub(crate) fn reviewer_weight(&self, user: &str) -> u32 {
+        self.reviewer_weights
+            .iter()
+            .find(|(name, _)| name.eq_ignore_ascii_case(user))
+            .map_or(1, |(_, weight)| *weight)
+    }
+
+    /// Returns the rotation referred to by `name`, e.g. `rotation:compiler-fast`.
+    pub(crate) fn rotation(&self, name: &str) -> Option<&RotationConfig> {
+        self.rotations.get(name.strip_prefix("rotation:")?)
+    }
+
+    /// Returns how to choose a reviewer when reviewers are requested from `names`.
+    ///
+    /// Requests only made of rotations with the same selection mode use that
+    /// mode, other requests use the `selection_mode` of the config.
+    pub(crate) fn selection_mode_for(&self, names: &[String]) -> ReviewerSelectionMode {
+        let mut modes = names
+            .iter()
+            .map(|name| self.rotation(name).map(|rotation| rotation.selection_mode));
+        match modes.next() {
+            Some(Some(mode)) if modes.all(|m| m == Some(mode)) => mode,
+            _ => self.selection_mode,
+        }
+    }
+
     /// Return a "fallback" adhoc group, which is used for assigning reviewers if no other
     /// reviewer was found.
     pub(crate) fn fallback_review_group(&self) -> Option<&[String]> {
-        self.adhoc_groups.get("fallback").map(|v| v.as_slice())
+        self.adhoc_groups
+            .get("fallback")
+            .map(|group| group.members())
     }
 }
 
-#[derive(PartialEq, Eq, Debug, serde::Deserialize)]
+/// An ad-hoc group of reviewers, either a list of members or a table.
+#[derive(PartialEq, Eq, Debug, serde::Deserialize, serde::Serialize)]
+#[serde(deny_unknown_fields)]
+#[serde(untagged)]
+pub(crate) enum AdhocGroup {
+    Simple(Vec<String>),
+    Extended {
+        members: Vec<String>,
+        /// Members reached through this group are assigned regardless of
+        /// their review capacity, e.g. for triage rotations.
+        #[serde(default)]
+        ignore_capacity: bool,
+    },
+}
+
+impl AdhocGroup {
+    /// Usernames, team names or other groups in this group.
+    pub(crate) fn members(&self) -> &[String] {
+        match self {
+            AdhocGroup::Simple(members) | AdhocGroup::Extended { members, .. } => members,
+        }
+    }
+
+    pub(crate) fn ignore_capacity(&self) -> bool {
+        match self {
+            AdhocGroup::Simple(_) => false,
+            AdhocGroup::Extended {
+                ignore_capacity, ..
+            } => *ignore_capacity,
+        }
+    }
+}
+
+#[derive(PartialEq, Eq, Debug, serde::Deserialize, serde::Serialize)]
 #[serde(deny_unknown_fields)]
 #[serde(untagged)]
 pub(crate) enum WarnNonDefaultBranchConfig {
@@ -162,7 +438,7 @@ pub(crate) enum WarnNonDefaultBranchConfig {
     },
 }
 
-#[derive(PartialEq, Eq, Debug, serde::Deserialize)]
+#[derive(PartialEq, Eq, Debug, serde::Deserialize, serde::Serialize)]
 #[serde(deny_unknown_fields)]
 pub(crate) struct WarnNonDefaultBranchException {
     /// Substring in the title that match this exception
@@ -525,6 +801,44 @@ pub(crate) struct BehindUpstreamConfig {
     /// The threshold of days for parent commit age to trigger a warning.
     /// Default is 7 days if not specified.
     pub(crate) days_threshold: Option<usize>,
+    /// Whether to @-mention the PR author in the warning.
+    #[serde(default)]
+    pub(crate) mention_author: bool,
+    /// Custom text of the warning. `{days_old}` is replaced by the age in
+    /// days of the merge base commit, and `{default_branch}` by the name of
+    /// the default branch of the repository.
+    #[serde(default, deserialize_with = "behind_upstream_message")]
+    pub(crate) message: Option<String>,
+    /// Label added to the PR while it is behind upstream, e.g. `S-needs-rebase`,
+    /// and removed once it is up to date again.
+    pub(crate) label: Option<String>,
+}
+
+/// The placeholders supported by the `behind-upstream` message.
+const BEHIND_UPSTREAM_PLACEHOLDERS: &[&str] = &["days_old", "default_branch"];
+
+/// Rejects `behind-upstream` messages with unknown placeholders, so that
+/// typos are reported instead of showing up in the comments.
+fn behind_upstream_message<'de, D>(deserializer: D) -> Result<Option<String>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    let message: String = serde::Deserialize::deserialize(deserializer)?;
+    let mut rest = message.as_str();
+    while let Some(start) = rest.find('{') {
+        let Some(len) = rest[start..].find('}') else {
+            break;
+        };
+        let placeholder = &rest[start + 1..start + len];
+        if !BEHIND_UPSTREAM_PLACEHOLDERS.contains(&placeholder) {
+            return Err(serde::de::Error::custom(format!(
+                "unknown placeholder `{{{placeholder}}}`, expected `{{days_old}}` or \
+                 `{{default_branch}}`"
+            )));
+        }
+        rest = &rest[start + len + 1..];
+    }
+    Ok(Some(message))
 }
 
 #[inline]
@@ -551,6 +865,37 @@ pub(crate) struct BackportRuleConfig {
     pub(crate) add_labels: Vec<String>,
 }
 
+/// Configuration for the tracking issues of release notes.
+#[derive(PartialEq, Eq, Debug, serde::Deserialize)]
+#[serde(rename_all = "kebab-case")]
+#[serde(deny_unknown_fields)]
+pub(crate) struct RelnotesConfig {
+    /// Repository where the tracking issues are opened, e.g. `rust-lang/rust`.
+    /// Defaults to the repository of the labeled issue or PR.
+    pub(crate) tracking_repo: Option<String>,
+    /// Labels opening a tracking issue when added. `finished-final-comment-period`
+    /// only does so if the issue is also labeled `disposition-merge`.
+    #[serde(default = "RelnotesConfig::trigger_labels_default")]
+    pub(crate) trigger_labels: Vec<String>,
+}
+
+impl RelnotesConfig {
+    fn trigger_labels_default() -> Vec<String> {
+        ["relnotes", "relnotes-perf", "finished-final-comment-period"]
+            .map(String::from)
+            .to_vec()
+    }
+}
+
+impl Default for RelnotesConfig {
+    fn default() -> Self {
+        Self {
+            tracking_repo: None,
+            trigger_labels: Self::trigger_labels_default(),
+        }
+    }
+}
+
 fn get_cached_config(repo: &str) -> Option<Result<Arc<Config>, ConfigurationError>> {
     let cache = CONFIG_CACHE.read().unwrap();
     cache.get(repo).and_then(|(config, fetch_time)| {
@@ -684,6 +1029,7 @@ mod tests {
 
             [behind-upstream]
             days-threshold = 14
+            label = "S-needs-rebase"
 
             [backport.teamRed]
             required-pr-labels = ["T-libs", "T-libs-api"]
@@ -734,13 +1080,45 @@ mod tests {
                     allow_unauthenticated: vec!["C-*".into()],
                 }),
                 assign: Some(AssignConfig {
+                    enabled: true,
+                    enabled_repos: Vec::new(),
+                    trigger_on: vec![AssignTrigger::Opened, AssignTrigger::ReadyForReview],
                     warn_non_default_branch: WarnNonDefaultBranchConfig::Simple(false),
                     contributing_url: None,
                     adhoc_groups: HashMap::new(),
+                    aliases: HashMap::new(),
+                    rotations: HashMap::new(),
                     owners: HashMap::new(),
+                    extension_owners: HashMap::new(),
+                    label_owners: HashMap::new(),
+                    blocking_labels: Vec::new(),
+                    no_assign_label: None,
+                    owners_file: None,
+                    use_codeowners: false,
                     users_on_vacation: HashSet::from(["jyn514".into()]),
+                    reviewer_weights: HashMap::new(),
                     review_prefs: None,
                     custom_messages: None,
+                    selection_mode: ReviewerSelectionMode::Random,
+                    cross_team_review: false,
+                    expand_subteams: false,
+                    allow_self_review: true,
+                    multiple_review_requests: MultipleReviewRequests::FirstWins,
+                    welcome_assigned_pr_count: false,
+                    welcome_diff_size: false,
+                    welcome_style: WelcomeStyle::Comment,
+                    welcome_once: false,
+                    ignore_authors: Vec::new(),
+                    ignore_authors_assignment: false,
+                    multi_area_threshold_percent: None,
+                    max_team_labels: None,
+                    stale_review_days: None,
+                    default_capacity: None,
+                    max_diff_lines: None,
+                    require_acceptance: false,
+                    acceptance_timeout_days: None,
+                    auto_reassign_days: None,
+                    waiting_on_review_label: None,
                 }),
                 note: Some(NoteConfig { _empty: () }),
                 ping: Some(PingConfig { teams: ping_teams }),
@@ -772,11 +1150,15 @@ mod tests {
                 no_mentions: Some(NoMentionsConfig {}),
                 behind_upstream: Some(BehindUpstreamConfig {
                     days_threshold: Some(14),
+                    mention_author: false,
+                    message: None,
+                    label: Some("S-needs-rebase".to_string()),
                 }),
                 concern: Some(ConcernConfig {
                     labels: vec!["has-concerns".to_string()],
                 }),
-                backport: Some(backport_team_config)
+                backport: Some(backport_team_config),
+                relnotes: None,
             }
         );
     }
@@ -804,6 +1186,10 @@ mod tests {
 
             [behind-upstream]
             days-threshold = 7
+
+            [relnotes]
+            tracking-repo = "rust-lang/release-notes"
+            trigger-labels = ["relnotes"]
         "#;
         let config = toml::from_str::<Config>(&config).unwrap();
         assert_eq!(
@@ -811,6 +1197,9 @@ mod tests {
             Config {
                 relabel: None,
                 assign: Some(AssignConfig {
+                    enabled: true,
+                    enabled_repos: Vec::new(),
+                    trigger_on: vec![AssignTrigger::Opened, AssignTrigger::ReadyForReview],
                     warn_non_default_branch: WarnNonDefaultBranchConfig::Extended {
                         enable: true,
                         exceptions: vec![
@@ -833,9 +1222,38 @@ mod tests {
                     }),
                     contributing_url: None,
                     adhoc_groups: HashMap::new(),
+                    aliases: HashMap::new(),
+                    rotations: HashMap::new(),
                     owners: HashMap::new(),
+                    extension_owners: HashMap::new(),
+                    label_owners: HashMap::new(),
+                    blocking_labels: Vec::new(),
+                    no_assign_label: None,
+                    owners_file: None,
+                    use_codeowners: false,
                     users_on_vacation: HashSet::new(),
+                    reviewer_weights: HashMap::new(),
                     review_prefs: None,
+                    selection_mode: ReviewerSelectionMode::Random,
+                    cross_team_review: false,
+                    expand_subteams: false,
+                    allow_self_review: true,
+                    multiple_review_requests: MultipleReviewRequests::FirstWins,
+                    welcome_assigned_pr_count: false,
+                    welcome_diff_size: false,
+                    welcome_style: WelcomeStyle::Comment,
+                    welcome_once: false,
+                    ignore_authors: Vec::new(),
+                    ignore_authors_assignment: false,
+                    multi_area_threshold_percent: None,
+                    max_team_labels: None,
+                    stale_review_days: None,
+                    default_capacity: None,
+                    max_diff_lines: None,
+                    require_acceptance: false,
+                    acceptance_timeout_days: None,
+                    auto_reassign_days: None,
+                    waiting_on_review_label: None,
                 }),
                 note: None,
                 ping: None,
@@ -863,12 +1281,55 @@ mod tests {
                 no_mentions: None,
                 behind_upstream: Some(BehindUpstreamConfig {
                     days_threshold: Some(7),
+                    mention_author: false,
+                    message: None,
+                    label: None,
+                }),
+                backport: None,
+                relnotes: Some(RelnotesConfig {
+                    tracking_repo: Some("rust-lang/release-notes".to_string()),
+                    trigger_labels: vec!["relnotes".to_string()],
                 }),
-                backport: None
             }
         );
     }
 
+    #[test]
+    fn behind_upstream_message_placeholders() {
+        let config = r#"
+            [behind-upstream]
+            message = "This PR is based on a `{default_branch}` commit {days_old} days old."
+        "#;
+        let config = toml::from_str::<Config>(&config).unwrap();
+        assert_eq!(
+            config.behind_upstream.unwrap().message.as_deref(),
+            Some("This PR is based on a `{default_branch}` commit {days_old} days old.")
+        );
+
+        let config = r#"
+            [behind-upstream]
+            message = "This PR is {behind_by} commits behind."
+        "#;
+        let err = toml::from_str::<Config>(&config).unwrap_err();
+        assert!(
+            err.to_string()
+                .contains("unknown placeholder `{behind_by}`")
+        );
+    }
+
+    #[test]
+    fn relnotes_default_trigger_labels() {
+        let config = r#"
+            [relnotes]
+        "#;
+        let config = toml::from_str::<Config>(&config).unwrap();
+        assert_eq!(config.relnotes, Some(RelnotesConfig::default()));
+        assert_eq!(
+            RelnotesConfig::default().trigger_labels,
+            ["relnotes", "relnotes-perf", "finished-final-comment-period"]
+        );
+    }
+
     #[test]
     fn assign_review_prefs() {
         let config = r#"
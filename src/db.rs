@@ -7,12 +7,18 @@ use std::sync::{Arc, LazyLock, Mutex};
 use tokio::sync::{OwnedSemaphorePermit, Semaphore};
 use tokio_postgres::Client as DbClient;
 
+pub mod assignment_events;
 pub mod issue_data;
 pub mod jobs;
 pub mod notifications;
 pub mod review_prefs;
+pub mod review_rotation;
+pub mod reviewer_activity;
+pub mod reviewer_assignments;
 pub mod rustc_commits;
 pub mod users;
+pub mod welcomed_authors;
+pub mod workqueue;
 
 const CERT_URL: &str = "https://truststore.pki.rds.amazonaws.com/global/global-bundle.pem";
 
@@ -234,21 +240,22 @@ pub async fn schedule_job(
 }
 
 pub async fn run_scheduled_jobs(ctx: &Context) -> anyhow::Result<()> {
-    let db = &ctx.db.get().await;
-    let jobs = get_jobs_to_execute(&db).await?;
+    let jobs = get_jobs_to_execute(&*ctx.db.get().await).await?;
     tracing::trace!("jobs to execute: {:#?}", jobs);
 
+    // The database client is only taken around the job bookkeeping, so that it
+    // is not held while jobs make GitHub calls, e.g. the behind upstream sweep.
     for job in jobs.iter() {
-        update_job_executed_at(&db, &job.id).await?;
+        update_job_executed_at(&*ctx.db.get().await, &job.id).await?;
 
         match handle_job(&ctx, &job.name, &job.metadata).await {
             Ok(_) => {
                 tracing::trace!("job successfully executed (id={})", job.id);
-                delete_job(&db, &job.id).await?;
+                delete_job(&*ctx.db.get().await, &job.id).await?;
             }
             Err(e) => {
                 tracing::error!("job failed on execution (id={:?}, error={:?})", job.id, e);
-                update_job_error_message(&db, &job.id, &e.to_string()).await?;
+                update_job_error_message(&*ctx.db.get().await, &job.id, &e.to_string()).await?;
             }
         }
     }
@@ -348,5 +355,82 @@ ALTER TABLE review_prefs ADD COLUMN IF NOT EXISTS max_assigned_prs INTEGER DEFAU
 ",
     "
 ALTER TABLE review_prefs ADD COLUMN IF NOT EXISTS rotation_mode TEXT NOT NULL DEFAULT 'on-rotation';
+",
+    "
+ALTER TABLE review_prefs ADD COLUMN IF NOT EXISTS away_message TEXT DEFAULT NULL;
+",
+    "
+ALTER TABLE review_prefs ADD COLUMN IF NOT EXISTS away_until TIMESTAMP WITH TIME ZONE DEFAULT NULL;
+",
+    "
+CREATE TABLE IF NOT EXISTS reviewer_activity (
+    user_id BIGINT PRIMARY KEY REFERENCES users(user_id),
+    last_activity TIMESTAMP WITH TIME ZONE NOT NULL
+);
+",
+    "
+ALTER TABLE review_prefs ADD COLUMN IF NOT EXISTS blocked_authors TEXT[] NOT NULL DEFAULT array[]::TEXT[];
+",
+    "
+CREATE TABLE IF NOT EXISTS review_rotation (
+    rotation TEXT PRIMARY KEY,
+    last_reviewer TEXT NOT NULL
+);
+",
+    "
+ALTER TABLE review_prefs ADD COLUMN IF NOT EXISTS office_hours_start SMALLINT DEFAULT NULL;
+",
+    "
+ALTER TABLE review_prefs ADD COLUMN IF NOT EXISTS office_hours_end SMALLINT DEFAULT NULL;
+",
+    "
+ALTER TABLE review_prefs ADD COLUMN IF NOT EXISTS office_hours_utc_offset INTEGER DEFAULT NULL;
+",
+    "
+CREATE TABLE IF NOT EXISTS welcomed_authors (
+    repo TEXT NOT NULL,
+    user_id BIGINT NOT NULL,
+    PRIMARY KEY (repo, user_id)
+);
+",
+    "
+CREATE TABLE IF NOT EXISTS reviewer_workqueue (
+    user_id BIGINT NOT NULL,
+    pr_number BIGINT NOT NULL,
+    title TEXT NOT NULL,
+    updated_at TIMESTAMP WITH TIME ZONE NOT NULL,
+    PRIMARY KEY (user_id, pr_number)
+);
+",
+    "
+ALTER TABLE review_prefs ADD COLUMN IF NOT EXISTS vacation_start TIMESTAMP WITH TIME ZONE DEFAULT NULL;
+",
+    "
+ALTER TABLE review_prefs ADD COLUMN IF NOT EXISTS vacation_end TIMESTAMP WITH TIME ZONE DEFAULT NULL;
+",
+    "
+ALTER TABLE review_prefs ADD COLUMN IF NOT EXISTS approver_only BOOLEAN NOT NULL DEFAULT FALSE;
+",
+    "
+CREATE TABLE IF NOT EXISTS reviewer_assignments (
+    username TEXT PRIMARY KEY,
+    last_assigned TIMESTAMP WITH TIME ZONE NOT NULL
+);
+",
+    "
+CREATE TABLE IF NOT EXISTS assignment_events (
+    id BIGSERIAL PRIMARY KEY,
+    repo TEXT NOT NULL,
+    pr_number BIGINT NOT NULL,
+    assignee TEXT NOT NULL,
+    source TEXT NOT NULL,
+    assigned_at TIMESTAMP WITH TIME ZONE NOT NULL
+);
+",
+    "
+CREATE INDEX IF NOT EXISTS assignment_events_assigned_at_idx ON assignment_events (assigned_at);
+",
+    "
+CREATE INDEX IF NOT EXISTS assignment_events_repo_idx ON assignment_events (repo, assigned_at);
 ",
 ];
@@ -0,0 +1,174 @@
+//! Log of the reviewers assigned by triagebot.
+//!
+//! This is not used by triagebot itself, it is served as JSON to external
+//! dashboards by the `/assignment-events/{owner}/{repo}` endpoint. Old events
+//! are regularly pruned.
+
+use anyhow::Context;
+use chrono::{DateTime, Utc};
+use tokio_postgres::Client as DbClient;
+
+/// How an assigned reviewer was picked.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize)]
+pub enum AssignmentSource {
+    /// Picked from the files modified by the PR.
+    #[serde(rename = "diff")]
+    Diff,
+    /// Requested with `r?` or `@rustbot assign @user`.
+    #[serde(rename = "r?")]
+    ReviewRequest,
+    /// The reviewer assigned themselves, e.g. with `@rustbot claim`.
+    #[serde(rename = "claim")]
+    Claim,
+}
+
+impl AssignmentSource {
+    fn as_str(self) -> &'static str {
+        match self {
+            AssignmentSource::Diff => "diff",
+            AssignmentSource::ReviewRequest => "r?",
+            AssignmentSource::Claim => "claim",
+        }
+    }
+
+    fn from_str(source: &str) -> anyhow::Result<Self> {
+        match source {
+            "diff" => Ok(AssignmentSource::Diff),
+            "r?" => Ok(AssignmentSource::ReviewRequest),
+            "claim" => Ok(AssignmentSource::Claim),
+            _ => anyhow::bail!("unknown assignment source `{source}`"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+pub struct AssignmentEvent {
+    /// Full name of the repository, e.g. `rust-lang/rust`.
+    pub repo: String,
+    pub pr: u64,
+    pub assignee: String,
+    pub source: AssignmentSource,
+    pub timestamp: DateTime<Utc>,
+}
+
+/// Records that a reviewer was assigned.
+pub async fn record_assignment_event(db: &DbClient, event: &AssignmentEvent) -> anyhow::Result<()> {
+    db.execute(
+        r"
+INSERT INTO assignment_events (repo, pr_number, assignee, source, assigned_at)
+VALUES ($1, $2, $3, $4, $5)",
+        &[
+            &event.repo,
+            &(event.pr as i64),
+            &event.assignee,
+            &event.source.as_str(),
+            &event.timestamp,
+        ],
+    )
+    .await
+    .context("Error recording an assignment event")?;
+    Ok(())
+}
+
+/// Returns the last `limit` assignment events of `repo` (e.g.
+/// `rust-lang/rust`), the most recent first.
+pub async fn get_recent_assignment_events(
+    db: &DbClient,
+    repo: &str,
+    limit: u32,
+) -> anyhow::Result<Vec<AssignmentEvent>> {
+    db.query(
+        r"
+SELECT repo, pr_number, assignee, source, assigned_at
+FROM assignment_events
+WHERE repo = $1
+ORDER BY assigned_at DESC, id DESC
+LIMIT $2",
+        &[&repo, &i64::from(limit)],
+    )
+    .await
+    .context("Error retrieving the assignment events")?
+    .into_iter()
+    .map(|row| {
+        let pr: i64 = row.get("pr_number");
+        Ok(AssignmentEvent {
+            repo: row.get("repo"),
+            pr: pr as u64,
+            assignee: row.get("assignee"),
+            source: AssignmentSource::from_str(row.get("source"))?,
+            timestamp: row.get("assigned_at"),
+        })
+    })
+    .collect()
+}
+
+/// Deletes the assignment events older than `cutoff`. Returns the number of
+/// deleted events.
+pub async fn delete_assignment_events_before(
+    db: &DbClient,
+    cutoff: DateTime<Utc>,
+) -> anyhow::Result<u64> {
+    db.execute(
+        "DELETE FROM assignment_events WHERE assigned_at < $1",
+        &[&cutoff],
+    )
+    .await
+    .context("Error deleting old assignment events")
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::db::assignment_events::{
+        AssignmentEvent, AssignmentSource, delete_assignment_events_before,
+        get_recent_assignment_events, record_assignment_event,
+    };
+    use crate::tests::run_db_test;
+    use chrono::{Duration, SubsecRound, Utc};
+
+    #[tokio::test]
+    async fn recent_assignment_events() {
+        run_db_test(|ctx| async {
+            let db = ctx.db_client();
+            let now = Utc::now().trunc_subsecs(6);
+            let event = |pr, source, days_ago| AssignmentEvent {
+                repo: "rust-lang/rust".to_string(),
+                pr,
+                assignee: "martin".to_string(),
+                source,
+                timestamp: now - Duration::days(days_ago),
+            };
+
+            record_assignment_event(&db, &event(1, AssignmentSource::Diff, 2)).await?;
+            record_assignment_event(&db, &event(2, AssignmentSource::Claim, 0)).await?;
+            record_assignment_event(&db, &event(3, AssignmentSource::ReviewRequest, 1)).await?;
+            let other_repo = AssignmentEvent {
+                repo: "rust-lang/cargo".to_string(),
+                ..event(4, AssignmentSource::Diff, 0)
+            };
+            record_assignment_event(&db, &other_repo).await?;
+
+            assert_eq!(
+                get_recent_assignment_events(&db, "rust-lang/rust", 2).await?,
+                [
+                    event(2, AssignmentSource::Claim, 0),
+                    event(3, AssignmentSource::ReviewRequest, 1),
+                ]
+            );
+
+            assert_eq!(
+                delete_assignment_events_before(&db, now - Duration::hours(36)).await?,
+                1
+            );
+            assert_eq!(
+                get_recent_assignment_events(&db, "rust-lang/rust", 10).await?,
+                [
+                    event(2, AssignmentSource::Claim, 0),
+                    event(3, AssignmentSource::ReviewRequest, 1),
+                ]
+            );
+
+            Ok(ctx)
+        })
+        .await;
+    }
+}
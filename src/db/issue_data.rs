@@ -35,8 +35,19 @@ where
         issue: &Issue,
         key: &str,
     ) -> Result<IssueData<'db, T>> {
-        let repo = issue.repository().to_string();
-        let issue_number = issue.number as i32;
+        Self::load_by_number(db, &issue.repository().to_string(), issue.number, key).await
+    }
+
+    /// Like [`Self::load`], for the issue `issue_number` of `repo` (e.g.
+    /// `rust-lang/rust`), when the issue itself is not at hand.
+    pub async fn load_by_number(
+        db: &'db mut DbClient,
+        repo: &str,
+        issue_number: u64,
+        key: &str,
+    ) -> Result<IssueData<'db, T>> {
+        let repo = repo.to_string();
+        let issue_number = issue_number as i32;
         let transaction = db.transaction().await?;
         transaction
             .execute("LOCK TABLE issue_data", &[])
@@ -2,9 +2,11 @@ use crate::db::users::record_username;
 use crate::github::{User, UserId};
 use anyhow::Context;
 use bytes::BytesMut;
+use chrono::{DateTime, Duration, Timelike, Utc};
 use postgres_types::{FromSql, IsNull, ToSql, Type, to_sql_checked};
 use std::collections::HashMap;
 use std::error::Error;
+use std::fmt;
 
 #[derive(Debug, Default, Copy, Clone, PartialEq)]
 pub enum RotationMode {
@@ -60,6 +62,115 @@ pub struct ReviewPrefs {
     pub user_id: i64,
     pub max_assigned_prs: Option<i32>,
     pub rotation_mode: RotationMode,
+    /// Custom note shown to people requesting a review while the user is away.
+    pub away_message: Option<String>,
+    /// When the away status ends. `None` means that it lasts until it is cleared.
+    pub away_until: Option<DateTime<Utc>>,
+    /// Logins of PR authors whose PRs should not be assigned to the user.
+    /// This is private to the user and must not be revealed in public messages.
+    pub blocked_authors: Vec<String>,
+    /// When the user prefers to be assigned PRs automatically.
+    pub office_hours: Option<OfficeHours>,
+    /// When the planned vacation of the user starts and ends (exclusive).
+    /// The user is not assigned PRs automatically during that time.
+    pub vacation: Option<(DateTime<Utc>, DateTime<Utc>)>,
+    /// The user approves PRs, but does not want to be picked from teams and
+    /// assign groups. They can still be requested directly.
+    pub approver_only: bool,
+}
+
+/// The hours of the day during which a reviewer prefers to be assigned PRs.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct OfficeHours {
+    /// First hour of the window, in the time zone of the reviewer.
+    pub start: u32,
+    /// Hour at which the window ends, in the time zone of the reviewer.
+    /// It is lower than `start` for windows spanning midnight.
+    pub end: u32,
+    /// Offset of the time zone of the reviewer from UTC, in minutes.
+    pub utc_offset: i32,
+}
+
+impl OfficeHours {
+    /// Returns true if `time` is within the office hours.
+    pub fn contains(&self, time: DateTime<Utc>) -> bool {
+        let hour = (time + Duration::minutes(self.utc_offset.into())).hour();
+        if self.start <= self.end {
+            self.start <= hour && hour < self.end
+        } else {
+            hour >= self.start || hour < self.end
+        }
+    }
+}
+
+impl fmt::Display for OfficeHours {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}-{} UTC", self.start, self.end)?;
+        if self.utc_offset != 0 {
+            let sign = if self.utc_offset < 0 { '-' } else { '+' };
+            let offset = self.utc_offset.unsigned_abs();
+            write!(f, "{sign}{}", offset / 60)?;
+            if offset % 60 != 0 {
+                write!(f, ":{:02}", offset % 60)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Why the review preferences of a user keep them from being assigned PRs
+/// automatically at the moment.
+#[derive(Debug, Copy, Clone, PartialEq, serde::Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Unavailability {
+    Vacation,
+    OffRotation,
+    Away,
+    OutsideOfficeHours,
+}
+
+impl ReviewPrefs {
+    /// Returns why the user is not assigned PRs automatically at `time`, if
+    /// they aren't. These are the checks of the candidate reviewers, apart
+    /// from their capacity and the PR author.
+    pub fn unavailability(&self, time: DateTime<Utc>) -> Option<Unavailability> {
+        if self.is_on_vacation(time) {
+            Some(Unavailability::Vacation)
+        } else if self.rotation_mode == RotationMode::OffRotation {
+            Some(Unavailability::OffRotation)
+        } else if self.active_away_message().is_some() {
+            Some(Unavailability::Away)
+        } else if self
+            .office_hours
+            .is_some_and(|office_hours| !office_hours.contains(time))
+        {
+            Some(Unavailability::OutsideOfficeHours)
+        } else {
+            None
+        }
+    }
+
+    /// Returns the away message of the user, if they are currently away.
+    pub fn active_away_message(&self) -> Option<&str> {
+        let message = self.away_message.as_deref()?;
+        match self.away_until {
+            Some(until) if until <= Utc::now() => None,
+            _ => Some(message),
+        }
+    }
+
+    /// Returns true if the user is on vacation at `time`.
+    pub fn is_on_vacation(&self, time: DateTime<Utc>) -> bool {
+        self.vacation
+            .is_some_and(|(start, end)| start <= time && time < end)
+    }
+
+    /// Returns true if the user opted out of reviewing PRs of `author`.
+    pub fn blocks_author(&self, author: &str) -> bool {
+        self.blocked_authors
+            .iter()
+            .any(|blocked| blocked.eq_ignore_ascii_case(author))
+    }
 }
 
 impl From<tokio_postgres::row::Row> for ReviewPrefs {
@@ -69,10 +180,29 @@ impl From<tokio_postgres::row::Row> for ReviewPrefs {
             user_id: row.get("user_id"),
             max_assigned_prs: row.get("max_assigned_prs"),
             rotation_mode: row.get("rotation_mode"),
+            away_message: row.get("away_message"),
+            away_until: row.get("away_until"),
+            blocked_authors: row.get("blocked_authors"),
+            office_hours: office_hours_from_row(&row),
+            vacation: row
+                .get::<_, Option<DateTime<Utc>>>("vacation_start")
+                .zip(row.get("vacation_end")),
+            approver_only: row.get("approver_only"),
         }
     }
 }
 
+fn office_hours_from_row(row: &tokio_postgres::row::Row) -> Option<OfficeHours> {
+    let start: i16 = row.get::<_, Option<i16>>("office_hours_start")?;
+    let end: i16 = row.get::<_, Option<i16>>("office_hours_end")?;
+    let utc_offset: i32 = row.get::<_, Option<i32>>("office_hours_utc_offset")?;
+    Some(OfficeHours {
+        start: start as u32,
+        end: end as u32,
+        utc_offset,
+    })
+}
+
 /// Get team member review preferences.
 /// If they are missing, returns `Ok(None)`.
 pub async fn get_review_prefs(
@@ -80,7 +210,9 @@ pub async fn get_review_prefs(
     user_id: UserId,
 ) -> anyhow::Result<Option<ReviewPrefs>> {
     let query = "
-SELECT id, user_id, max_assigned_prs, rotation_mode
+SELECT id, user_id, max_assigned_prs, rotation_mode, away_message, away_until, blocked_authors,
+    office_hours_start, office_hours_end, office_hours_utc_offset, vacation_start, vacation_end,
+    approver_only
 FROM review_prefs
 WHERE review_prefs.user_id = $1;";
     let row = db
@@ -109,7 +241,8 @@ pub async fn get_review_prefs_batch<'a>(
         .collect();
     let lowercase_users: Vec<&str> = lowercase_map.keys().map(|s| s.as_str()).collect();
 
-    // The id/user_id/max_assigned_prs/rotation_mode columns have to match the names used in
+    // The id/user_id/max_assigned_prs/rotation_mode/away_message/away_until/blocked_authors/
+    // office_hours_*/vacation_*/approver_only columns have to match the names used in
     // `From<tokio_postgres::row::Row> for ReviewPrefs`.
     let query = "
 SELECT
@@ -117,7 +250,16 @@ SELECT
     r.id AS id,
     r.user_id AS user_id,
     r.max_assigned_prs AS max_assigned_prs,
-    r.rotation_mode AS rotation_mode
+    r.rotation_mode AS rotation_mode,
+    r.away_message AS away_message,
+    r.away_until AS away_until,
+    r.blocked_authors AS blocked_authors,
+    r.office_hours_start AS office_hours_start,
+    r.office_hours_end AS office_hours_end,
+    r.office_hours_utc_offset AS office_hours_utc_offset,
+    r.vacation_start AS vacation_start,
+    r.vacation_end AS vacation_end,
+    r.approver_only AS approver_only
 FROM review_prefs AS r
 JOIN users AS u ON u.user_id = r.user_id
 WHERE lower(u.username) = ANY($1);";
@@ -139,6 +281,49 @@ WHERE lower(u.username) = ANY($1);";
         .collect())
 }
 
+/// Returns the usernames of all users whose review preferences keep them from
+/// being assigned PRs automatically at `time`, with the reason, sorted
+/// alphabetically.
+pub async fn get_unavailable_users(
+    db: &tokio_postgres::Client,
+    time: DateTime<Utc>,
+) -> anyhow::Result<Vec<(String, Unavailability)>> {
+    // The columns have to match the names used in
+    // `From<tokio_postgres::row::Row> for ReviewPrefs`.
+    let query = "
+SELECT
+    u.username AS username,
+    r.id AS id,
+    r.user_id AS user_id,
+    r.max_assigned_prs AS max_assigned_prs,
+    r.rotation_mode AS rotation_mode,
+    r.away_message AS away_message,
+    r.away_until AS away_until,
+    r.blocked_authors AS blocked_authors,
+    r.office_hours_start AS office_hours_start,
+    r.office_hours_end AS office_hours_end,
+    r.office_hours_utc_offset AS office_hours_utc_offset,
+    r.vacation_start AS vacation_start,
+    r.vacation_end AS vacation_end,
+    r.approver_only AS approver_only
+FROM review_prefs AS r
+JOIN users AS u ON u.user_id = r.user_id
+ORDER BY u.username;";
+    Ok(db
+        .query(query, &[])
+        .await
+        .context("Error retrieving unavailable users")?
+        .into_iter()
+        .filter_map(|row| {
+            let username: String = row.get("username");
+            let prefs: ReviewPrefs = row.into();
+            prefs
+                .unavailability(time)
+                .map(|unavailability| (username, unavailability))
+        })
+        .collect())
+}
+
 /// Updates review preferences of the specified user, or creates them
 /// if they do not exist yet.
 pub async fn upsert_review_prefs(
@@ -150,7 +335,10 @@ pub async fn upsert_review_prefs(
     // We need to have the user stored in the DB to have a valid FK link in review_prefs
     record_username(db, user.id, &user.login).await?;
 
-    let max_assigned_prs = max_assigned_prs.map(|v| v as i32);
+    let max_assigned_prs = max_assigned_prs
+        .map(i32::try_from)
+        .transpose()
+        .context("The review capacity is too large")?;
     let query = "
 INSERT INTO review_prefs(user_id, max_assigned_prs, rotation_mode)
 VALUES ($1, $2, $3)
@@ -169,12 +357,147 @@ SET max_assigned_prs = excluded.max_assigned_prs,
     Ok(res)
 }
 
+/// Sets (or clears, if `message` is `None`) the away status of the specified user.
+/// Creates review preferences with default values if they do not exist yet.
+pub async fn set_away_status(
+    db: &tokio_postgres::Client,
+    user: User,
+    message: Option<&str>,
+    until: Option<DateTime<Utc>>,
+) -> anyhow::Result<u64, anyhow::Error> {
+    // We need to have the user stored in the DB to have a valid FK link in review_prefs
+    record_username(db, user.id, &user.login).await?;
+
+    let query = "
+INSERT INTO review_prefs(user_id, away_message, away_until)
+VALUES ($1, $2, $3)
+ON CONFLICT (user_id)
+DO UPDATE
+SET away_message = excluded.away_message,
+    away_until = excluded.away_until";
+
+    let res = db
+        .execute(query, &[&(user.id as i64), &message, &until])
+        .await
+        .context("Error setting away status")?;
+    Ok(res)
+}
+
+/// Sets the logins of the PR authors whose PRs should not be assigned to the specified user.
+/// Creates review preferences with default values if they do not exist yet.
+pub async fn set_blocked_authors(
+    db: &tokio_postgres::Client,
+    user: User,
+    authors: &[String],
+) -> anyhow::Result<u64, anyhow::Error> {
+    // We need to have the user stored in the DB to have a valid FK link in review_prefs
+    record_username(db, user.id, &user.login).await?;
+
+    let query = "
+INSERT INTO review_prefs(user_id, blocked_authors)
+VALUES ($1, $2)
+ON CONFLICT (user_id)
+DO UPDATE
+SET blocked_authors = excluded.blocked_authors";
+
+    let res = db
+        .execute(query, &[&(user.id as i64), &authors])
+        .await
+        .context("Error setting blocked authors")?;
+    Ok(res)
+}
+
+/// Sets (or clears, if `office_hours` is `None`) the office hours of the specified user.
+/// Creates review preferences with default values if they do not exist yet.
+pub async fn set_office_hours(
+    db: &tokio_postgres::Client,
+    user: User,
+    office_hours: Option<OfficeHours>,
+) -> anyhow::Result<u64, anyhow::Error> {
+    // We need to have the user stored in the DB to have a valid FK link in review_prefs
+    record_username(db, user.id, &user.login).await?;
+
+    let start = office_hours.map(|hours| hours.start as i16);
+    let end = office_hours.map(|hours| hours.end as i16);
+    let utc_offset = office_hours.map(|hours| hours.utc_offset);
+    let query = "
+INSERT INTO review_prefs(user_id, office_hours_start, office_hours_end, office_hours_utc_offset)
+VALUES ($1, $2, $3, $4)
+ON CONFLICT (user_id)
+DO UPDATE
+SET office_hours_start = excluded.office_hours_start,
+    office_hours_end = excluded.office_hours_end,
+    office_hours_utc_offset = excluded.office_hours_utc_offset";
+
+    let res = db
+        .execute(query, &[&(user.id as i64), &start, &end, &utc_offset])
+        .await
+        .context("Error setting office hours")?;
+    Ok(res)
+}
+
+/// Sets (or clears, if `vacation` is `None`) the planned vacation of the specified user.
+/// Creates review preferences with default values if they do not exist yet.
+pub async fn set_vacation(
+    db: &tokio_postgres::Client,
+    user: User,
+    vacation: Option<(DateTime<Utc>, DateTime<Utc>)>,
+) -> anyhow::Result<u64, anyhow::Error> {
+    // We need to have the user stored in the DB to have a valid FK link in review_prefs
+    record_username(db, user.id, &user.login).await?;
+
+    let start = vacation.map(|(start, _)| start);
+    let end = vacation.map(|(_, end)| end);
+    let query = "
+INSERT INTO review_prefs(user_id, vacation_start, vacation_end)
+VALUES ($1, $2, $3)
+ON CONFLICT (user_id)
+DO UPDATE
+SET vacation_start = excluded.vacation_start,
+    vacation_end = excluded.vacation_end";
+
+    let res = db
+        .execute(query, &[&(user.id as i64), &start, &end])
+        .await
+        .context("Error setting vacation")?;
+    Ok(res)
+}
+
+/// Sets whether the specified user is only an approver, i.e. is not picked from teams
+/// and assign groups. Creates review preferences with default values if they do not exist yet.
+pub async fn set_approver_only(
+    db: &tokio_postgres::Client,
+    user: User,
+    approver_only: bool,
+) -> anyhow::Result<u64, anyhow::Error> {
+    // We need to have the user stored in the DB to have a valid FK link in review_prefs
+    record_username(db, user.id, &user.login).await?;
+
+    let query = "
+INSERT INTO review_prefs(user_id, approver_only)
+VALUES ($1, $2)
+ON CONFLICT (user_id)
+DO UPDATE
+SET approver_only = excluded.approver_only";
+
+    let res = db
+        .execute(query, &[&(user.id as i64), &approver_only])
+        .await
+        .context("Error setting approver only")?;
+    Ok(res)
+}
+
 #[cfg(test)]
 mod tests {
-    use crate::db::review_prefs::{RotationMode, get_review_prefs, upsert_review_prefs};
+    use crate::db::review_prefs::{
+        OfficeHours, RotationMode, Unavailability, get_review_prefs, get_unavailable_users,
+        set_approver_only, set_away_status, set_blocked_authors, set_office_hours, set_vacation,
+        upsert_review_prefs,
+    };
     use crate::db::users::get_user;
     use crate::tests::github::user;
     use crate::tests::run_db_test;
+    use chrono::Duration;
 
     #[tokio::test]
     async fn insert_prefs_create_user() {
@@ -239,6 +562,22 @@ mod tests {
                 None
             );
 
+            // Capacities that don't fit in the database are rejected.
+            assert!(
+                upsert_review_prefs(
+                    &db,
+                    user("Martin", 1),
+                    Some(u32::MAX),
+                    RotationMode::OnRotation
+                )
+                .await
+                .is_err()
+            );
+            assert_eq!(
+                get_review_prefs(&db, 1).await?.unwrap().max_assigned_prs,
+                None
+            );
+
             Ok(ctx)
         })
         .await;
@@ -265,4 +604,183 @@ mod tests {
         })
         .await;
     }
+
+    #[tokio::test]
+    async fn list_unavailable_users() {
+        run_db_test(|ctx| async {
+            let db = ctx.db_client();
+            let now = chrono::Utc::now();
+
+            upsert_review_prefs(&db, user("Martin", 1), None, RotationMode::OffRotation).await?;
+            upsert_review_prefs(&db, user("Diana", 2), None, RotationMode::OnRotation).await?;
+            upsert_review_prefs(&db, user("Adam", 3), None, RotationMode::OnRotation).await?;
+            set_vacation(
+                &db,
+                user("Adam", 3),
+                Some((now - Duration::days(1), now + Duration::days(1))),
+            )
+            .await?;
+            upsert_review_prefs(&db, user("Jana", 4), None, RotationMode::OnRotation).await?;
+            set_away_status(&db, user("Jana", 4), Some("at a conference"), None).await?;
+            assert_eq!(
+                get_unavailable_users(&db, now).await?,
+                vec![
+                    ("Adam".to_string(), Unavailability::Vacation),
+                    ("Jana".to_string(), Unavailability::Away),
+                    ("Martin".to_string(), Unavailability::OffRotation),
+                ]
+            );
+
+            Ok(ctx)
+        })
+        .await;
+    }
+
+    #[tokio::test]
+    async fn set_and_clear_away_status() {
+        run_db_test(|ctx| async {
+            let db = ctx.db_client();
+            let user = user("Martin", 1);
+
+            upsert_review_prefs(&db, user.clone(), Some(5), RotationMode::OnRotation).await?;
+            set_away_status(&db, user.clone(), Some("back Monday"), None).await?;
+            let prefs = get_review_prefs(&db, 1).await?.unwrap();
+            assert_eq!(prefs.active_away_message(), Some("back Monday"));
+            // Other preferences are kept
+            assert_eq!(prefs.max_assigned_prs, Some(5));
+
+            let expired = chrono::Utc::now() - chrono::Duration::days(1);
+            set_away_status(&db, user.clone(), Some("back Monday"), Some(expired)).await?;
+            let prefs = get_review_prefs(&db, 1).await?.unwrap();
+            assert_eq!(prefs.active_away_message(), None);
+
+            set_away_status(&db, user.clone(), None, None).await?;
+            let prefs = get_review_prefs(&db, 1).await?.unwrap();
+            assert_eq!(prefs.away_message, None);
+
+            Ok(ctx)
+        })
+        .await;
+    }
+
+    #[tokio::test]
+    async fn set_and_clear_blocked_authors() {
+        run_db_test(|ctx| async {
+            let db = ctx.db_client();
+            let user = user("Martin", 1);
+
+            set_blocked_authors(&db, user.clone(), &["Diana".to_string()]).await?;
+            let prefs = get_review_prefs(&db, 1).await?.unwrap();
+            assert!(prefs.blocks_author("diana"));
+            assert!(!prefs.blocks_author("jyn"));
+
+            set_blocked_authors(&db, user.clone(), &[]).await?;
+            let prefs = get_review_prefs(&db, 1).await?.unwrap();
+            assert!(prefs.blocked_authors.is_empty());
+
+            Ok(ctx)
+        })
+        .await;
+    }
+
+    #[tokio::test]
+    async fn set_and_clear_office_hours() {
+        run_db_test(|ctx| async {
+            let db = ctx.db_client();
+            let user = user("Martin", 1);
+            let office_hours = OfficeHours {
+                start: 9,
+                end: 17,
+                utc_offset: 120,
+            };
+
+            upsert_review_prefs(&db, user.clone(), Some(5), RotationMode::OnRotation).await?;
+            set_office_hours(&db, user.clone(), Some(office_hours)).await?;
+            let prefs = get_review_prefs(&db, 1).await?.unwrap();
+            assert_eq!(prefs.office_hours, Some(office_hours));
+            // Other preferences are kept
+            assert_eq!(prefs.max_assigned_prs, Some(5));
+
+            set_office_hours(&db, user.clone(), None).await?;
+            let prefs = get_review_prefs(&db, 1).await?.unwrap();
+            assert_eq!(prefs.office_hours, None);
+
+            Ok(ctx)
+        })
+        .await;
+    }
+
+    #[tokio::test]
+    async fn set_and_end_vacation() {
+        run_db_test(|ctx| async {
+            let db = ctx.db_client();
+            let user = user("Martin", 1);
+            let day = |day: u32| {
+                chrono::NaiveDate::from_ymd_opt(2025, 8, day)
+                    .unwrap()
+                    .and_time(chrono::NaiveTime::MIN)
+                    .and_utc()
+            };
+
+            set_vacation(&db, user.clone(), Some((day(1), day(15)))).await?;
+            let prefs = get_review_prefs(&db, 1).await?.unwrap();
+            assert!(!prefs.is_on_vacation(day(1) - Duration::seconds(1)));
+            assert!(prefs.is_on_vacation(day(1)));
+            assert!(prefs.is_on_vacation(day(14)));
+            assert!(!prefs.is_on_vacation(day(15)));
+
+            set_vacation(&db, user.clone(), None).await?;
+            let prefs = get_review_prefs(&db, 1).await?.unwrap();
+            assert!(!prefs.is_on_vacation(day(2)));
+
+            Ok(ctx)
+        })
+        .await;
+    }
+
+    #[tokio::test]
+    async fn set_and_clear_approver_only() {
+        run_db_test(|ctx| async {
+            let db = ctx.db_client();
+            let user = user("Martin", 1);
+
+            set_approver_only(&db, user.clone(), true).await?;
+            assert!(get_review_prefs(&db, 1).await?.unwrap().approver_only);
+            set_approver_only(&db, user.clone(), false).await?;
+            assert!(!get_review_prefs(&db, 1).await?.unwrap().approver_only);
+
+            Ok(ctx)
+        })
+        .await;
+    }
+
+    #[test]
+    fn office_hours() {
+        let at = |hour: u32| {
+            chrono::NaiveDate::from_ymd_opt(2024, 1, 1)
+                .unwrap()
+                .and_hms_opt(hour, 30, 0)
+                .unwrap()
+                .and_utc()
+        };
+        let office_hours = OfficeHours {
+            start: 9,
+            end: 17,
+            utc_offset: 120,
+        };
+        assert!(!office_hours.contains(at(6)));
+        assert!(office_hours.contains(at(7)));
+        assert!(office_hours.contains(at(14)));
+        assert!(!office_hours.contains(at(15)));
+        assert_eq!(office_hours.to_string(), "9-17 UTC+2");
+
+        let night_shift = OfficeHours {
+            start: 22,
+            end: 6,
+            utc_offset: -330,
+        };
+        assert!(night_shift.contains(at(3)));
+        assert!(!night_shift.contains(at(12)));
+        assert_eq!(night_shift.to_string(), "22-6 UTC-5:30");
+    }
 }
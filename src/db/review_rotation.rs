@@ -0,0 +1,37 @@
+//! Tracks the last reviewer assigned from each review rotation.
+//!
+//! This is used by the `strict-alternate` reviewer selection mode to assign
+//! the members of a team in turn.
+
+use anyhow::Context;
+use tokio_postgres::Client as DbClient;
+
+/// Returns the last reviewer assigned from `rotation`, if any.
+pub async fn get_last_reviewer(db: &DbClient, rotation: &str) -> anyhow::Result<Option<String>> {
+    let row = db
+        .query_opt(
+            "SELECT last_reviewer FROM review_rotation WHERE rotation = $1",
+            &[&rotation],
+        )
+        .await
+        .context("Error retrieving the last reviewer of a rotation")?;
+    Ok(row.map(|row| row.get("last_reviewer")))
+}
+
+/// Records that `reviewer` was the last reviewer assigned from `rotation`.
+pub async fn record_last_reviewer(
+    db: &DbClient,
+    rotation: &str,
+    reviewer: &str,
+) -> anyhow::Result<()> {
+    db.execute(
+        r"
+INSERT INTO review_rotation (rotation, last_reviewer) VALUES ($1, $2)
+ON CONFLICT (rotation)
+DO UPDATE SET last_reviewer = excluded.last_reviewer",
+        &[&rotation, &reviewer],
+    )
+    .await
+    .context("Error recording the last reviewer of a rotation")?;
+    Ok(())
+}
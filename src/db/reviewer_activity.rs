@@ -0,0 +1,99 @@
+//! Tracks when reviewers were last active on the pull requests assigned to them.
+//!
+//! This is used by the `least-recently-active` reviewer selection mode to
+//! spread the review load towards reviewers that haven't been busy lately.
+
+use crate::db::users::record_username;
+use crate::github::User;
+use anyhow::Context;
+use chrono::{DateTime, Utc};
+use std::collections::HashMap;
+use tokio_postgres::Client as DbClient;
+
+/// Records that `user` was active on an assigned pull request at `time`.
+/// Older timestamps never overwrite a more recent activity.
+pub async fn record_reviewer_activity(
+    db: &DbClient,
+    user: &User,
+    time: DateTime<Utc>,
+) -> anyhow::Result<()> {
+    record_username(db, user.id, &user.login)
+        .await
+        .context("failed to record username")?;
+
+    db.execute(
+        r"
+INSERT INTO reviewer_activity (user_id, last_activity) VALUES ($1, $2)
+ON CONFLICT (user_id)
+DO UPDATE SET last_activity = GREATEST(reviewer_activity.last_activity, excluded.last_activity)",
+        &[&(user.id as i64), &time],
+    )
+    .await
+    .context("Error recording reviewer activity")?;
+    Ok(())
+}
+
+/// Returns the last recorded activity of the given users.
+/// Users without any recorded activity are missing from the returned map.
+pub async fn get_last_activity_batch<'a>(
+    db: &DbClient,
+    users: &[&'a str],
+) -> anyhow::Result<HashMap<&'a str, DateTime<Utc>>> {
+    // Match usernames regardless of case, but return the originally-cased names.
+    let lowercase_map: HashMap<String, &str> = users
+        .iter()
+        .map(|name| (name.to_lowercase(), *name))
+        .collect();
+    let lowercase_users: Vec<&str> = lowercase_map.keys().map(|s| s.as_str()).collect();
+
+    let query = "
+SELECT lower(u.username) AS username, a.last_activity AS last_activity
+FROM reviewer_activity AS a
+JOIN users AS u ON u.user_id = a.user_id
+WHERE lower(u.username) = ANY($1);";
+
+    Ok(db
+        .query(query, &[&lowercase_users])
+        .await
+        .context("Error retrieving reviewer activity from usernames")?
+        .into_iter()
+        .map(|row| {
+            let username_lower: &str = row.get("username");
+            let username = lowercase_map
+                .get(username_lower)
+                .expect("Lowercase username not found");
+            let last_activity: DateTime<Utc> = row.get("last_activity");
+            (*username, last_activity)
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::db::reviewer_activity::{get_last_activity_batch, record_reviewer_activity};
+    use crate::tests::github::user;
+    use crate::tests::run_db_test;
+    use chrono::{Duration, Utc};
+
+    #[tokio::test]
+    async fn keep_most_recent_activity() {
+        run_db_test(|ctx| async {
+            let db = ctx.db_client();
+            let martin = user("Martin", 1);
+            let now = Utc::now();
+
+            record_reviewer_activity(&db, &martin, now).await?;
+            record_reviewer_activity(&db, &martin, now - Duration::days(1)).await?;
+
+            let activity = get_last_activity_batch(&db, &["martin", "diana"]).await?;
+            assert_eq!(activity.len(), 1);
+            assert_eq!(
+                activity["martin"].timestamp_micros(),
+                now.timestamp_micros()
+            );
+
+            Ok(ctx)
+        })
+        .await;
+    }
+}
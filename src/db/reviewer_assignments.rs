@@ -0,0 +1,87 @@
+//! Tracks when reviewers were last assigned a pull request.
+//!
+//! This is used by the `round-robin` reviewer selection mode to pick the
+//! reviewer who has been waiting the longest for a new review request.
+
+use anyhow::Context;
+use chrono::{DateTime, Utc};
+use std::collections::HashMap;
+use tokio_postgres::Client as DbClient;
+
+/// Records that `username` was assigned a pull request at `time`.
+pub async fn record_last_assigned(
+    db: &DbClient,
+    username: &str,
+    time: DateTime<Utc>,
+) -> anyhow::Result<()> {
+    db.execute(
+        r"
+INSERT INTO reviewer_assignments (username, last_assigned) VALUES ($1, $2)
+ON CONFLICT (username)
+DO UPDATE SET last_assigned = excluded.last_assigned",
+        &[&username.to_lowercase(), &time],
+    )
+    .await
+    .context("Error recording the last assignment of a reviewer")?;
+    Ok(())
+}
+
+/// Returns when the given users were last assigned a pull request.
+/// Users that were never assigned are missing from the returned map.
+pub async fn get_last_assigned_batch<'a>(
+    db: &DbClient,
+    users: &[&'a str],
+) -> anyhow::Result<HashMap<&'a str, DateTime<Utc>>> {
+    // Match usernames regardless of case, but return the originally-cased names.
+    let lowercase_map: HashMap<String, &str> = users
+        .iter()
+        .map(|name| (name.to_lowercase(), *name))
+        .collect();
+    let lowercase_users: Vec<&str> = lowercase_map.keys().map(|s| s.as_str()).collect();
+
+    Ok(db
+        .query(
+            "SELECT username, last_assigned FROM reviewer_assignments WHERE username = ANY($1)",
+            &[&lowercase_users],
+        )
+        .await
+        .context("Error retrieving the last assignments of reviewers")?
+        .into_iter()
+        .map(|row| {
+            let username_lower: &str = row.get("username");
+            let username = lowercase_map
+                .get(username_lower)
+                .expect("Lowercase username not found");
+            let last_assigned: DateTime<Utc> = row.get("last_assigned");
+            (*username, last_assigned)
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::db::reviewer_assignments::{get_last_assigned_batch, record_last_assigned};
+    use crate::tests::run_db_test;
+    use chrono::{Duration, Utc};
+
+    #[tokio::test]
+    async fn keep_last_assignment() {
+        run_db_test(|ctx| async {
+            let db = ctx.db_client();
+            let now = Utc::now();
+
+            record_last_assigned(&db, "Martin", now - Duration::days(1)).await?;
+            record_last_assigned(&db, "martin", now).await?;
+
+            let assigned = get_last_assigned_batch(&db, &["Martin", "diana"]).await?;
+            assert_eq!(assigned.len(), 1);
+            assert_eq!(
+                assigned["Martin"].timestamp_micros(),
+                now.timestamp_micros()
+            );
+
+            Ok(ctx)
+        })
+        .await;
+    }
+}
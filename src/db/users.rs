@@ -1,5 +1,6 @@
 use crate::github::User;
 use anyhow::Context;
+use std::collections::HashMap;
 use tokio_postgres::Client as DbClient;
 
 /// Add a new user.
@@ -38,9 +39,41 @@ WHERE user_id = $1;",
     }))
 }
 
+/// Returns the user ids of the given `users`, keyed by the usernames as passed.
+///
+/// Usernames are matched regardless of case. Unknown users are not present in
+/// the resulting map.
+pub async fn get_user_ids_batch<'a>(
+    db: &DbClient,
+    users: &[&'a str],
+) -> anyhow::Result<HashMap<&'a str, u64>> {
+    let lowercase_map: HashMap<String, &str> = users
+        .iter()
+        .map(|name| (name.to_lowercase(), *name))
+        .collect();
+    let lowercase_users: Vec<&str> = lowercase_map.keys().map(|s| s.as_str()).collect();
+    Ok(db
+        .query(
+            r"
+SELECT lower(username), user_id
+FROM users
+WHERE lower(username) = ANY($1);",
+            &[&lowercase_users],
+        )
+        .await
+        .context("cannot load user ids from DB")?
+        .into_iter()
+        .map(|row| {
+            let username_lower: &str = row.get(0);
+            let user_id: i64 = row.get(1);
+            (lowercase_map[username_lower], user_id as u64)
+        })
+        .collect())
+}
+
 #[cfg(test)]
 mod tests {
-    use crate::db::users::{get_user, record_username};
+    use crate::db::users::{get_user, get_user_ids_batch, record_username};
     use crate::tests::run_db_test;
 
     #[tokio::test]
@@ -57,4 +90,22 @@ mod tests {
         })
         .await;
     }
+
+    #[tokio::test]
+    async fn user_ids_by_username() {
+        run_db_test(|ctx| async {
+            let db = ctx.db_client();
+
+            record_username(&db, 1, "Foo").await?;
+            record_username(&db, 2, "bar").await?;
+
+            let ids = get_user_ids_batch(&db, &["foo", "Bar", "baz"]).await?;
+            assert_eq!(ids.len(), 2);
+            assert_eq!(ids["foo"], 1);
+            assert_eq!(ids["Bar"], 2);
+
+            Ok(ctx)
+        })
+        .await;
+    }
 }
@@ -0,0 +1,40 @@
+//! Tracks the authors that were already given the full welcome message in a
+//! repository.
+//!
+//! This is used by the `welcome_once` option of `[assign]` to avoid repeating
+//! the contribution instructions on every PR of a new contributor.
+
+use anyhow::Context;
+use tokio_postgres::Client as DbClient;
+
+/// Returns true if the author `user_id` was already welcomed in `repo`.
+pub async fn was_author_welcomed(db: &DbClient, repo: &str, user_id: u64) -> anyhow::Result<bool> {
+    let row = db
+        .query_opt(
+            "SELECT 1 FROM welcomed_authors WHERE repo = $1 AND user_id = $2",
+            &[&repo, &(user_id as i64)],
+        )
+        .await
+        .context("Error retrieving a welcomed author")?;
+    Ok(row.is_some())
+}
+
+/// Records that the author `user_id` was welcomed in `repo`.
+///
+/// Returns true if they had not been welcomed in `repo` before.
+pub async fn record_welcomed_author(
+    db: &DbClient,
+    repo: &str,
+    user_id: u64,
+) -> anyhow::Result<bool> {
+    let inserted = db
+        .execute(
+            r"
+INSERT INTO welcomed_authors (repo, user_id) VALUES ($1, $2)
+ON CONFLICT (repo, user_id) DO NOTHING",
+            &[&repo, &(user_id as i64)],
+        )
+        .await
+        .context("Error recording a welcomed author")?;
+    Ok(inserted == 1)
+}
@@ -0,0 +1,123 @@
+//! Snapshots of the reviewer workqueue.
+//!
+//! The workqueue is kept in memory and periodically reloaded from GitHub. A
+//! snapshot is stored in the DB so that triagebot can restore it on startup
+//! when loading it from GitHub is skipped or fails.
+
+use crate::github::{PullRequestNumber, UserId};
+use crate::handlers::pr_tracking::{AssignedPullRequest, ReviewerWorkqueue};
+use anyhow::Context;
+use chrono::{DateTime, Utc};
+use std::collections::HashMap;
+use tokio_postgres::Client as DbClient;
+
+/// Replaces the stored snapshot with `workqueue`.
+pub async fn save_workqueue(
+    db: &mut DbClient,
+    workqueue: &ReviewerWorkqueue,
+) -> anyhow::Result<()> {
+    let mut user_ids = vec![];
+    let mut pr_numbers = vec![];
+    let mut titles = vec![];
+    let mut updated_ats = vec![];
+    for (user_id, prs) in workqueue.reviewers() {
+        for (pr_number, pr) in prs {
+            user_ids.push(*user_id as i64);
+            pr_numbers.push(*pr_number as i64);
+            titles.push(pr.title.as_str());
+            updated_ats.push(pr.updated_at);
+        }
+    }
+
+    let tx = db.transaction().await?;
+    tx.execute("DELETE FROM reviewer_workqueue", &[])
+        .await
+        .context("Error clearing the workqueue snapshot")?;
+    tx.execute(
+        r"
+INSERT INTO reviewer_workqueue (user_id, pr_number, title, updated_at)
+SELECT * FROM UNNEST($1::BIGINT[], $2::BIGINT[], $3::TEXT[], $4::TIMESTAMPTZ[])",
+        &[&user_ids, &pr_numbers, &titles, &updated_ats],
+    )
+    .await
+    .context("Error saving the workqueue snapshot")?;
+    tx.commit().await?;
+    Ok(())
+}
+
+/// Loads the stored snapshot of the workqueue.
+///
+/// The workqueue is empty if no snapshot was saved yet.
+pub async fn load_workqueue_snapshot(db: &DbClient) -> anyhow::Result<ReviewerWorkqueue> {
+    let rows = db
+        .query(
+            "SELECT user_id, pr_number, title, updated_at FROM reviewer_workqueue",
+            &[],
+        )
+        .await
+        .context("Error loading the workqueue snapshot")?;
+    let mut reviewers: HashMap<UserId, HashMap<PullRequestNumber, AssignedPullRequest>> =
+        HashMap::new();
+    for row in rows {
+        let user_id: i64 = row.get("user_id");
+        let pr_number: i64 = row.get("pr_number");
+        let updated_at: DateTime<Utc> = row.get("updated_at");
+        reviewers.entry(user_id as UserId).or_default().insert(
+            pr_number as PullRequestNumber,
+            AssignedPullRequest {
+                title: row.get("title"),
+                updated_at,
+            },
+        );
+    }
+    Ok(ReviewerWorkqueue::new(reviewers))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::db::workqueue::{load_workqueue_snapshot, save_workqueue};
+    use crate::handlers::pr_tracking::{AssignedPullRequest, ReviewerWorkqueue};
+    use crate::tests::run_db_test;
+    use chrono::{DateTime, Utc};
+    use std::collections::HashMap;
+
+    fn pr(title: &str, updated_at: DateTime<Utc>) -> AssignedPullRequest {
+        AssignedPullRequest {
+            title: title.to_string(),
+            updated_at,
+        }
+    }
+
+    #[tokio::test]
+    async fn round_trip_workqueue() {
+        run_db_test(|mut ctx| async {
+            // Postgres stores timestamps with microsecond precision.
+            let now = DateTime::from_timestamp_micros(Utc::now().timestamp_micros()).unwrap();
+            let workqueue = ReviewerWorkqueue::new(HashMap::from([
+                (
+                    1,
+                    HashMap::from([(10, pr("Fix ICE", now)), (11, pr("Docs", now))]),
+                ),
+                (2, HashMap::from([(12, pr("Refactor", now))])),
+            ]));
+            save_workqueue(ctx.db_client_mut(), &workqueue).await?;
+
+            // Saving again replaces the previous snapshot.
+            let workqueue = ReviewerWorkqueue::new(HashMap::from([(
+                1,
+                HashMap::from([(10, pr("Fix ICE", now))]),
+            )]));
+            save_workqueue(ctx.db_client_mut(), &workqueue).await?;
+
+            let loaded = load_workqueue_snapshot(ctx.db_client()).await?;
+            assert_eq!(loaded.assigned_pr_count(1), 1);
+            assert_eq!(loaded.assigned_pr_count(2), 0);
+            let prs = &loaded.reviewers()[&1];
+            assert_eq!(prs[&10].title, "Fix ICE");
+            assert_eq!(prs[&10].updated_at, now);
+
+            Ok(ctx)
+        })
+        .await;
+    }
+}
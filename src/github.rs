@@ -9,10 +9,10 @@ use regex::Regex;
 use reqwest::header::{AUTHORIZATION, USER_AGENT};
 use reqwest::{Client, Request, RequestBuilder, Response, StatusCode};
 use std::collections::{HashMap, HashSet};
-use std::sync::OnceLock;
+use std::sync::{Arc, Mutex, OnceLock};
 use std::{
     fmt,
-    time::{Duration, SystemTime},
+    time::{Duration, Instant, SystemTime},
 };
 use tracing as log;
 
@@ -207,6 +207,49 @@ impl GithubClient {
         .context("failed to create issue")
     }
 
+    /// Adds `labels` to the issue `number` in `repo`.
+    ///
+    /// Labels that do not exist in the repository are skipped, the other labels are
+    /// still added. Returns an [`UnknownLabels`] error if any label was skipped.
+    pub(crate) async fn add_labels(
+        &self,
+        repo: &IssueRepository,
+        number: u64,
+        labels: Vec<String>,
+    ) -> anyhow::Result<()> {
+        #[derive(serde::Serialize)]
+        struct LabelsReq {
+            labels: Vec<String>,
+        }
+
+        let mut unknown_labels = vec![];
+        let mut known_labels = vec![];
+        for label in labels {
+            if repo.has_label(self, &label).await? {
+                known_labels.push(label);
+            } else {
+                unknown_labels.push(label);
+            }
+        }
+
+        if !known_labels.is_empty() {
+            let url = format!("{}/issues/{number}/labels", repo.url(self));
+            self.send_req(self.post(&url).json(&LabelsReq {
+                labels: known_labels,
+            }))
+            .await
+            .context("failed to add labels")?;
+        }
+
+        if !unknown_labels.is_empty() {
+            return Err(UnknownLabels {
+                labels: unknown_labels,
+            }
+            .into());
+        }
+        Ok(())
+    }
+
     pub(crate) async fn set_pr_state(
         &self,
         repo: &IssueRepository,
@@ -224,6 +267,34 @@ impl GithubClient {
         Ok(())
     }
 
+    pub(crate) async fn get_issue(
+        &self,
+        repo: &IssueRepository,
+        number: u64,
+    ) -> anyhow::Result<Issue> {
+        let url = format!("{}/issues/{number}", repo.url(&self));
+        self.json(self.get(&url))
+            .await
+            .with_context(|| format!("failed to get issue {repo}#{number}"))
+    }
+
+    pub(crate) async fn set_issue_state(
+        &self,
+        repo: &IssueRepository,
+        number: u64,
+        state: PrState,
+    ) -> anyhow::Result<()> {
+        #[derive(serde::Serialize)]
+        struct Update {
+            state: PrState,
+        }
+        let url = format!("{}/issues/{number}", repo.url(&self));
+        self.send_req(self.patch(&url).json(&Update { state }))
+            .await
+            .context("failed to update issue state")?;
+        Ok(())
+    }
+
     pub async fn raw_job_logs(
         &self,
         repo: &IssueRepository,
@@ -311,10 +382,15 @@ pub struct Label {
 ///
 /// Some webhook events include a `pull_request` field in the Issue object,
 /// and some don't. GitHub does include a few fields here, but they aren't
-/// needed at this time (merged_at, diff_url, html_url, patch_url, url).
+/// needed at this time (diff_url, html_url, patch_url, url).
 #[derive(Debug, serde::Deserialize)]
 #[cfg_attr(test, derive(Default))]
 pub struct PullRequestDetails {
+    /// When the pull request was merged.
+    ///
+    /// This is only filled in for issue events (such as issue comments) on pull requests.
+    #[serde(default)]
+    pub merged_at: Option<chrono::DateTime<Utc>>,
     /// This is a slot to hold the diff for a PR.
     ///
     /// This will be filled in only once as an optimization since multiple
@@ -348,11 +424,18 @@ pub struct FileDiff {
     /// (like when a file is renamed without it's content being modified).
     #[serde(default)]
     pub patch: String,
+    /// The number of added and removed lines.
+    ///
+    /// Unlike `patch`, this is also set for files whose patch is omitted by
+    /// GitHub, like large deleted files.
+    #[serde(default)]
+    pub changes: u32,
 }
 
 impl PullRequestDetails {
     pub fn new() -> PullRequestDetails {
         PullRequestDetails {
+            merged_at: None,
             compare: tokio::sync::OnceCell::new(),
         }
     }
@@ -767,19 +850,13 @@ impl Issue {
         Ok(())
     }
 
+    /// Adds `labels` to this issue, see [`GithubClient::add_labels`].
     pub async fn add_labels(
         &self,
         client: &GithubClient,
         labels: Vec<Label>,
     ) -> anyhow::Result<()> {
         log::info!("add_labels: {} +{:?}", self.global_id(), labels);
-        // POST /repos/:owner/:repo/issues/:number/labels
-        // repo_url = https://api.github.com/repos/Codertocat/Hello-World
-        let url = format!(
-            "{repo_url}/issues/{number}/labels",
-            repo_url = self.repository().url(client),
-            number = self.number
-        );
 
         // Don't try to add labels already present on this issue.
         let labels = labels
@@ -794,36 +871,9 @@ impl Issue {
             return Ok(());
         }
 
-        let mut unknown_labels = vec![];
-        let mut known_labels = vec![];
-        for label in labels {
-            if !self.repository().has_label(client, &label).await? {
-                unknown_labels.push(label);
-            } else {
-                known_labels.push(label);
-            }
-        }
-
-        if !unknown_labels.is_empty() {
-            return Err(UnknownLabels {
-                labels: unknown_labels,
-            }
-            .into());
-        }
-
-        #[derive(serde::Serialize)]
-        struct LabelsReq {
-            labels: Vec<String>,
-        }
-
         client
-            .send_req(client.post(&url).json(&LabelsReq {
-                labels: known_labels,
-            }))
+            .add_labels(self.repository(), self.number, labels)
             .await
-            .context("failed to add labels")?;
-
-        Ok(())
     }
 
     pub fn labels(&self) -> &[Label] {
@@ -975,6 +1025,24 @@ impl Issue {
         Ok(())
     }
 
+    /// Adds a reaction (e.g. `eyes`) to the issue itself.
+    pub async fn add_reaction(&self, client: &GithubClient, content: &str) -> anyhow::Result<()> {
+        let url = format!(
+            "{}/issues/{}/reactions",
+            self.repository().url(client),
+            self.number
+        );
+        #[derive(serde::Serialize)]
+        struct Reaction<'a> {
+            content: &'a str,
+        }
+        client
+            .send_req(client.post(&url).json(&Reaction { content }))
+            .await
+            .context("failed to add reaction")?;
+        Ok(())
+    }
+
     pub async fn close(&self, client: &GithubClient) -> anyhow::Result<()> {
         let edit_url = format!("{}/issues/{}", self.repository().url(client), self.number);
         #[derive(serde::Serialize)]
@@ -1133,7 +1201,7 @@ pub struct PullRequestFile {
     pub changes: u64,
 }
 
-#[derive(Debug, serde::Deserialize)]
+#[derive(Debug, Clone, serde::Deserialize, serde::Serialize)]
 pub struct Milestone {
     number: u64,
     title: String,
@@ -1334,6 +1402,25 @@ impl Repository {
         self.full_name.split_once('/').unwrap().1
     }
 
+    /// Returns `true` if the repository has a branch with the given name.
+    pub async fn has_branch(&self, client: &GithubClient, branch: &str) -> anyhow::Result<bool> {
+        let url = format!("{}/branches/{branch}", self.url(client));
+        match client.send_req(client.get(&url)).await {
+            Ok(_) => Ok(true),
+            Err(e) => {
+                if e.downcast_ref::<reqwest::Error>()
+                    .is_some_and(|e| e.status() == Some(StatusCode::NOT_FOUND))
+                {
+                    Ok(false)
+                } else {
+                    Err(e).with_context(|| {
+                        format!("{} failed to get branch {branch}", self.full_name)
+                    })
+                }
+            }
+        }
+    }
+
     pub async fn get_issues<'a>(
         &self,
         client: &GithubClient,
@@ -1967,6 +2054,17 @@ impl Repository {
             .with_context(|| format!("{} failed to get issue {issue_num}", self.full_name))
     }
 
+    /// Fetches a PR, including its base and head commits, unlike [`Repository::get_issue`].
+    pub async fn get_pr(&self, client: &GithubClient, pr_num: u64) -> anyhow::Result<Issue> {
+        let url = format!("{}/pulls/{pr_num}", self.url(client));
+        let mut issue: Issue = client
+            .json(client.get(&url))
+            .await
+            .with_context(|| format!("{} failed to get PR {pr_num}", self.full_name))?;
+        issue.pull_request = Some(PullRequestDetails::new());
+        Ok(issue)
+    }
+
     /// Fetches information about merge conflicts on open PRs.
     pub async fn get_merge_conflict_prs(
         &self,
@@ -2472,8 +2570,16 @@ pub struct GithubClient {
     raw_url: String,
     /// If `true`, requests will sleep if it hits GitHub's rate limit.
     retry_rate_limit: bool,
+    /// Repositories fetched by [`GithubClient::repository`], with their fetch time.
+    repository_cache: Arc<Mutex<HashMap<String, (Repository, Instant)>>>,
 }
 
+/// How long the information returned by [`GithubClient::repository`] is cached.
+const REPOSITORY_CACHE_TTL: Duration = Duration::from_secs(10 * 60);
+
+/// Maximum number of repositories cached by [`GithubClient::repository`].
+const REPOSITORY_CACHE_CAPACITY: usize = 1000;
+
 impl GithubClient {
     pub fn new(token: String, api_url: String, graphql_url: String, raw_url: String) -> Self {
         GithubClient {
@@ -2483,6 +2589,7 @@ impl GithubClient {
             graphql_url,
             raw_url,
             retry_rate_limit: false,
+            repository_cache: Default::default(),
         }
     }
 
@@ -2644,6 +2751,47 @@ impl GithubClient {
         Ok(result)
     }
 
+    /// Returns the open pull requests of a repository, with their labels and
+    /// assignees, in as few GraphQL queries as possible.
+    pub async fn open_pull_requests(
+        &self,
+        owner: &str,
+        name: &str,
+    ) -> anyhow::Result<Vec<github_graphql::open_pull_requests::PullRequest>> {
+        use cynic::QueryBuilder;
+        use github_graphql::open_pull_requests;
+
+        let mut args = open_pull_requests::Arguments {
+            repository_owner: owner,
+            repository_name: name,
+            after: None,
+        };
+
+        let mut prs = vec![];
+        loop {
+            let query = open_pull_requests::Query::build(args.clone());
+            let req = self.post(&self.graphql_url).json(&query);
+
+            let data: cynic::GraphQlResponse<open_pull_requests::Query> = self.json(req).await?;
+            if let Some(errors) = data.errors {
+                anyhow::bail!("There were graphql errors. {:?}", errors);
+            }
+            let repository = data
+                .data
+                .ok_or_else(|| anyhow::anyhow!("No data returned."))?
+                .repository
+                .ok_or_else(|| anyhow::anyhow!("No repository."))?;
+            prs.extend(repository.pull_requests.nodes);
+
+            let page_info = repository.pull_requests.page_info;
+            if !page_info.has_next_page || page_info.end_cursor.is_none() {
+                break;
+            }
+            args.after = page_info.end_cursor;
+        }
+        Ok(prs)
+    }
+
     /// Returns the object ID of the given user.
     ///
     /// Returns `None` if the user doesn't exist.
@@ -2750,11 +2898,35 @@ impl GithubClient {
     /// Returns information about a repository.
     ///
     /// The `full_name` should be something like `rust-lang/rust`.
+    ///
+    /// The result is cached for a few minutes, as things like the default
+    /// branch rarely change. Expired entries are dropped whenever a repository
+    /// is fetched, and the cache is bounded to [`REPOSITORY_CACHE_CAPACITY`]
+    /// repositories.
     pub async fn repository(&self, full_name: &str) -> anyhow::Result<Repository> {
+        if let Some((repo, fetch_time)) = self.repository_cache.lock().unwrap().get(full_name)
+            && fetch_time.elapsed() < REPOSITORY_CACHE_TTL
+        {
+            log::trace!("returning repository {full_name} from cache");
+            return Ok(repo.clone());
+        }
         let req = self.get(&format!("{}/repos/{full_name}", self.api_url));
-        self.json(req)
+        let repo: Repository = self
+            .json(req)
             .await
-            .with_context(|| format!("{} failed to get repo", full_name))
+            .with_context(|| format!("{} failed to get repo", full_name))?;
+        let mut cache = self.repository_cache.lock().unwrap();
+        cache.retain(|_, (_, fetch_time)| fetch_time.elapsed() < REPOSITORY_CACHE_TTL);
+        if cache.len() >= REPOSITORY_CACHE_CAPACITY
+            && let Some(oldest) = cache
+                .iter()
+                .min_by_key(|(_, (_, fetch_time))| *fetch_time)
+                .map(|(name, _)| name.clone())
+        {
+            cache.remove(&oldest);
+        }
+        cache.insert(full_name.to_string(), (repo.clone(), Instant::now()));
+        Ok(repo)
     }
 
     /// Get or create a [`Milestone`].
@@ -2828,6 +3000,21 @@ impl GithubClient {
         Ok(())
     }
 
+    /// Remove the milestone of an issue or PR.
+    pub async fn remove_milestone(
+        &self,
+        full_repo_name: &str,
+        issue_num: u64,
+    ) -> anyhow::Result<()> {
+        let url = format!("{}/repos/{full_repo_name}/issues/{issue_num}", self.api_url);
+        self.send_req(self.patch(&url).json(&serde_json::json!({
+            "milestone": null
+        })))
+        .await
+        .with_context(|| format!("failed to remove the milestone of {url}"))?;
+        Ok(())
+    }
+
     /// Returns the GraphQL ID of the given repository.
     async fn graphql_repo_id(&self, owner: &str, repo: &str) -> anyhow::Result<String> {
         let mut repo_id = self
@@ -3231,6 +3418,8 @@ impl Submodule {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::tests::mock_github::MockGithub;
+    use axum::http::Method;
 
     #[test]
     fn display_labels() {
@@ -3239,4 +3428,30 @@ mod tests {
         };
         assert_eq!(x.to_string(), "Unknown labels: A-bootstrap, xxx");
     }
+
+    #[tokio::test]
+    async fn repository_is_cached() {
+        let mock = MockGithub::start().await;
+        mock.respond(
+            Method::GET,
+            "/repos/rust-lang/rust",
+            serde_json::json!({
+                "full_name": "rust-lang/rust",
+                "default_branch": "main",
+                "parent": null,
+            }),
+        );
+        let client = GithubClient::new(
+            "gh-test-fake-token".to_string(),
+            mock.url().to_string(),
+            format!("{}/graphql", mock.url()),
+            mock.url().to_string(),
+        );
+
+        let repo = client.repository("rust-lang/rust").await.unwrap();
+        assert_eq!(repo.default_branch, "main");
+        let repo = client.clone().repository("rust-lang/rust").await.unwrap();
+        assert_eq!(repo.default_branch, "main");
+        assert_eq!(mock.requests().len(), 1);
+    }
 }
@@ -1,6 +1,8 @@
 use crate::config::{self, Config, ConfigurationError};
 use crate::gha_logs::GitHubActionLogsCache;
 use crate::github::{Event, GithubClient, IssueCommentAction, IssuesAction, IssuesEvent};
+use crate::handlers::assign::OwnersFilesCache;
+use crate::handlers::concurrency::RepoConcurrencyLimiter;
 use crate::handlers::pr_tracking::ReviewerWorkqueue;
 use crate::team_data::TeamClient;
 use crate::zulip::client::ZulipClient;
@@ -27,13 +29,32 @@ impl fmt::Display for HandlerError {
     }
 }
 
-mod assign;
+/// Error returned by the `parse_input` function of the issue handlers.
+#[derive(Debug)]
+pub(crate) enum ParseInputError {
+    /// The event has invalid content, which is reported to the user.
+    InvalidInput(String),
+    /// Something unexpected happened, which is only logged.
+    Internal(String),
+}
+
+impl From<ParseInputError> for HandlerError {
+    fn from(err: ParseInputError) -> Self {
+        match err {
+            ParseInputError::InvalidInput(msg) => HandlerError::Message(msg),
+            ParseInputError::Internal(msg) => HandlerError::Other(anyhow::anyhow!(msg)),
+        }
+    }
+}
+
+pub(crate) mod assign;
 mod autolabel;
 mod backport;
 mod bot_pull_requests;
-mod check_commits;
+pub mod check_commits;
 mod close;
 mod concern;
+pub mod concurrency;
 pub mod docs_update;
 mod github_releases;
 mod issue_links;
@@ -51,16 +72,21 @@ mod prioritize;
 pub mod project_goals;
 pub mod pull_requests_assignment_update;
 mod relabel;
-mod relnotes;
+pub(crate) mod relnotes;
 mod rendered_link;
 mod review_requested;
 mod review_submitted;
+mod reviewer_activity;
 pub mod rustc_commits;
 mod shortcut;
 mod transfer;
+
+use check_commits::behind_upstream;
 pub mod types_planning_updates;
+pub mod unassigned_prs_digest;
 
 pub async fn handle(ctx: &Context, event: &Event) -> Vec<HandlerError> {
+    let _permit = ctx.repo_limiter.acquire(&event.repo().full_name).await;
     let config = config::get(&ctx.github, event.repo()).await;
     if let Err(e) = &config {
         log::warn!("configuration error {}: {e}", event.repo().full_name);
@@ -128,7 +154,8 @@ pub async fn handle(ctx: &Context, event: &Event) -> Vec<HandlerError> {
         }
     }
 
-    if let Err(e) = relnotes::handle(ctx, event).await {
+    let relnotes_config = config.as_ref().ok().and_then(|c| c.relnotes.as_ref());
+    if let Err(e) = relnotes::handle(ctx, event, relnotes_config).await {
         log::error!(
             "failed to process event {:?} with relnotes handler: {:?}",
             event,
@@ -146,6 +173,16 @@ pub async fn handle(ctx: &Context, event: &Event) -> Vec<HandlerError> {
         }
     }
 
+    if config.as_ref().is_ok_and(|c| c.assign.is_some()) {
+        if let Err(e) = reviewer_activity::handle(ctx, event).await {
+            log::error!(
+                "failed to process event {:?} with reviewer_activity handler: {:?}",
+                event,
+                e
+            )
+        }
+    }
+
     if let Some(config) = config
         .as_ref()
         .ok()
@@ -201,7 +238,7 @@ macro_rules! issue_handlers {
         ) {
             $(
             match $name::parse_input(ctx, event, config.$name.as_ref()).await {
-                Err(err) => errors.push(HandlerError::Message(err)),
+                Err(err) => errors.push(err.into()),
                 Ok(Some(input)) => {
                     if let Some(config) = &config.$name {
                         $name::handle_input(ctx, config, event, input).await.unwrap_or_else(|err| errors.push(HandlerError::Other(err)));
@@ -237,7 +274,7 @@ issue_handlers! {
 }
 
 macro_rules! command_handlers {
-    ($($name:ident: $enum:ident,)*) => {
+    ($($name:ident: $enum:ident $(= $default:path)?,)*) => {
         async fn handle_command(
             ctx: &Context,
             event: &Event,
@@ -327,7 +364,19 @@ macro_rules! command_handlers {
                 match command {
                     $(
                     Command::$enum(Ok(command)) => {
-                        if let Some(config) = &config.$name {
+                        let section = config.$name.as_ref();
+                        $(
+                        // Some handlers also run without their section, with a default config.
+                        let default;
+                        let section = match section {
+                            Some(section) => Some(section),
+                            None => {
+                                default = $default(event);
+                                default.as_ref()
+                            }
+                        };
+                        )?
+                        if let Some(config) = section {
                             $name::handle_command(ctx, config, event, command)
                                 .await
                                 .unwrap_or_else(|err| errors.push(HandlerError::Other(err)));
@@ -358,7 +407,9 @@ macro_rules! command_handlers {
 //
 // This is for handlers for commands parsed by the `parser` crate.
 // Each variant of `parser::command::Command` must be in this list,
-// preceded by the module containing the corresponding `handle_command` function
+// preceded by the module containing the corresponding `handle_command` function,
+// and optionally followed by a function returning the config to use when the
+// section of the module is missing.
 command_handlers! {
     assign: Assign,
     nominate: Nominate,
@@ -371,6 +422,8 @@ command_handlers! {
     note: Note,
     concern: Concern,
     transfer: Transfer,
+    behind_upstream: Behind,
+    relnotes: Relnotes = relnotes::default_config,
 }
 
 pub struct Context {
@@ -384,4 +437,8 @@ pub struct Context {
     /// tokio's RwLock is used to avoid deadlocks, since we run on a single-threaded tokio runtime.
     pub workqueue: Arc<tokio::sync::RwLock<ReviewerWorkqueue>>,
     pub gha_logs: Arc<tokio::sync::RwLock<GitHubActionLogsCache>>,
+    /// Bounds the number of events handled concurrently for each repository.
+    pub repo_limiter: Arc<RepoConcurrencyLimiter>,
+    /// The owners files recently loaded for the PRs, when assigning reviewers.
+    pub owners_files: Arc<std::sync::Mutex<OwnersFilesCache>>,
 }
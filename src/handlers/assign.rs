@@ -3,9 +3,17 @@
 //! This supports several ways for setting issue/PR assignment:
 //!
 //! * `@rustbot assign @gh-user`: Assigns to the given user.
-//! * `@rustbot claim`: Assigns to the comment author.
+//! * `@rustbot claim`: Assigns to the comment author. On PRs, reviewers at
+//!   their review capacity need `@rustbot claim --force`.
 //! * `@rustbot release-assignment`: Removes the commenter's assignment.
-//! * `r? @user`: Assigns to the given user (PRs only).
+//! * `r? @user`: Assigns to the given user (PRs only). On a recently merged PR,
+//!   team members can use it to record a post-merge reviewer instead.
+//! * `@rustbot away "message"`: Marks the commenter as away, with an optional
+//!   `until YYYY-MM-DD` expiry. `@rustbot back` clears it.
+//! * `@rustbot assign rollup`: Removes the assignees of a PR that doesn't need
+//!   a reviewer, such as a rollup, and keeps it from being auto-assigned later.
+//! * `@rustbot owners`: Lists the `assign.owners` patterns matching the files
+//!   modified by the PR, along with their reviewers (PRs only).
 //!
 //! Note: this module does not handle review assignments issued from the
 //! GitHub "Assignees" dropdown menu
@@ -18,23 +26,38 @@
 //!
 //! This also supports auto-assignment of new PRs. Based on rules in the
 //! `assign.owners` config, it will auto-select an assignee based on the files
-//! the PR modifies.
+//! the PR modifies. Paths owned by `ghost` (or nobody) suppress the
+//! auto-assignment when they are the most modified area.
 
+use crate::db::assignment_events::{AssignmentEvent, AssignmentSource, record_assignment_event};
 use crate::db::issue_data::IssueData;
-use crate::db::review_prefs::{RotationMode, get_review_prefs_batch};
+use crate::db::review_prefs::{
+    RotationMode, get_review_prefs, get_review_prefs_batch, set_away_status, set_vacation,
+    upsert_review_prefs,
+};
+use crate::db::review_rotation::{get_last_reviewer, record_last_reviewer};
+use crate::db::reviewer_activity::get_last_activity_batch;
+use crate::db::reviewer_assignments::{get_last_assigned_batch, record_last_assigned};
+use crate::db::users::get_user_ids_batch;
+use crate::db::welcomed_authors::{record_welcomed_author, was_author_welcomed};
 use crate::github::UserId;
 use crate::handlers::pr_tracking::ReviewerWorkqueue;
 use crate::{
-    config::AssignConfig,
-    github::{self, Event, FileDiff, Issue, IssuesAction, Selection},
-    handlers::{Context, GithubClient, IssuesEvent},
+    config::{
+        AssignConfig, AssignTrigger, MultipleReviewRequests, ReviewerSelectionMode, WelcomeStyle,
+    },
+    github::{self, Event, FileDiff, Issue, IssuesAction, Repository, Selection, User},
+    handlers::{Context, GithubClient, IssuesEvent, ParseInputError},
     interactions::EditIssueBody,
 };
 use anyhow::{Context as _, bail};
+use futures::StreamExt as _;
 use octocrab::models::AuthorAssociation;
+use owners_files::OwnersFiles;
 use parser::command::assign::AssignCommand;
 use parser::command::{Command, Input};
-use rand::seq::IteratorRandom;
+use rand::Rng;
+use rand::distributions::{Distribution, WeightedIndex};
 use rust_team_data::v1::Teams;
 use std::collections::{HashMap, HashSet};
 use std::fmt;
@@ -43,25 +66,125 @@ use tokio::sync::RwLock;
 use tokio_postgres::Client as DbClient;
 use tracing as log;
 
+mod auto_reassign;
 mod messages;
+mod owners_files;
+mod proposal;
+
+pub(crate) use auto_reassign::{AutoReassignJob, AutoReassignMetadata};
+pub(crate) use owners_files::OwnersFilesCache;
+pub(crate) use proposal::ReviewProposalTimeoutJob;
 
 #[cfg(test)]
 mod tests {
     mod tests_candidates;
     mod tests_from_diff;
+    mod tests_input;
 }
 
 // Special account that we use to prevent assignment.
 const GHOST_ACCOUNT: &str = "ghost";
 
+/// Tracing target for the verbose owner matching diagnostics of [`find_reviewers_from_diff`].
+///
+/// Enable them with `RUST_LOG=assign::owners=debug`.
+const OWNERS_LOG_TARGET: &str = "assign::owners";
+
+/// Tracing target for the expansion tree of teams and groups built by
+/// [`expand_teams_and_groups`].
+///
+/// Enable it with `RUST_LOG=assign::expansion=debug`.
+const EXPANSION_LOG_TARGET: &str = "assign::expansion";
+
+/// Number of attempts to assign a reviewer on GitHub before giving up.
+const SET_ASSIGNEE_ATTEMPTS: u32 = 3;
+
+/// Delay before the first retry to assign a reviewer, doubled on each retry.
+#[cfg(not(test))]
+const SET_ASSIGNEE_RETRY_DELAY: std::time::Duration = std::time::Duration::from_secs(1);
+#[cfg(test)]
+const SET_ASSIGNEE_RETRY_DELAY: std::time::Duration = std::time::Duration::from_millis(1);
+
 /// Key for the state in the database
 const PREVIOUS_REVIEWERS_KEY: &str = "previous-reviewers";
 
+/// Key for the post-merge reviewer stored in the PR body
+const POST_MERGE_REVIEW_KEY: &str = "POST_MERGE_REVIEW";
+
+/// Key for the state in the database recording that a PR doesn't need a reviewer
+const NO_REVIEWER_KEY: &str = "no-reviewer";
+
+/// Key for the state in the database recording the team labels added by `r?`
+const BOT_TEAM_LABELS_KEY: &str = "bot-team-labels";
+
+/// Key for the state in the database recording that a PR went through
+/// auto-assignment
+const AUTO_ASSIGNED_KEY: &str = "auto-assigned";
+
+/// Key for the state in the database recording that the PR author was told
+/// that all the reviewers of the diff are off the review rotation
+const OFF_ROTATION_WARNING_KEY: &str = "off-rotation-warning";
+
+/// Key for the state in the database recording that the PR author was welcomed
+const WELCOMED_KEY: &str = "welcomed";
+
+/// Diffs with at least this many modified lines are described as large in the
+/// welcome message, rather than with exact counts.
+const LARGE_DIFF_LINES: u32 = 5000;
+
+/// Maximum number of files returned by the GitHub compare API. Diffs with that
+/// many files may have been truncated.
+const MAX_COMPARE_FILES: usize = 300;
+
+/// Maximum number of unassigned PRs inspected by `@rustbot assign next`.
+const MAX_NEXT_PR_CANDIDATES: usize = 30;
+
+/// Number of PRs inspected concurrently by `@rustbot assign next`.
+const NEXT_PR_CONCURRENCY: usize = 5;
+
+/// For how many days after a merge a post-merge review can be requested with `r?`.
+const POST_MERGE_REVIEW_DAYS: i64 = 14;
+
 /// State stored in the database
 #[derive(Debug, Clone, PartialEq, Default, serde::Deserialize, serde::Serialize)]
 struct Reviewers {
     // names are stored in lowercase
     names: HashSet<String>,
+    /// Reviewers who declined the PR with `not-me`, in lowercase.
+    #[serde(default)]
+    declined: HashSet<String>,
+    /// When the current reviewer was assigned.
+    #[serde(default)]
+    assigned_at: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+/// Where the reviewer found by [`determine_assignee`] comes from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum AssigneeSource {
+    /// An `r?` command in the PR body.
+    Comment,
+    /// The owners of the PR labels or of the modified files.
+    Owners,
+    /// The fallback group, because none of the owners could be assigned.
+    Fallback,
+}
+
+impl From<AssigneeSource> for AssignmentSource {
+    fn from(source: AssigneeSource) -> Self {
+        match source {
+            AssigneeSource::Comment => AssignmentSource::ReviewRequest,
+            AssigneeSource::Owners | AssigneeSource::Fallback => AssignmentSource::Diff,
+        }
+    }
+}
+
+/// Team labels added to a PR by triagebot, stored in the database.
+///
+/// Labels added by someone else are never recorded here, so that they are
+/// never removed by triagebot.
+#[derive(Debug, Clone, PartialEq, Default, serde::Deserialize, serde::Serialize)]
+struct BotTeamLabels {
+    names: HashSet<String>,
 }
 
 /// Assignment data stored in the issue/PR body.
@@ -70,11 +193,28 @@ struct AssignData {
     user: Option<String>,
 }
 
-/// Input for auto-assignment when a PR is created or converted from draft.
+/// Returns whether `user` is assigned to `issue`.
+///
+/// Users that cannot be assigned on GitHub are recorded in the `ASSIGN` data
+/// while the bot assigns itself in their place, so both are consulted.
+fn is_effective_assignee(issue: &Issue, data: &AssignData, bot: &str, user: &str) -> bool {
+    if issue.contain_assignee(user) {
+        return true;
+    }
+    data.user
+        .as_deref()
+        .is_some_and(|assigned| assigned.eq_ignore_ascii_case(user))
+        && issue.contain_assignee(bot)
+}
+
+/// Input for auto-assignment when a PR is created, converted from draft,
+/// reopened, or when its last blocking label is removed.
 #[derive(Debug)]
 pub(super) enum AssignInput {
     Opened { draft: bool },
     ReadyForReview,
+    Reopened,
+    Unblocked,
 }
 
 /// Prepares the input when a new PR is opened.
@@ -82,16 +222,34 @@ pub(super) async fn parse_input(
     _ctx: &Context,
     event: &IssuesEvent,
     config: Option<&AssignConfig>,
-) -> Result<Option<AssignInput>, String> {
-    if config.is_none() || !event.issue.is_pr() {
+) -> Result<Option<AssignInput>, ParseInputError> {
+    let Some(config) = config else {
+        return Ok(None);
+    };
+    if !event.issue.is_pr() || !config.is_enabled_for(&event.repository.full_name) {
         return Ok(None);
     }
 
+    let triggered = |trigger| config.trigger_on.contains(&trigger);
     match event.action {
-        IssuesAction::Opened => Ok(Some(AssignInput::Opened {
+        IssuesAction::Opened if triggered(AssignTrigger::Opened) => Ok(Some(AssignInput::Opened {
             draft: event.issue.draft,
         })),
-        IssuesAction::ReadyForReview => Ok(Some(AssignInput::ReadyForReview)),
+        IssuesAction::ReadyForReview if triggered(AssignTrigger::ReadyForReview) => {
+            Ok(Some(AssignInput::ReadyForReview))
+        }
+        IssuesAction::Reopened if triggered(AssignTrigger::Reopened) && !event.issue.draft => {
+            Ok(Some(AssignInput::Reopened))
+        }
+        // Like when they are reopened, drafts are only assigned once ready for review.
+        IssuesAction::Unlabeled {
+            label: Some(ref label),
+        } if config.blocking_labels.contains(&label.name)
+            && !is_blocked(config, &event.issue)
+            && !event.issue.draft =>
+        {
+            Ok(Some(AssignInput::Unblocked))
+        }
         _ => Ok(None),
     }
 }
@@ -104,12 +262,13 @@ pub(super) async fn handle_input(
     event: &IssuesEvent,
     input: AssignInput,
 ) -> anyhow::Result<()> {
-    let assign_command = find_assign_command(ctx, event);
+    let review_requests = find_assign_commands(ctx, &event.issue);
+    let assign_command = select_assign_command(config, &review_requests);
 
     // Perform assignment when:
     // - PR was opened normally
     // - PR was opened as a draft with an explicit r? (but not r? ghost)
-    // - PR was converted from a draft and there are no current assignees
+    // - PR was converted from a draft for the first time and there are no current assignees
     let should_assign = match input {
         AssignInput::Opened { draft: false } => true,
         AssignInput::Opened { draft: true } => {
@@ -119,7 +278,15 @@ pub(super) async fn handle_input(
             // the PR has been marked as being ready for review.
             assign_command.as_ref().is_some_and(|a| a != GHOST_ACCOUNT)
         }
-        AssignInput::ReadyForReview => event.issue.assignees.is_empty(),
+        AssignInput::ReadyForReview => {
+            // Toggling the draft status again must not assign another reviewer.
+            event.issue.assignees.is_empty()
+                && !is_no_reviewer_requested(ctx, &event.issue).await?
+                && !was_auto_assigned(ctx, &event.issue).await?
+        }
+        AssignInput::Reopened | AssignInput::Unblocked => {
+            event.issue.assignees.is_empty() && !is_no_reviewer_requested(ctx, &event.issue).await?
+        }
     };
 
     if !should_assign {
@@ -127,17 +294,74 @@ pub(super) async fn handle_input(
         return Ok(());
     }
 
+    if is_blocked(config, &event.issue) {
+        log::info!(
+            "Skipping PR assignment for blocked PR {}, it will be assigned once unblocked",
+            event.issue.global_id()
+        );
+        return Ok(());
+    }
+
+    // Like `r? ghost`, the opt-out label means no assignee and no welcome.
+    if has_no_assign_label(config, &event.issue) {
+        log::info!(
+            "Skipping PR assignment for {}, it has the no-assign label",
+            event.issue.global_id()
+        );
+        return Ok(());
+    }
+
+    let is_ignored_author = is_ignored_author(config, &event.issue.user.login);
+    if is_ignored_author && config.ignore_authors_assignment {
+        log::info!(
+            "Skipping PR assignment for {}, its author {} is ignored",
+            event.issue.global_id(),
+            event.issue.user.login
+        );
+        return Ok(());
+    }
+
+    if assign_command.is_none() && review_requests.len() > 1 {
+        // The `multiple_review_requests` policy rejected the `r?` commands.
+        event
+            .issue
+            .post_comment(
+                &ctx.github,
+                &messages::multiple_review_requests_message(&review_requests),
+            )
+            .await?;
+    }
+
     let Some(diff) = event.issue.diff(&ctx.github).await? else {
-        bail!(
-            "expected issue {} to be a PR, but the diff could not be determined",
-            event.issue.number
-        )
+        // GitHub may not have computed the diff yet for very fresh PRs. This is not worth
+        // reporting as an error, the PR can still be assigned manually with `r?`.
+        log::info!(
+            "Skipping PR assignment for {}, the diff could not be determined",
+            event.issue.global_id()
+        );
+        return Ok(());
     };
 
     // Don't auto-assign or welcome if the user manually set the assignee when opening.
     if event.issue.assignees.is_empty() {
-        let (assignee, from_comment) =
-            determine_assignee(ctx, assign_command, event, config, &diff).await?;
+        let owners_files = load_owners_files(ctx, config, &event.issue, diff).await;
+        if assign_command.is_none()
+            && let Some(threshold) = config.multi_area_threshold_percent
+            && let Some(areas) = multi_area_owners(config, &owners_files, &diff, threshold)?
+        {
+            return label_multi_area_pr(ctx, config, &event.issue, &areas).await;
+        }
+        let (assignee, source) = determine_assignee(
+            ctx,
+            assign_command,
+            &event.issue,
+            config,
+            &owners_files,
+            &diff,
+            false,
+        )
+        .await?;
+        record_auto_assigned(ctx, &event.issue).await?;
         if assignee.as_ref().map(|r| r.name.as_str()) == Some(GHOST_ACCOUNT) {
             // "ghost" is GitHub's placeholder account for deleted accounts.
             // It is used here as a convenient way to prevent assignment. This
@@ -145,8 +369,23 @@ pub(super) async fn handle_input(
             // want any assignments or noise.
             return Ok(());
         }
+        if config.require_acceptance
+            && source != AssigneeSource::Comment
+            && let Some(assignee) = &assignee
+        {
+            // The proposal comment replaces the welcome, which announces the assignment.
+            return proposal::propose_reviewer(ctx, config, &event.issue, assignee).await;
+        }
+        let assigned_pr_count = match &assignee {
+            Some(assignee) if config.welcome_assigned_pr_count => {
+                assigned_pr_count(ctx, &assignee.name).await?
+            }
+            _ => None,
+        };
+        // Whether the welcome is the full one, which `welcome_once` only posts once.
+        let mut full_welcome = false;
         let welcome = if let Some(custom_messages) = &config.custom_messages {
-            if !from_comment {
+            if source != AssigneeSource::Comment {
                 let mut welcome = match &assignee {
                     Some(assignee) => custom_messages
                         .auto_assign_someone
@@ -161,7 +400,9 @@ pub(super) async fn handle_input(
                     if matches!(
                         event.issue.author_association,
                         AuthorAssociation::FirstTimer | AuthorAssociation::FirstTimeContributor
-                    ) {
+                    ) && is_first_welcome(ctx, config, &event.issue).await?
+                    {
+                        full_welcome = true;
                         welcome.push_str("\n\n");
                         welcome.push_str(&messages::contribution_message(contrib, &ctx.username));
                     }
@@ -175,22 +416,39 @@ pub(super) async fn handle_input(
             event.issue.author_association,
             AuthorAssociation::FirstTimer | AuthorAssociation::FirstTimeContributor
         ) {
-            let assignee_text = match &assignee {
-                Some(assignee) => messages::welcome_with_reviewer(&assignee.name),
-                None => messages::WELCOME_WITHOUT_REVIEWER.to_string(),
-            };
-            let mut welcome = messages::new_user_welcome_message(&assignee_text);
-            if let Some(contrib) = &config.contributing_url {
-                welcome.push_str("\n\n");
-                welcome.push_str(&messages::contribution_message(contrib, &ctx.username));
+            if is_first_welcome(ctx, config, &event.issue).await? {
+                full_welcome = true;
+                let assignee_text = match &assignee {
+                    Some(assignee) => messages::welcome_with_reviewer(&assignee.name),
+                    None => messages::WELCOME_WITHOUT_REVIEWER.to_string(),
+                };
+                let mut welcome = messages::new_user_welcome_message(&assignee_text);
+                if let Some(contrib) = &config.contributing_url {
+                    welcome.push_str("\n\n");
+                    welcome.push_str(&messages::contribution_message(contrib, &ctx.username));
+                }
+                Some(welcome)
+            } else {
+                Some(messages::short_welcome_message(
+                    assignee.as_ref().map(|a| a.name.as_str()),
+                ))
             }
-            Some(welcome)
-        } else if !from_comment {
+        } else if source != AssigneeSource::Comment {
             match &assignee {
-                Some(assignee) => Some(messages::returning_user_welcome_message(
-                    &assignee.name,
-                    &ctx.username,
-                )),
+                Some(assignee) => {
+                    let mut welcome =
+                        messages::returning_user_welcome_message(&assignee.name, &ctx.username);
+                    if let Some(count) = assigned_pr_count {
+                        welcome.push_str("\n\n");
+                        welcome
+                            .push_str(&messages::assigned_pr_count_message(&assignee.name, count));
+                    }
+                    if config.welcome_diff_size {
+                        welcome.push_str("\n\n");
+                        welcome.push_str(&diff_size_message(diff));
+                    }
+                    Some(welcome)
+                }
                 None => {
                     // If the assign fallback group is empty, then we don't expect any automatic
                     // assignment, and this message would just be spam.
@@ -207,16 +465,47 @@ pub(super) async fn handle_input(
             // No welcome is posted if they are not new and they used `r?` in the opening body.
             None
         };
+        // A reaction only replaces the welcome announcing the reviewer, the
+        // author still needs to know when no reviewer was found, and new
+        // contributors need the full welcome.
+        let welcome_style = match config.welcome_style {
+            WelcomeStyle::Reaction if assignee.is_none() || full_welcome => WelcomeStyle::Comment,
+            style => style,
+        };
         if let Some(assignee) = assignee {
-            set_assignee(&ctx, &event.issue, &ctx.github, &assignee).await?;
+            set_assignee(&ctx, &event.issue, &ctx.github, &assignee, source.into()).await?;
         }
 
-        if let Some(welcome) = welcome {
-            if let Err(e) = event.issue.post_comment(&ctx.github, &welcome).await {
-                log::warn!(
-                    "failed to post welcome comment to {}: {e}",
-                    event.issue.global_id()
-                );
+        // PRs that were reopened or unblocked were opened a while ago, only assign them.
+        // The author is also only welcomed once, should the opening be handled again.
+        if let Some(welcome) = welcome
+            && !is_ignored_author
+            && !matches!(input, AssignInput::Reopened | AssignInput::Unblocked)
+            && !was_welcomed(ctx, &event.issue).await?
+        {
+            let result = match welcome_style {
+                WelcomeStyle::Comment => event
+                    .issue
+                    .post_comment(&ctx.github, &welcome)
+                    .await
+                    .map(|_| ()),
+                WelcomeStyle::Reaction => event.issue.add_reaction(&ctx.github, "eyes").await,
+                WelcomeStyle::None => Ok(()),
+            };
+            match result {
+                Ok(()) => {
+                    record_welcomed(ctx, &event.issue).await?;
+                    // The author only saw the full welcome if it was posted.
+                    if full_welcome
+                        && config.welcome_once
+                        && matches!(welcome_style, WelcomeStyle::Comment)
+                    {
+                        let db = ctx.db.get().await;
+                        let repo = event.issue.repository().to_string();
+                        record_welcomed_author(&db, &repo, event.issue.user.id).await?;
+                    }
+                }
+                Err(e) => log::warn!("failed to welcome {}: {e}", event.issue.global_id()),
             }
         }
     }
@@ -224,15 +513,139 @@ pub(super) async fn handle_input(
     Ok(())
 }
 
-/// Finds the `r?` command in the PR body.
+/// Returns true if the author of `issue` should get the full welcome message.
+///
+/// With `welcome_once`, this is only the case until the full welcome was
+/// posted on one of their PRs in the repository.
+async fn is_first_welcome(
+    ctx: &Context,
+    config: &AssignConfig,
+    issue: &Issue,
+) -> anyhow::Result<bool> {
+    if !config.welcome_once {
+        return Ok(true);
+    }
+    let db = ctx.db.get().await;
+    let repo = issue.repository().to_string();
+    Ok(!was_author_welcomed(&db, &repo, issue.user.id).await?)
+}
+
+/// Returns true if the PR has any of the `blocking_labels`.
+fn is_blocked(config: &AssignConfig, issue: &Issue) -> bool {
+    issue
+        .labels()
+        .iter()
+        .any(|label| config.blocking_labels.contains(&label.name))
+}
+
+/// Returns true if the PR has the `no_assign_label`, if configured.
+fn has_no_assign_label(config: &AssignConfig, issue: &Issue) -> bool {
+    config
+        .no_assign_label
+        .as_ref()
+        .is_some_and(|no_assign| issue.labels().iter().any(|label| &label.name == no_assign))
+}
+
+/// Returns true if `author` matches one of the `ignore_authors` patterns.
+fn is_ignored_author(config: &AssignConfig, author: &str) -> bool {
+    config
+        .ignore_authors
+        .iter()
+        .any(|pattern| matches_author_pattern(pattern, author))
+}
+
+/// Matches `author` against `pattern`, ignoring case, where `*` matches any
+/// characters. Unlike regular globs, `[bot]` is not a character class.
+fn matches_author_pattern(pattern: &str, author: &str) -> bool {
+    let pattern = pattern.to_lowercase();
+    let author = author.to_lowercase();
+    let mut parts = pattern.split('*');
+    // `split` always yields at least one part.
+    let first = parts.next().unwrap();
+    let Some(mut rest) = author.strip_prefix(first) else {
+        return false;
+    };
+    let mut parts: Vec<&str> = parts.collect();
+    let Some(last) = parts.pop() else {
+        // No `*`, the whole author must match.
+        return rest.is_empty();
+    };
+    for part in parts {
+        let Some(index) = rest.find(part) else {
+            return false;
+        };
+        rest = &rest[index + part.len()..];
+    }
+    rest.ends_with(last)
+}
+
+/// Finds the `r?` commands in the PR body.
 ///
-/// Returns the name after the `r?` command, or None if not found.
-fn find_assign_command(ctx: &Context, event: &IssuesEvent) -> Option<String> {
-    let mut input = Input::new(&event.issue.body, vec![&ctx.username]);
-    input.find_map(|command| match command {
-        Command::Assign(Ok(AssignCommand::RequestReview { name })) => Some(name),
-        _ => None,
-    })
+/// Returns the names after the `r?` commands, in order.
+fn find_assign_commands(ctx: &Context, issue: &Issue) -> Vec<String> {
+    Input::new(&issue.body, vec![&ctx.username])
+        .filter_map(|command| match command {
+            Command::Assign(Ok(AssignCommand::RequestReview { name, .. })) => Some(name),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Picks the `r?` command to use among `names`, according to the
+/// `multiple_review_requests` policy.
+///
+/// Returns None if there are none, or if the policy rejects several of them.
+fn select_assign_command(config: &AssignConfig, names: &[String]) -> Option<String> {
+    match (names, config.multiple_review_requests) {
+        ([name], _) => Some(name.clone()),
+        (_, MultipleReviewRequests::FirstWins) => names.first().cloned(),
+        (_, MultipleReviewRequests::LastWins) => names.last().cloned(),
+        (_, MultipleReviewRequests::Error) => None,
+    }
+}
+
+/// Returns `true` if the PR body opted out of reviewer assignment with `r? ghost`.
+pub(super) fn is_ghost_requested(ctx: &Context, issue: &Issue) -> bool {
+    // This doesn't depend on the `multiple_review_requests` policy, any `r? ghost`
+    // is a sign that the author doesn't want a reviewer to be assigned yet.
+    find_assign_commands(ctx, issue)
+        .iter()
+        .any(|name| name == GHOST_ACCOUNT)
+}
+
+/// Returns the number of PRs currently assigned to `username`, or None if
+/// they don't have any review preferences.
+async fn assigned_pr_count(ctx: &Context, username: &str) -> anyhow::Result<Option<u64>> {
+    let db = ctx.db.get().await;
+    let review_prefs = get_review_prefs_batch(&db, &[username])
+        .await
+        .context("cannot fetch review preferences")?;
+    let Some(review_prefs) = review_prefs.get(username) else {
+        return Ok(None);
+    };
+    let workqueue = ctx.workqueue.read().await;
+    Ok(Some(
+        workqueue.assigned_pr_count(review_prefs.user_id as UserId),
+    ))
+}
+
+/// Describes the size of `diff` for the welcome message.
+fn diff_size_message(diff: &[FileDiff]) -> String {
+    let mut added = 0;
+    let mut removed = 0;
+    for line in diff.iter().flat_map(|file_diff| file_diff.patch.lines()) {
+        if !line.starts_with("+++") && line.starts_with('+') {
+            added += 1;
+        } else if !line.starts_with("---") && line.starts_with('-') {
+            removed += 1;
+        }
+    }
+    // Exact counts would be misleading if the diff was truncated.
+    if added + removed >= LARGE_DIFF_LINES || diff.len() >= MAX_COMPARE_FILES {
+        messages::large_diff_message(LARGE_DIFF_LINES)
+    } else {
+        messages::diff_size_message(added, removed, diff.len())
+    }
 }
 
 fn is_self_assign(assignee: &str, pr_author: &str) -> bool {
@@ -245,6 +658,7 @@ async fn set_assignee(
     issue: &Issue,
     github: &GithubClient,
     reviewer: &ReviewerSelection,
+    source: AssignmentSource,
 ) -> anyhow::Result<()> {
     let mut db = ctx.db.get().await;
     let mut state: IssueData<'_, Reviewers> =
@@ -259,7 +673,8 @@ async fn set_assignee(
         );
         return Ok(());
     }
-    if let Err(err) = issue.set_assignee(github, &reviewer.name).await {
+    let result = set_assignee_with_retry(issue, github, &reviewer.name).await;
+    if let Err(err) = &result {
         log::warn!(
             "failed to set assignee of PR {} to {}: {:?}",
             issue.global_id(),
@@ -294,6 +709,13 @@ They may take a while to respond.
                 )),
                 FindReviewerError::ReviewerAtMaxCapacity { username } => Some(format!(
                     "`{username}` is currently at their maximum review capacity.
+They may take a while to respond."
+                )),
+                FindReviewerError::ReviewerAway { username, message } => {
+                    Some(messages::reviewer_away_message(username, message))
+                }
+                FindReviewerError::ReviewerHasStaleReviews { username } => Some(format!(
+                    "`{username}` has had other PRs waiting for their review for a while.
 They may take a while to respond."
                 )),
                 _ => None,
@@ -309,26 +731,74 @@ They may take a while to respond."
 
     // Record the reviewer in the database
     state.data.names.insert(reviewer.name.to_lowercase());
+    if result.is_ok() {
+        state.data.assigned_at = Some(chrono::Utc::now());
+    }
     state.save().await?;
+    if result.is_ok() {
+        record_assignment(&db, issue, &reviewer.name, source).await;
+        if let Err(err) = proposal::clear_proposal(&mut db, issue).await {
+            log::warn!(
+                "failed to clear the review proposal of {}: {err:?}",
+                issue.global_id()
+            );
+        }
+        if let Some(rotation) = &reviewer.rotation
+            && let Err(err) = record_last_reviewer(&db, rotation, &reviewer.name).await
+        {
+            log::warn!("failed to record the last reviewer of rotation {rotation}: {err:?}");
+        }
+    }
     Ok(())
 }
 
+/// Assigns `reviewer` to `issue` on GitHub. HTTP errors, which are often
+/// transient, are retried with an exponential backoff, unlike invalid
+/// assignees.
+async fn set_assignee_with_retry(
+    issue: &Issue,
+    github: &GithubClient,
+    reviewer: &str,
+) -> Result<(), github::AssignmentError> {
+    let mut delay = SET_ASSIGNEE_RETRY_DELAY;
+    let mut attempt = 1;
+    loop {
+        match issue.set_assignee(github, reviewer).await {
+            Err(github::AssignmentError::Http(err)) if attempt < SET_ASSIGNEE_ATTEMPTS => {
+                log::info!(
+                    "failed to set assignee of PR {} to {reviewer} (attempt {attempt}), \
+                     retrying: {err:?}",
+                    issue.global_id(),
+                );
+                tokio::time::sleep(delay).await;
+                delay *= 2;
+                attempt += 1;
+            }
+            result => return result,
+        }
+    }
+}
+
 /// Determines who to assign the PR to based on either an `r?` command, or
 /// based on which files were modified.
 ///
 /// Will also check if candidates have capacity in their work queue.
 ///
-/// Returns `(assignee, from_comment)` where `assignee` is who to assign to
-/// (or None if no assignee could be found). `from_comment` is a boolean
-/// indicating if the assignee came from an `r?` command (it is false if
-/// determined from the diff).
+/// Returns `(assignee, source)` where `assignee` is who to assign to
+/// (or None if no assignee could be found), and `source` where the assignee
+/// comes from. `source` is [`AssigneeSource::Owners`] if no assignee was found.
+///
+/// With `dry_run`, nothing is posted on the PR nor written to the database,
+/// the assignee is only previewed.
 async fn determine_assignee(
     ctx: &Context,
     assign_command: Option<String>,
-    event: &IssuesEvent,
+    issue: &Issue,
     config: &AssignConfig,
+    owners_files: &OwnersFiles,
     diff: &[FileDiff],
-) -> anyhow::Result<(Option<ReviewerSelection>, bool)> {
+    dry_run: bool,
+) -> anyhow::Result<(Option<ReviewerSelection>, AssigneeSource)> {
     let mut db_client = ctx.db.get().await;
     let teams = &ctx.team.teams().await?;
     if let Some(name) = assign_command {
@@ -338,53 +808,104 @@ async fn determine_assignee(
             ctx.workqueue.clone(),
             &teams,
             config,
-            &event.issue,
-            &event.issue.user.login,
+            issue,
+            &issue.user.login,
+            &ctx.username,
             &[name],
         )
         .await
         {
-            Ok(assignee) => return Ok((Some(assignee), true)),
+            Ok(assignee) => return Ok((Some(assignee), AssigneeSource::Comment)),
             Err(e) => {
-                event
-                    .issue
-                    .post_comment(&ctx.github, &e.to_string())
-                    .await?;
+                if !dry_run {
+                    issue.post_comment(&ctx.github, &e.to_string()).await?;
+                }
                 // Fall through below for normal diff detection.
             }
         }
     }
+    let from_labels = reviewers_from_labels(config, issue);
+    if !from_labels.is_empty() {
+        match find_reviewer_from_names(
+            &mut db_client,
+            ctx.workqueue.clone(),
+            &teams,
+            config,
+            issue,
+            &issue.user.login,
+            &ctx.username,
+            &from_labels,
+        )
+        .await
+        {
+            Ok(assignee) => return Ok((Some(assignee), AssigneeSource::Owners)),
+            // Fall through below for normal diff detection.
+            Err(e) => log::trace!(
+                "no reviewer could be determined from the labels of PR {}: {e}",
+                issue.global_id()
+            ),
+        }
+    }
+    let from_diff = if config.cross_team_review {
+        // Prefer reviewers from outside of the teams that the PR author is a member of.
+        let author_teams = teams_of_user(teams, &issue.user.login);
+        find_reviewers_from_diff_excluding(config, owners_files, diff, |owner| {
+            get_team_name(teams, issue, owner).is_some_and(|t| author_teams.contains(t))
+        })
+    } else {
+        find_reviewers_from_diff(config, owners_files, diff)
+    };
+    // Reviewers of the diff that were skipped because all of them are off the rotation.
+    let mut off_rotation_reviewers = Vec::new();
     // Errors fall-through to try fallback group.
-    match find_reviewers_from_diff(config, diff) {
+    match from_diff {
+        Ok(candidates) if candidates == [GHOST_ACCOUNT] => {
+            // The PR mostly modifies paths that should not be auto-assigned.
+            return Ok((
+                Some(ReviewerSelection::from_name(GHOST_ACCOUNT.to_string())),
+                AssigneeSource::Owners,
+            ));
+        }
         Ok(candidates) if !candidates.is_empty() => {
             match find_reviewer_from_names(
                 &mut db_client,
                 ctx.workqueue.clone(),
                 &teams,
                 config,
-                &event.issue,
-                &event.issue.user.login,
+                issue,
+                &issue.user.login,
+                &ctx.username,
                 &candidates,
             )
             .await
             {
-                Ok(assignee) => return Ok((Some(assignee), false)),
+                Ok(assignee) => return Ok((Some(assignee), AssigneeSource::Owners)),
+                Err(FindReviewerError::NoReviewerOnRotation { off_rotation, .. }) => {
+                    off_rotation_reviewers = off_rotation;
+                }
                 Err(FindReviewerError::TeamNotFound(team)) => log::warn!(
                     "team {team} not found via diff from PR {}, \
                     is there maybe a misconfigured group?",
-                    event.issue.global_id()
+                    issue.global_id()
                 ),
                 Err(
                     e @ FindReviewerError::NoReviewer { .. }
+                    | e @ FindReviewerError::NoReviewerHasCapacity { .. }
+                    | e @ FindReviewerError::EmptyTeam { .. }
                     | e @ FindReviewerError::ReviewerIsPrAuthor { .. }
+                    | e @ FindReviewerError::ReviewerIsBot { .. }
                     | e @ FindReviewerError::ReviewerAlreadyAssigned { .. }
                     | e @ FindReviewerError::ReviewerPreviouslyAssigned { .. }
+                    | e @ FindReviewerError::ReviewerDeclined { .. }
                     | e @ FindReviewerError::ReviewerOffRotation { .. }
+                    | e @ FindReviewerError::ReviewerAway { .. }
+                    | e @ FindReviewerError::ReviewerUnavailable { .. }
+                    | e @ FindReviewerError::ReviewerHasStaleReviews { .. }
                     | e @ FindReviewerError::DatabaseError(_)
                     | e @ FindReviewerError::ReviewerAtMaxCapacity { .. },
                 ) => log::trace!(
                     "no reviewer could be determined for PR {}: {e}",
-                    event.issue.global_id()
+                    issue.global_id()
                 ),
             }
         }
@@ -404,95 +925,95 @@ async fn determine_assignee(
             ctx.workqueue.clone(),
             &teams,
             config,
-            &event.issue,
-            &event.issue.user.login,
+            issue,
+            &issue.user.login,
+            &ctx.username,
             fallback,
         )
         .await
         {
-            Ok(assignee) => return Ok((Some(assignee), false)),
+            Ok(assignee) => return Ok((Some(assignee), AssigneeSource::Fallback)),
             Err(e) => {
                 log::trace!(
                     "failed to select from fallback group for PR {}: {e}",
-                    event.issue.global_id()
+                    issue.global_id()
                 );
             }
         }
     }
-    Ok((None, false))
+    if !off_rotation_reviewers.is_empty() && !dry_run {
+        // Only tell the author once, not on every push.
+        let mut state: IssueData<'_, bool> =
+            IssueData::load(&mut db_client, issue, OFF_ROTATION_WARNING_KEY).await?;
+        if !state.data {
+            issue
+                .post_comment(
+                    &ctx.github,
+                    &messages::reviewers_off_rotation_message(&off_rotation_reviewers),
+                )
+                .await?;
+            state.data = true;
+            state.save().await?;
+        }
+    }
+    Ok((None, AssigneeSource::Owners))
+}
+
+/// Returns the `label_owners` reviewers of all the labels of `issue`, without
+/// duplicates.
+fn reviewers_from_labels(config: &AssignConfig, issue: &Issue) -> Vec<String> {
+    let mut reviewers = Vec::new();
+    for label in issue.labels() {
+        for reviewer in config.label_owners.get(&label.name).into_iter().flatten() {
+            if !reviewers.contains(reviewer) {
+                reviewers.push(reviewer.clone());
+            }
+        }
+    }
+    reviewers
 }
 
 /// Returns a list of candidate reviewers to use based on which files were changed.
 ///
 /// May return an error if the owners map is misconfigured.
 ///
-/// Beware this may return an empty list if nothing matches.
+/// Beware this may return an empty list if nothing matches. If the most modified
+/// area is owned by `ghost` (or by nobody), this returns just `ghost`.
 fn find_reviewers_from_diff(
     config: &AssignConfig,
+    owners_files: &OwnersFiles,
     diff: &[FileDiff],
 ) -> anyhow::Result<Vec<String>> {
-    // Map of `owners` path to the number of changes found in that path.
-    // This weights the reviewer choice towards places where the most edits are done.
-    let mut counts: HashMap<&str, u32> = HashMap::new();
-    // Iterate over the diff, counting the number of modified lines in each
-    // file, and tracks those in the `counts` map.
-    for file_diff in diff {
-        // List of the longest `owners` patterns that match the current path. This
-        // prefers choosing reviewers from deeply nested paths over those defined
-        // for top-level paths, under the assumption that they are more
-        // specialized.
-        //
-        // This is a list to handle the situation if multiple paths of the same
-        // length match.
-        let mut longest_owner_patterns = Vec::new();
-
-        // Find the longest `owners` entries that match this path.
-        let mut longest = HashMap::new();
-        for owner_pattern in config.owners.keys() {
-            let ignore = ignore::gitignore::GitignoreBuilder::new("/")
-                .add_line(None, owner_pattern)
-                .with_context(|| format!("owner file pattern `{owner_pattern}` is not valid"))?
-                .build()?;
-            if ignore
-                .matched_path_or_any_parents(&file_diff.filename, false)
-                .is_ignore()
-            {
-                let owner_len = owner_pattern.split('/').count();
-                longest.insert(owner_pattern, owner_len);
-            }
-        }
-        let max_count = longest.values().copied().max().unwrap_or(0);
-        longest_owner_patterns.extend(
-            longest
-                .iter()
-                .filter(|(_, count)| **count == max_count)
-                .map(|x| *x.0),
-        );
-        // Give some weight to these patterns to start. This helps with
-        // files modified without any lines changed.
-        for owner_pattern in &longest_owner_patterns {
-            *counts.entry(owner_pattern).or_default() += 1;
-        }
+    find_reviewers_from_diff_excluding(config, owners_files, diff, |_| false)
+}
 
-        // Count the modified lines.
-        for line in file_diff.patch.lines() {
-            if (!line.starts_with("+++") && line.starts_with('+'))
-                || (!line.starts_with("---") && line.starts_with('-'))
-            {
-                for owner_path in &longest_owner_patterns {
-                    *counts.entry(owner_path).or_default() += 1;
-                }
-            }
-        }
-    }
+/// Like [`find_reviewers_from_diff`], but ignores the owners for which `is_excluded`
+/// returns `true`. Matched patterns without any remaining owner are skipped, so
+/// the reviewers are picked from the other matched areas instead.
+fn find_reviewers_from_diff_excluding(
+    config: &AssignConfig,
+    owners_files: &OwnersFiles,
+    diff: &[FileDiff],
+    is_excluded: impl Fn(&str) -> bool,
+) -> anyhow::Result<Vec<String>> {
+    let mut counts = owner_counts(config, owners_files, diff)?;
+    let is_unassigned =
+        |owners: &[String]| owners.is_empty() || owners.iter().any(|owner| owner == GHOST_ACCOUNT);
+    counts.retain(|(_, owners), _| {
+        is_unassigned(owners) || owners.iter().any(|owner| !is_excluded(owner))
+    });
     // Use the `owners` entry with the most number of modifications.
     let max_count = counts.values().copied().max().unwrap_or(0);
     let max_paths = counts
         .iter()
         .filter(|(_, count)| **count == max_count)
-        .map(|(path, _)| path);
+        .map(|((_, owners), _)| *owners);
+    if max_paths.clone().any(is_unassigned) {
+        return Ok(vec![GHOST_ACCOUNT.to_string()]);
+    }
     let mut potential: Vec<_> = max_paths
-        .flat_map(|owner_path| &config.owners[*owner_path])
+        .flatten()
+        .filter(|owner| !is_excluded(owner))
         .map(|owner| owner.to_string())
         .collect();
     // Dedupe. This isn't strictly necessary, as `find_reviewer_from_names` will deduplicate.
@@ -502,19 +1023,482 @@ fn find_reviewers_from_diff(
     Ok(potential)
 }
 
-/// Handles a command posted in a comment.
-pub(super) async fn handle_command(
+/// Returns the `owners` areas matched by the files in `diff`, together with
+/// the number of changes in each of them.
+fn owner_counts<'a>(
+    config: &'a AssignConfig,
+    owners_files: &'a OwnersFiles,
+    diff: &[FileDiff],
+) -> anyhow::Result<HashMap<(&'a str, &'a [String]), u32>> {
+    // Map of `owners` path (and its owners) to the number of changes found in that path.
+    // This weights the reviewer choice towards places where the most edits are done.
+    let mut counts: HashMap<(&str, &[String]), u32> = HashMap::new();
+    // For huge diffs, only count the modified files. The scan stops as soon
+    // as the limit is exceeded.
+    let count_lines = config.max_diff_lines.is_none_or(|max| {
+        diff.iter()
+            .flat_map(|file_diff| file_diff.patch.lines())
+            .nth(max as usize)
+            .is_none()
+    });
+    if !count_lines {
+        log::debug!(
+            target: OWNERS_LOG_TARGET,
+            "diff exceeds {:?} lines, only counting modified files",
+            config.max_diff_lines
+        );
+    }
+    // Iterate over the diff, counting the number of modified lines in each
+    // file, and tracks those in the `counts` map.
+    for file_diff in diff {
+        let matching_owners = matching_owners(config, owners_files, &file_diff.filename)?;
+        // Give some weight to these patterns to start. This helps with
+        // files modified without any lines changed.
+        for owner_pattern in &matching_owners {
+            *counts.entry(*owner_pattern).or_default() += 1;
+        }
+
+        // Count the modified lines.
+        let mut modified_lines = 0;
+        if count_lines && file_diff.patch.is_empty() {
+            // GitHub omits the patch of binary and large files, which still
+            // count as a whole file touch, plus the changed lines if known.
+            // This way, deleting large files routes the PR to their owners.
+            modified_lines = file_diff.changes;
+            for owner_path in &matching_owners {
+                *counts.entry(*owner_path).or_default() += file_diff.changes;
+            }
+        } else if count_lines {
+            for line in file_diff.patch.lines() {
+                if (!line.starts_with("+++") && line.starts_with('+'))
+                    || (!line.starts_with("---") && line.starts_with('-'))
+                {
+                    modified_lines += 1;
+                    for owner_path in &matching_owners {
+                        *counts.entry(*owner_path).or_default() += 1;
+                    }
+                }
+            }
+        }
+        log::debug!(
+            target: OWNERS_LOG_TARGET,
+            "file `{}` ({modified_lines} modified lines) matched owner patterns {:?}",
+            file_diff.filename,
+            matching_owners
+                .iter()
+                .map(|(pattern, _)| pattern)
+                .collect::<Vec<_>>()
+        );
+    }
+    log::debug!(target: OWNERS_LOG_TARGET, "owner pattern counts: {counts:?}");
+    Ok(counts)
+}
+
+/// Returns all the `owners` areas modified by `diff`, most modified first, if
+/// none of them accounts for at least `threshold_percent` of the changes.
+fn multi_area_owners<'a>(
+    config: &'a AssignConfig,
+    owners_files: &'a OwnersFiles,
+    diff: &[FileDiff],
+    threshold_percent: u32,
+) -> anyhow::Result<Option<Vec<(&'a str, &'a [String])>>> {
+    let counts = owner_counts(config, owners_files, diff)?;
+    let total: u32 = counts.values().sum();
+    let max_count = counts.values().copied().max().unwrap_or(0);
+    if total == 0 || max_count * 100 >= threshold_percent * total {
+        return Ok(None);
+    }
+    let mut areas: Vec<_> = counts.into_iter().collect();
+    areas.sort_by_key(|&((pattern, _), count)| (std::cmp::Reverse(count), pattern));
+    Ok(Some(areas.into_iter().map(|(area, _)| area).collect()))
+}
+
+/// Adds the team labels of the modified `areas` to a PR that spans several
+/// areas, up to `max_team_labels`, and lists them in a comment, leaving the
+/// choice of a reviewer to triage.
+async fn label_multi_area_pr(
     ctx: &Context,
     config: &AssignConfig,
-    event: &Event,
-    cmd: AssignCommand,
+    issue: &Issue,
+    areas: &[(&str, &[String])],
 ) -> anyhow::Result<()> {
-    let is_team_member = if let Err(_) | Ok(false) = event.user().is_team_member(&ctx.team).await {
-        false
-    } else {
-        true
+    let teams = ctx.team.teams().await?;
+    // `areas` are sorted by the number of changes, so are the labels.
+    let mut labels: Vec<String> = vec![];
+    for team_name in areas
+        .iter()
+        .flat_map(|(_, owners)| owners.iter())
+        .filter_map(|owner| get_team_name(&teams, issue, owner))
+    {
+        let label = format!("T-{team_name}");
+        if !labels.contains(&label) {
+            labels.push(label);
+        }
+    }
+    let unlabeled = match config.max_team_labels {
+        Some(max) if labels.len() > max => labels.split_off(max),
+        _ => vec![],
     };
-
+    log::info!(
+        "PR {} modifies several areas, adding labels {labels:?} instead of assigning it",
+        issue.global_id()
+    );
+    if !labels.is_empty() {
+        let labels = labels
+            .into_iter()
+            .map(|name| github::Label { name })
+            .collect();
+        if let Err(err) = issue.add_labels(&ctx.github, labels).await {
+            if let Some(github::UnknownLabels { .. }) = err.downcast_ref() {
+                log::warn!("Error adding labels: {}", err);
+            } else {
+                return Err(err);
+            }
+        }
+    }
+    let mut message = messages::multi_area_message(areas);
+    if !unlabeled.is_empty() {
+        message.push_str("\n\n");
+        message.push_str(&messages::unlabeled_teams_message(&unlabeled));
+    }
+    issue.post_comment(&ctx.github, &message).await?;
+    Ok(())
+}
+
+/// Fetches the owners files committed in the repository that apply to `diff`,
+/// if `owners_file` is configured.
+///
+/// Errors are only logged, in which case the `owners` table is used alone.
+async fn load_owners_files(
+    ctx: &Context,
+    config: &AssignConfig,
+    issue: &Issue,
+    diff: &[FileDiff],
+) -> OwnersFiles {
+    let mut owners_files = match &config.owners_file {
+        Some(file_name) => {
+            match OwnersFiles::load(&ctx.github, &ctx.owners_files, issue, file_name, diff).await {
+                Ok(owners_files) => owners_files,
+                Err(err) => {
+                    log::warn!(
+                        "failed to load the `{file_name}` files of PR {}: {err:?}",
+                        issue.global_id()
+                    );
+                    OwnersFiles::new(file_name)
+                }
+            }
+        }
+        None => OwnersFiles::default(),
+    };
+    if config.use_codeowners
+        && let Err(err) = owners_files.load_codeowners(&ctx.github, issue).await
+    {
+        log::warn!(
+            "failed to load the CODEOWNERS file of PR {}: {err:?}",
+            issue.global_id()
+        );
+    }
+    owners_files
+}
+
+/// Returns the `owners` patterns that match `filename`, together with their
+/// reviewers. The nearest owners file committed in the repository takes
+/// precedence. The `CODEOWNERS` patterns are considered like `owners`
+/// patterns.
+///
+/// The `extension_owners` entry of the file extension is matched
+/// independently of the path patterns, so that a file counts for both its
+/// directory and its kind.
+fn matching_owners<'a>(
+    config: &'a AssignConfig,
+    owners_files: &'a OwnersFiles,
+    filename: &str,
+) -> anyhow::Result<Vec<(&'a str, &'a [String])>> {
+    if let Some(owners_file) = owners_files.nearest(filename) {
+        return Ok(vec![owners_file]);
+    }
+    let mut patterns = longest_owner_patterns(config, owners_files, filename)?;
+    if let Some(extension) = std::path::Path::new(filename)
+        .extension()
+        .and_then(|extension| extension.to_str())
+    {
+        patterns.extend(
+            config
+                .extension_owners
+                .iter()
+                .filter(|(key, _)| key.trim_start_matches('.') == extension)
+                .map(|(key, owners)| (key.as_str(), owners.as_slice())),
+        );
+    }
+    Ok(patterns)
+}
+
+/// Returns the longest `owners` patterns that match `filename`. This
+/// prefers choosing reviewers from deeply nested paths over those defined
+/// for top-level paths, under the assumption that they are more
+/// specialized.
+///
+/// This is a list to handle the situation if multiple paths of the same
+/// length match.
+///
+/// The `CODEOWNERS` patterns are included, unless `owners` has the same
+/// pattern.
+fn longest_owner_patterns<'a>(
+    config: &'a AssignConfig,
+    owners_files: &'a OwnersFiles,
+    filename: &str,
+) -> anyhow::Result<Vec<(&'a str, &'a [String])>> {
+    let codeowners = owners_files
+        .codeowners()
+        .iter()
+        .filter(|(pattern, _)| !config.owners.contains_key(*pattern));
+    // Find the longest `owners` entries that match this path.
+    let mut longest = HashMap::new();
+    for (owner_pattern, owners) in config.owners.iter().chain(codeowners) {
+        let ignore = ignore::gitignore::GitignoreBuilder::new("/")
+            .add_line(None, owner_pattern)
+            .with_context(|| format!("owner file pattern `{owner_pattern}` is not valid"))?
+            .build()?;
+        if ignore
+            .matched_path_or_any_parents(filename, false)
+            .is_ignore()
+        {
+            let owner_len = owner_pattern.split('/').count();
+            longest.insert((owner_pattern.as_str(), owners.as_slice()), owner_len);
+        }
+    }
+    let max_count = longest.values().copied().max().unwrap_or(0);
+    Ok(longest
+        .into_iter()
+        .filter(|(_, count)| *count == max_count)
+        .map(|(pattern, _)| pattern)
+        .collect())
+}
+
+/// Returns the `owners` patterns matched by the files in `diff`, together with
+/// their configured reviewers, sorted by pattern.
+fn owner_patterns_from_diff<'a>(
+    config: &'a AssignConfig,
+    owners_files: &'a OwnersFiles,
+    diff: &[FileDiff],
+) -> anyhow::Result<Vec<(&'a str, &'a [String])>> {
+    let mut patterns = HashSet::new();
+    for file_diff in diff {
+        patterns.extend(matching_owners(config, owners_files, &file_diff.filename)?);
+    }
+    let mut owners: Vec<_> = patterns.into_iter().collect();
+    owners.sort();
+    Ok(owners)
+}
+
+/// Replies with the `owners` patterns that match the files modified by a PR.
+async fn list_owners(
+    ctx: &Context,
+    config: &AssignConfig,
+    event: &Event,
+    issue: &Issue,
+) -> anyhow::Result<()> {
+    if !issue.is_pr() {
+        issue
+            .post_comment(
+                &ctx.github,
+                "The `owners` command is only available on pull requests.",
+            )
+            .await?;
+        return Ok(());
+    }
+
+    // Comment events don't include what is needed to compute the diff.
+    let pr = event.repo().get_pr(&ctx.github, issue.number).await?;
+    let Some(diff) = pr.diff(&ctx.github).await? else {
+        issue
+            .post_comment(
+                &ctx.github,
+                "The diff of this pull request is not available yet, please try again later.",
+            )
+            .await?;
+        return Ok(());
+    };
+
+    let owners_files = load_owners_files(ctx, config, &pr, diff).await;
+    let owners = owner_patterns_from_diff(config, &owners_files, diff)?;
+    issue
+        .post_comment(&ctx.github, &messages::owners_message(&owners))
+        .await?;
+    Ok(())
+}
+
+/// Posts the `owners` patterns matching `path` and the reviewers they expand
+/// to, without looking at the diff of the PR.
+async fn preview_path_owners(
+    ctx: &Context,
+    config: &AssignConfig,
+    issue: &Issue,
+    path: &str,
+) -> anyhow::Result<()> {
+    let file = FileDiff {
+        filename: path.to_string(),
+        patch: String::new(),
+        changes: 0,
+    };
+    let owners_files = load_owners_files(ctx, config, issue, std::slice::from_ref(&file)).await;
+    let owners = matching_owners(config, &owners_files, path)?;
+    let names: Vec<String> = owners
+        .iter()
+        .flat_map(|(_, owners)| owners.iter().cloned())
+        .collect();
+    let pool = if names.is_empty() {
+        Ok(vec![])
+    } else {
+        let teams = ctx.team.teams().await?;
+        expand_teams_and_groups(&teams, issue, config, &names).map(|candidates| {
+            let mut pool: Vec<String> = candidates
+                .into_iter()
+                .map(|candidate| candidate.name)
+                .collect();
+            pool.sort();
+            pool
+        })
+    };
+    issue
+        .post_comment(
+            &ctx.github,
+            &messages::path_owners_message(path, &owners, pool.map_err(|err| err.to_string())),
+        )
+        .await?;
+    Ok(())
+}
+
+/// Unassigns the current reviewer of a PR who cannot review it, and picks
+/// another reviewer from the diff.
+///
+/// The declining reviewer is recorded as a previous reviewer of the PR, so
+/// that they are not picked again.
+async fn decline_assignment(
+    ctx: &Context,
+    config: &AssignConfig,
+    event: &Event,
+    issue: &Issue,
+    reviewer: &User,
+) -> anyhow::Result<()> {
+    if !issue.is_pr() || !issue.is_open() {
+        bail!("`not-me` is only allowed on open pull requests.");
+    }
+    if !issue.contain_assignee(&reviewer.login) {
+        bail!("Only the assignee of this pull request can use `not-me`.");
+    }
+    issue
+        .remove_assignees(&ctx.github, Selection::One(&reviewer.login))
+        .await?;
+    {
+        let mut db = ctx.db.get().await;
+        let mut state: IssueData<'_, Reviewers> =
+            IssueData::load(&mut db, issue, PREVIOUS_REVIEWERS_KEY).await?;
+        state.data.names.insert(reviewer.login.to_lowercase());
+        state.data.declined.insert(reviewer.login.to_lowercase());
+        state.save().await?;
+    }
+
+    // Comment events don't include what is needed to compute the diff.
+    let pr = event.repo().get_pr(&ctx.github, issue.number).await?;
+    if assign_replacement(ctx, config, &pr).await?.is_none() {
+        issue
+            .post_comment(
+                &ctx.github,
+                &messages::no_replacement_reviewer_message(&reviewer.login),
+            )
+            .await?;
+    }
+    Ok(())
+}
+
+/// Posts the reviewer that would be picked from the diff of a PR, without
+/// assigning them.
+async fn suggest_reviewer(
+    ctx: &Context,
+    config: &AssignConfig,
+    event: &Event,
+    issue: &Issue,
+) -> anyhow::Result<()> {
+    if !issue.is_pr() || !issue.is_open() {
+        bail!("`suggest-reviewer` is only allowed on open pull requests.");
+    }
+    // Comment events don't include what is needed to compute the diff.
+    let pr = event.repo().get_pr(&ctx.github, issue.number).await?;
+    let suggestion = match pr.diff(&ctx.github).await? {
+        Some(diff) => {
+            let owners_files = load_owners_files(ctx, config, &pr, diff).await;
+            // The selection itself is read-only, the strict alternation only
+            // moves on once the reviewer is assigned.
+            determine_assignee(ctx, None, &pr, config, &owners_files, diff, true)
+                .await?
+                .0
+        }
+        None => None,
+    };
+    let suggestion = suggestion
+        .map(|reviewer| reviewer.name)
+        .filter(|name| name != GHOST_ACCOUNT);
+    issue
+        .post_comment(
+            &ctx.github,
+            &messages::suggested_reviewer_message(suggestion.as_deref()),
+        )
+        .await?;
+    Ok(())
+}
+
+/// Picks another reviewer from the diff of a PR whose reviewer declined it,
+/// and assigns them, or proposes the review to them with `require_acceptance`.
+///
+/// Returns the new reviewer, or None if no other reviewer could be found.
+async fn assign_replacement(
+    ctx: &Context,
+    config: &AssignConfig,
+    issue: &Issue,
+) -> anyhow::Result<Option<String>> {
+    let replacement = match issue.diff(&ctx.github).await? {
+        Some(diff) => {
+            let owners_files = load_owners_files(ctx, config, issue, diff).await;
+            determine_assignee(ctx, None, issue, config, &owners_files, diff, false).await?
+        }
+        None => (None, AssigneeSource::Owners),
+    };
+    let (Some(replacement), source) = replacement else {
+        return Ok(None);
+    };
+    if replacement.name == GHOST_ACCOUNT {
+        return Ok(None);
+    }
+    if config.require_acceptance {
+        proposal::propose_reviewer(ctx, config, issue, &replacement).await?;
+    } else {
+        set_assignee(ctx, issue, &ctx.github, &replacement, source.into()).await?;
+    }
+    if source == AssigneeSource::Fallback {
+        issue
+            .post_comment(
+                &ctx.github,
+                &messages::fallback_after_decline_message(&replacement.name),
+            )
+            .await?;
+    }
+    Ok(Some(replacement.name))
+}
+
+/// Handles a command posted in a comment.
+pub(super) async fn handle_command(
+    ctx: &Context,
+    config: &AssignConfig,
+    event: &Event,
+    cmd: AssignCommand,
+) -> anyhow::Result<()> {
+    let is_team_member = if let Err(_) | Ok(false) = event.user().is_team_member(&ctx.team).await {
+        false
+    } else {
+        true
+    };
+
     // Don't handle commands in comments from the bot. Some of the comments it
     // posts contain commands to instruct the user, not things that the bot
     // should respond to.
@@ -523,8 +1507,75 @@ pub(super) async fn handle_command(
     }
 
     let issue = event.issue().unwrap();
+
+    // The away status is not tied to the issue or PR it was set from.
+    match cmd {
+        AssignCommand::Away { message, until } => {
+            return set_away(ctx, event, issue, Some(message), until).await;
+        }
+        AssignCommand::Back => return set_away(ctx, event, issue, None, None).await,
+        AssignCommand::Vacation { start, end } => {
+            return set_vacation_range(ctx, event, issue, Some((start, end))).await;
+        }
+        AssignCommand::VacationEnd => return set_vacation_range(ctx, event, issue, None).await,
+        AssignCommand::Owners => return list_owners(ctx, config, event, issue).await,
+        AssignCommand::ShowWork => return show_work(ctx, issue, event.user()).await,
+        AssignCommand::SetCapacity { user, capacity } => {
+            return set_review_capacity(ctx, event, issue, user, capacity, is_team_member).await;
+        }
+        AssignCommand::SetRotation { on_rotation } => {
+            return set_rotation_mode(ctx, issue, event.user(), on_rotation).await;
+        }
+        AssignCommand::ShowTeamWork { team } => {
+            return show_team_work(ctx, config, issue, &team, is_team_member).await;
+        }
+        AssignCommand::PathOwners { path } => {
+            if !is_team_member {
+                issue
+                    .post_comment(
+                        &ctx.github,
+                        "Only Rust team members can use `assign?` to look up the owners of a path.",
+                    )
+                    .await?;
+                return Ok(());
+            }
+            return preview_path_owners(ctx, config, issue, &path).await;
+        }
+        AssignCommand::NotMe => {
+            return decline_assignment(ctx, config, event, issue, event.user()).await;
+        }
+        AssignCommand::Accept => {
+            return proposal::answer_proposal(ctx, config, event, issue, true).await;
+        }
+        AssignCommand::Pass => {
+            return proposal::answer_proposal(ctx, config, event, issue, false).await;
+        }
+        AssignCommand::SuggestReview => return suggest_reviewer(ctx, config, event, issue).await,
+        AssignCommand::ShowConfig => {
+            if !is_team_member {
+                bail!("Only Rust team members can use `config show assign`.");
+            }
+            let config = toml::to_string(config).context("failed to serialize the config")?;
+            issue
+                .post_comment(&ctx.github, &messages::assign_config_message(&config))
+                .await?;
+            return Ok(());
+        }
+        AssignCommand::Next => {
+            return assign_next_pr(ctx, config, event, issue, is_team_member).await;
+        }
+        _ => {}
+    }
+
     if issue.is_pr() {
         if !issue.is_open() {
+            // Team members can still request a post-merge review of a recently merged PR.
+            if let AssignCommand::RequestReview { name, .. } = &cmd
+                && is_team_member
+                && is_recently_merged(issue)
+            {
+                return request_post_merge_review(ctx, config, event, issue, name).await;
+            }
             issue
                 .post_comment(&ctx.github, "Assignment is not allowed on a closed PR.")
                 .await?;
@@ -545,33 +1596,72 @@ pub(super) async fn handle_command(
 
         let teams = ctx.team.teams().await?;
 
+        let mut shadow = None;
+        let mut others = Vec::new();
+        let mut force_claim = false;
+        let source = match cmd {
+            AssignCommand::Claim { .. } => AssignmentSource::Claim,
+            _ => AssignmentSource::ReviewRequest,
+        };
         let assignee = match cmd {
-            AssignCommand::Claim => event.user().login.clone(),
+            AssignCommand::Claim { force } => {
+                force_claim = force;
+                event.user().login.clone()
+            }
             AssignCommand::AssignUser { username } => username,
+            AssignCommand::Rollup => {
+                return set_no_reviewer(ctx, event, issue, is_team_member).await;
+            }
             AssignCommand::ReleaseAssignment => {
                 log::trace!(
                     "ignoring release on PR {:?}, must always have assignee",
                     issue.global_id()
                 );
-                return Ok(());
+                // The team label of the released review request is not relevant anymore.
+                return replace_bot_team_label(ctx, issue, None).await;
             }
-            AssignCommand::RequestReview { name } => {
-                // Determine if assignee is a team. If yes, add the corresponding GH label.
-                if let Some(team_name) = get_team_name(&teams, &issue, &name) {
-                    let t_label = format!("T-{team_name}");
-                    if let Err(err) = issue
-                        .add_labels(&ctx.github, vec![github::Label { name: t_label }])
-                        .await
-                    {
-                        if let Some(github::UnknownLabels { .. }) = err.downcast_ref() {
-                            log::warn!("Error assigning label: {}", err);
-                        } else {
-                            return Err(err);
-                        }
-                    }
+            AssignCommand::RequestReview {
+                name,
+                others: other_names,
+                shadow: shadow_name,
+            } => {
+                if shadow_name.as_ref() == Some(&issue.user.login) {
+                    issue
+                        .post_comment(&ctx.github, messages::SHADOW_IS_PR_AUTHOR)
+                        .await?;
+                    return Ok(());
                 }
+                shadow = shadow_name;
+                others = other_names;
+                // Determine if assignee is a team. If yes, add the corresponding GH label,
+                // replacing the one added by a previous review request.
+                let team_name = config
+                    .aliases
+                    .get(strip_organization_prefix(issue, &name))
+                    .unwrap_or(&name);
+                let t_label = get_team_name(&teams, &issue, team_name)
+                    .map(|team_name| format!("T-{team_name}"));
+                replace_bot_team_label(ctx, issue, t_label).await?;
                 name
             }
+            AssignCommand::Away { .. }
+            | AssignCommand::Back
+            | AssignCommand::Vacation { .. }
+            | AssignCommand::VacationEnd
+            | AssignCommand::Owners
+            | AssignCommand::PathOwners { .. }
+            | AssignCommand::Next
+            | AssignCommand::NotMe
+            | AssignCommand::Accept
+            | AssignCommand::Pass
+            | AssignCommand::SuggestReview
+            | AssignCommand::ShowWork
+            | AssignCommand::SetCapacity { .. }
+            | AssignCommand::SetRotation { .. }
+            | AssignCommand::ShowTeamWork { .. }
+            | AssignCommand::ShowConfig => {
+                unreachable!("handled above")
+            }
         };
 
         // In the PR body, `r? ghost` means "do not assign anybody".
@@ -582,26 +1672,61 @@ pub(super) async fn handle_command(
             issue.remove_assignees(&ctx.github, Selection::All).await?;
             return Ok(());
         }
-        let mut db_client = ctx.db.get().await;
-        let assignee = match find_reviewer_from_names(
-            &mut db_client,
-            ctx.workqueue.clone(),
-            &teams,
-            config,
-            issue,
-            &event.user().login,
-            &[assignee.to_string()],
-        )
-        .await
-        {
-            Ok(assignee) => assignee,
-            Err(e) => {
-                issue.post_comment(&ctx.github, &e.to_string()).await?;
+        let assignee = if others.is_empty() {
+            let mut db_client = ctx.db.get().await;
+            let assignee = match find_reviewer_from_names(
+                &mut db_client,
+                ctx.workqueue.clone(),
+                &teams,
+                config,
+                issue,
+                &event.user().login,
+                &ctx.username,
+                &[assignee.to_string()],
+            )
+            .await
+            {
+                Ok(assignee) => assignee,
+                Err(e) => {
+                    issue.post_comment(&ctx.github, &e.to_string()).await?;
+                    return Ok(());
+                }
+            };
+            // Unlike other reviewers, those assigning themselves beyond
+            // their capacity are not assigned, unless they insist. The
+            // self-assignment skips the candidate checks, so their capacity
+            // is checked here.
+            if !force_claim
+                && assignee.name.eq_ignore_ascii_case(&event.user().login)
+                && is_at_max_capacity(&db_client, &ctx.workqueue, config, event.user()).await?
+            {
+                issue
+                    .post_comment(&ctx.github, messages::SELF_ASSIGN_HAS_NO_CAPACITY)
+                    .await?;
                 return Ok(());
             }
+            set_assignee(ctx, issue, &ctx.github, &assignee, source).await?;
+            assignee
+        } else {
+            let names = std::iter::once(assignee).chain(others).collect();
+            match request_several_reviewers(ctx, config, event, issue, &teams, names).await? {
+                Some(assignee) => assignee,
+                None => return Ok(()),
+            }
         };
 
-        set_assignee(ctx, issue, &ctx.github, &assignee).await?;
+        // The shadow reviewer is only mentioned, not assigned, so that the
+        // review doesn't take from their review capacity.
+        if let Some(shadow) = shadow
+            && shadow != assignee.name
+        {
+            issue
+                .post_comment(
+                    &ctx.github,
+                    &messages::shadow_reviewer_message(&shadow, &assignee.name),
+                )
+                .await?;
+        }
     } else {
         let mut client = ctx.db.get().await;
         let mut e: EditIssueBody<'_, AssignData> =
@@ -609,7 +1734,7 @@ pub(super) async fn handle_command(
         let d = e.data_mut();
 
         let to_assign = match cmd {
-            AssignCommand::Claim => event.user().login.clone(),
+            AssignCommand::Claim { .. } => event.user().login.clone(),
             AssignCommand::AssignUser { username } => {
                 if !is_team_member && username != event.user().login {
                     bail!("Only Rust team members can assign other users");
@@ -644,9 +1769,28 @@ pub(super) async fn handle_command(
                 };
             }
             AssignCommand::RequestReview { .. } => bail!("r? is only allowed on PRs."),
+            AssignCommand::Rollup => bail!("`assign rollup` is only allowed on PRs."),
+            AssignCommand::Away { .. }
+            | AssignCommand::Back
+            | AssignCommand::Vacation { .. }
+            | AssignCommand::VacationEnd
+            | AssignCommand::Owners
+            | AssignCommand::PathOwners { .. }
+            | AssignCommand::Next
+            | AssignCommand::NotMe
+            | AssignCommand::Accept
+            | AssignCommand::Pass
+            | AssignCommand::SuggestReview
+            | AssignCommand::ShowWork
+            | AssignCommand::SetCapacity { .. }
+            | AssignCommand::SetRotation { .. }
+            | AssignCommand::ShowTeamWork { .. }
+            | AssignCommand::ShowConfig => {
+                unreachable!("handled above")
+            }
         };
         // Don't re-assign if aleady assigned, e.g. on comment edit
-        if issue.contain_assignee(&to_assign) {
+        if is_effective_assignee(issue, d, &ctx.username, &to_assign) {
             log::trace!(
                 "ignoring assign issue {} to {}, already assigned",
                 issue.global_id(),
@@ -654,31 +1798,768 @@ pub(super) async fn handle_command(
             );
             return Ok(());
         }
-        *d = AssignData {
-            user: Some(to_assign.clone()),
-        };
+        *d = AssignData {
+            user: Some(to_assign.clone()),
+        };
+
+        match issue.set_assignee(&ctx.github, &to_assign).await {
+            Ok(()) => {
+                // Show who claimed the issue when a team member did it on
+                // behalf of someone else, e.g. when handing it off.
+                let cmt_body = if to_assign != event.user().login {
+                    format!(
+                        "This issue has been claimed for @{} by @{} via [this comment]({}).",
+                        to_assign,
+                        event.user().login,
+                        event.html_url().unwrap()
+                    )
+                } else {
+                    String::new()
+                };
+                e.apply(&ctx.github, cmt_body).await?;
+                return Ok(());
+            } // we are done
+            Err(github::AssignmentError::InvalidAssignee) => {
+                issue
+                    .set_assignee(&ctx.github, &ctx.username)
+                    .await
+                    .context("self-assignment failed")?;
+                let cmt_body = format!(
+                    "This issue has been assigned to @{} via [this comment]({}).",
+                    to_assign,
+                    event.html_url().unwrap()
+                );
+                e.apply(&ctx.github, cmt_body).await?;
+            }
+            Err(e) => return Err(e.into()),
+        }
+    }
+
+    Ok(())
+}
+
+/// Assigns the oldest unassigned open PR of the repository that modifies an area
+/// owned by the commenter, either directly or through one of their teams or groups.
+async fn assign_next_pr(
+    ctx: &Context,
+    config: &AssignConfig,
+    event: &Event,
+    issue: &Issue,
+    is_team_member: bool,
+) -> anyhow::Result<()> {
+    if !is_team_member {
+        bail!("Only Rust team members can use `assign next`.");
+    }
+    let reviewer = event.user();
+
+    if config.review_prefs.is_some() {
+        let db = ctx.db.get().await;
+        if let Some(review_prefs) = get_review_prefs(&db, reviewer.id).await?
+            && let Some(capacity) = review_prefs.max_assigned_prs
+        {
+            let assigned_prs = ctx.workqueue.read().await.assigned_pr_count(reviewer.id);
+            if (assigned_prs as i32) >= capacity {
+                bail!(
+                    "You already have {assigned_prs} PRs assigned, which is your maximum \
+                     review capacity."
+                );
+            }
+        }
+    }
+
+    let query = github::Query {
+        filters: vec![
+            ("state", "open"),
+            ("is", "pull-request"),
+            ("no", "assignee"),
+            ("draft", "false"),
+        ],
+        include_labels: vec![],
+        exclude_labels: vec![],
+    };
+    let repo = event.repo();
+    let prs = repo.get_issues(&ctx.github, &query).await?;
+    let teams = ctx.team.teams().await?;
+    let candidate_prs = prs
+        .iter()
+        .filter(|pr| {
+            !pr.user.login.eq_ignore_ascii_case(&reviewer.login) && !is_ghost_requested(ctx, pr)
+        })
+        .take(MAX_NEXT_PR_CANDIDATES);
+    // The PRs are inspected concurrently, but the first owned one in the search
+    // results is picked. PRs that cannot be inspected are skipped.
+    let mut owned_prs = futures::stream::iter(candidate_prs)
+        .map(|pr| {
+            let teams = &teams;
+            async move {
+                next_pr_candidate(ctx, config, teams, repo, pr.number, reviewer)
+                    .await
+                    .unwrap_or_else(|err| {
+                        log::warn!(
+                            "failed to inspect {}#{} for `assign next`: {err:?}",
+                            repo.full_name,
+                            pr.number
+                        );
+                        None
+                    })
+            }
+        })
+        .buffered(NEXT_PR_CONCURRENCY);
+    while let Some(pr) = owned_prs.next().await {
+        if let Some(pr) = pr {
+            set_assignee(
+                ctx,
+                &pr,
+                &ctx.github,
+                &ReviewerSelection::from_name(reviewer.login.clone()),
+                AssignmentSource::Claim,
+            )
+            .await?;
+            issue
+                .post_comment(
+                    &ctx.github,
+                    &messages::next_pr_assigned_message(&reviewer.login, &pr.html_url),
+                )
+                .await?;
+            return Ok(());
+        }
+    }
+
+    issue
+        .post_comment(&ctx.github, messages::NO_NEXT_PR)
+        .await?;
+    Ok(())
+}
+
+/// Fetches the PR `number` for `assign next`, and returns it if `reviewer` is
+/// one of the owners of the files it modifies.
+async fn next_pr_candidate(
+    ctx: &Context,
+    config: &AssignConfig,
+    teams: &Teams,
+    repo: &Repository,
+    number: u64,
+    reviewer: &User,
+) -> anyhow::Result<Option<Issue>> {
+    // The search results don't include what is needed to compute the diff.
+    let pr = repo.get_pr(&ctx.github, number).await?;
+    if is_no_reviewer_requested(ctx, &pr).await? {
+        return Ok(None);
+    }
+    let Some(diff) = pr.diff(&ctx.github).await? else {
+        return Ok(None);
+    };
+    let owners_files = load_owners_files(ctx, config, &pr, diff).await;
+    let owners = match find_reviewers_from_diff(config, &owners_files, diff) {
+        Ok(owners) if !owners.is_empty() && owners != [GHOST_ACCOUNT] => owners,
+        Ok(_) => return Ok(None),
+        Err(err) => {
+            log::warn!("failed to find the owners of PR {}: {err}", pr.global_id());
+            return Ok(None);
+        }
+    };
+    let Ok(candidates) = expand_teams_and_groups(teams, &pr, config, &owners) else {
+        return Ok(None);
+    };
+    let is_owner = candidates
+        .iter()
+        .any(|candidate| candidate.name.eq_ignore_ascii_case(&reviewer.login));
+    Ok(is_owner.then_some(pr))
+}
+
+/// Removes the assignees of a PR that doesn't need a reviewer, such as a rollup
+/// or an experiment, and records it so that it isn't auto-assigned later.
+async fn set_no_reviewer(
+    ctx: &Context,
+    event: &Event,
+    issue: &Issue,
+    is_team_member: bool,
+) -> anyhow::Result<()> {
+    if !is_team_member && issue.user.login != event.user().login {
+        bail!("Only Rust team members and the PR author can mark a PR as not needing a reviewer");
+    }
+    issue.remove_assignees(&ctx.github, Selection::All).await?;
+
+    let mut db = ctx.db.get().await;
+    let mut state: IssueData<'_, bool> = IssueData::load(&mut db, issue, NO_REVIEWER_KEY).await?;
+    state.data = true;
+    state.save().await?;
+
+    issue
+        .post_comment(
+            &ctx.github,
+            &messages::no_reviewer_message(&event.user().login),
+        )
+        .await?;
+    Ok(())
+}
+
+/// Returns `true` if the PR was marked as not needing a reviewer with `@rustbot assign rollup`.
+async fn is_no_reviewer_requested(ctx: &Context, issue: &Issue) -> anyhow::Result<bool> {
+    let mut db = ctx.db.get().await;
+    let state: IssueData<'_, bool> = IssueData::load(&mut db, issue, NO_REVIEWER_KEY).await?;
+    Ok(state.data)
+}
+
+/// Records that `issue` went through auto-assignment.
+async fn record_auto_assigned(ctx: &Context, issue: &Issue) -> anyhow::Result<()> {
+    let mut db = ctx.db.get().await;
+    let mut state: IssueData<'_, bool> = IssueData::load(&mut db, issue, AUTO_ASSIGNED_KEY).await?;
+    state.data = true;
+    state.save().await?;
+    Ok(())
+}
+
+/// Returns `true` if `issue` already went through auto-assignment, e.g. when
+/// it was first marked as ready for review.
+async fn was_auto_assigned(ctx: &Context, issue: &Issue) -> anyhow::Result<bool> {
+    let mut db = ctx.db.get().await;
+    let state: IssueData<'_, bool> = IssueData::load(&mut db, issue, AUTO_ASSIGNED_KEY).await?;
+    Ok(state.data)
+}
+
+/// Records that the author of `issue` was welcomed.
+async fn record_welcomed(ctx: &Context, issue: &Issue) -> anyhow::Result<()> {
+    let mut db = ctx.db.get().await;
+    let mut state: IssueData<'_, bool> = IssueData::load(&mut db, issue, WELCOMED_KEY).await?;
+    state.data = true;
+    state.save().await?;
+    Ok(())
+}
+
+/// Returns `true` if the author of `issue` was already welcomed.
+async fn was_welcomed(ctx: &Context, issue: &Issue) -> anyhow::Result<bool> {
+    let mut db = ctx.db.get().await;
+    let state: IssueData<'_, bool> = IssueData::load(&mut db, issue, WELCOMED_KEY).await?;
+    Ok(state.data)
+}
+
+/// Replaces the team label that triagebot added for a previous `r?` with `new_label`.
+///
+/// Team labels that were not added by triagebot are never removed.
+async fn replace_bot_team_label(
+    ctx: &Context,
+    issue: &Issue,
+    new_label: Option<String>,
+) -> anyhow::Result<()> {
+    // Loading the issue data locks it, so the database is only held around
+    // reading and updating the recorded labels, not during the GitHub calls.
+    let recorded_labels = {
+        let mut db = ctx.db.get().await;
+        let state: IssueData<'_, BotTeamLabels> =
+            IssueData::load(&mut db, issue, BOT_TEAM_LABELS_KEY).await?;
+        let names = state.data.names.clone();
+        state.save().await?;
+        names
+    };
+
+    let mut removed_labels = vec![];
+    for label in recorded_labels
+        .into_iter()
+        .filter(|label| Some(label.as_str()) != new_label.as_deref())
+    {
+        issue.remove_label(&ctx.github, &label).await?;
+        removed_labels.push(label);
+    }
+
+    let mut added_label = None;
+    if let Some(label) = new_label {
+        let already_present = issue.labels().iter().any(|l| l.name == label);
+        match issue
+            .add_labels(
+                &ctx.github,
+                vec![github::Label {
+                    name: label.clone(),
+                }],
+            )
+            .await
+        {
+            Ok(()) => {
+                if !already_present {
+                    added_label = Some(label);
+                }
+            }
+            Err(err) => {
+                if let Some(github::UnknownLabels { .. }) = err.downcast_ref() {
+                    log::warn!("Error assigning label: {}", err);
+                } else {
+                    return Err(err);
+                }
+            }
+        }
+    }
+
+    if removed_labels.is_empty() && added_label.is_none() {
+        return Ok(());
+    }
+    let mut db = ctx.db.get().await;
+    let mut state: IssueData<'_, BotTeamLabels> =
+        IssueData::load(&mut db, issue, BOT_TEAM_LABELS_KEY).await?;
+    for label in &removed_labels {
+        state.data.names.remove(label);
+    }
+    state.data.names.extend(added_label);
+    state.save().await?;
+    Ok(())
+}
+
+/// Returns `true` if the PR was merged within the last [`POST_MERGE_REVIEW_DAYS`].
+fn is_recently_merged(issue: &Issue) -> bool {
+    issue
+        .pull_request
+        .as_ref()
+        .and_then(|pr| pr.merged_at)
+        .is_some_and(|merged_at| {
+            chrono::Utc::now() - merged_at <= chrono::Duration::days(POST_MERGE_REVIEW_DAYS)
+        })
+}
+
+/// Assigns all the reviewers of `r? @a @b`, in addition to each other.
+///
+/// A name that cannot be assigned, e.g. an unknown team or the PR author,
+/// does not prevent assigning the other ones. A single comment lists the
+/// assigned reviewers and the failures. Returns the first assigned reviewer.
+async fn request_several_reviewers(
+    ctx: &Context,
+    config: &AssignConfig,
+    event: &Event,
+    issue: &Issue,
+    teams: &Teams,
+    names: Vec<String>,
+) -> anyhow::Result<Option<ReviewerSelection>> {
+    let mut reviewers: Vec<ReviewerSelection> = Vec::new();
+    let mut failures = Vec::new();
+    for name in names {
+        if is_self_assign(&name, &issue.user.login) {
+            failures.push((name, messages::REVIEWER_IS_PR_AUTHOR.to_string()));
+            continue;
+        }
+        let mut db_client = ctx.db.get().await;
+        let reviewer = match find_reviewer_from_names(
+            &mut db_client,
+            ctx.workqueue.clone(),
+            teams,
+            config,
+            issue,
+            &event.user().login,
+            &ctx.username,
+            &[name.clone()],
+        )
+        .await
+        {
+            Ok(reviewer) => reviewer,
+            Err(e) => {
+                failures.push((name, e.to_string()));
+                continue;
+            }
+        };
+        if reviewers
+            .iter()
+            .any(|r| r.name.eq_ignore_ascii_case(&reviewer.name))
+        {
+            continue;
+        }
+        // The first reviewer replaces the current assignees, the other ones
+        // are added next to it.
+        let result = if reviewers.is_empty() {
+            set_assignee(
+                ctx,
+                issue,
+                &ctx.github,
+                &reviewer,
+                AssignmentSource::ReviewRequest,
+            )
+            .await
+        } else {
+            add_reviewer(ctx, issue, &reviewer).await
+        };
+        match result {
+            Ok(()) => reviewers.push(reviewer),
+            Err(e) => failures.push((name, e.to_string())),
+        }
+    }
+
+    let names: Vec<_> = reviewers.iter().map(|r| r.name.as_str()).collect();
+    issue
+        .post_comment(
+            &ctx.github,
+            &messages::several_reviewers_message(&names, &failures),
+        )
+        .await?;
+    Ok(reviewers.into_iter().next())
+}
+
+/// Adds `reviewer` to the assignees of a PR, keeping the current ones.
+async fn add_reviewer(
+    ctx: &Context,
+    issue: &Issue,
+    reviewer: &ReviewerSelection,
+) -> anyhow::Result<()> {
+    if !issue.contain_assignee(&reviewer.name) {
+        issue.add_assignee(&ctx.github, &reviewer.name).await?;
+    }
+    let mut db = ctx.db.get().await;
+    let mut state: IssueData<'_, Reviewers> =
+        IssueData::load(&mut db, issue, PREVIOUS_REVIEWERS_KEY).await?;
+    state.data.names.insert(reviewer.name.to_lowercase());
+    state.save().await?;
+    record_assignment(&db, issue, &reviewer.name, AssignmentSource::ReviewRequest).await;
+    Ok(())
+}
+
+/// Records when `reviewer` was last assigned, for the `round-robin` selection,
+/// and logs the assignment for external dashboards. This is best effort, so
+/// that a failure never prevents an assignment.
+async fn record_assignment(db: &DbClient, issue: &Issue, reviewer: &str, source: AssignmentSource) {
+    if let Err(err) = record_last_assigned(db, reviewer, chrono::Utc::now()).await {
+        log::warn!("failed to record the last assignment of {reviewer}: {err:?}");
+    }
+    let event = AssignmentEvent {
+        repo: issue.repository().full_repo_name(),
+        pr: issue.number,
+        assignee: reviewer.to_string(),
+        source,
+        timestamp: chrono::Utc::now(),
+    };
+    if let Err(err) = record_assignment_event(db, &event).await {
+        log::warn!(
+            "failed to record the assignment of {} to {reviewer}: {err:?}",
+            issue.global_id()
+        );
+    }
+}
+
+/// Records a post-merge reviewer in the body of a merged PR.
+///
+/// The reviewer is not assigned on GitHub, the PR is already merged.
+async fn request_post_merge_review(
+    ctx: &Context,
+    config: &AssignConfig,
+    event: &Event,
+    issue: &Issue,
+    name: &str,
+) -> anyhow::Result<()> {
+    let teams = ctx.team.teams().await?;
+    let mut db_client = ctx.db.get().await;
+    let reviewer = match find_reviewer_from_names(
+        &mut db_client,
+        ctx.workqueue.clone(),
+        &teams,
+        config,
+        issue,
+        &event.user().login,
+        &ctx.username,
+        &[name.to_string()],
+    )
+    .await
+    {
+        Ok(reviewer) => reviewer.name,
+        Err(e) => {
+            issue.post_comment(&ctx.github, &e.to_string()).await?;
+            return Ok(());
+        }
+    };
+
+    let mut e: EditIssueBody<'_, AssignData> =
+        EditIssueBody::load(&mut db_client, issue, POST_MERGE_REVIEW_KEY).await?;
+    *e.data_mut() = AssignData {
+        user: Some(reviewer.clone()),
+    };
+    let text = format!(
+        "Post-merge review requested from @{} via [this comment]({}).",
+        reviewer,
+        event.html_url().unwrap()
+    );
+    e.apply(&ctx.github, text).await?;
+    Ok(())
+}
+
+/// Sets or clears the away status of the command author.
+async fn set_away(
+    ctx: &Context,
+    event: &Event,
+    issue: &Issue,
+    message: Option<String>,
+    until: Option<String>,
+) -> anyhow::Result<()> {
+    let until = match until {
+        Some(until) => match chrono::NaiveDate::parse_from_str(&until, "%Y-%m-%d") {
+            Ok(date) => Some(date.and_time(chrono::NaiveTime::MIN).and_utc()),
+            Err(_) => {
+                issue
+                    .post_comment(
+                        &ctx.github,
+                        &format!("Invalid date `{until}`, expected the `YYYY-MM-DD` format."),
+                    )
+                    .await?;
+                return Ok(());
+            }
+        },
+        None => None,
+    };
+
+    let db = ctx.db.get().await;
+    set_away_status(&db, event.user().clone(), message.as_deref(), until).await?;
+
+    let reply = match (&message, until) {
+        (Some(_), Some(until)) => format!(
+            "@{} is now marked as away until {}.",
+            event.user().login,
+            until.format("%Y-%m-%d")
+        ),
+        (Some(_), None) => format!("@{} is now marked as away.", event.user().login),
+        (None, _) => format!("@{} is no longer marked as away.", event.user().login),
+    };
+    issue.post_comment(&ctx.github, &reply).await?;
+    Ok(())
+}
+
+/// Posts the review capacity, the number of assigned PRs, and the rotation mode
+/// of `user`. Users without review preferences have the defaults.
+async fn show_work(ctx: &Context, issue: &Issue, user: &User) -> anyhow::Result<()> {
+    let review_prefs = {
+        let db = ctx.db.get().await;
+        get_review_prefs(&db, user.id)
+            .await
+            .context("cannot get review preferences")?
+    };
+    let assigned_prs = ctx.workqueue.read().await.assigned_pr_count(user.id);
+    let on_rotation = review_prefs
+        .as_ref()
+        .is_none_or(|prefs| prefs.rotation_mode == RotationMode::OnRotation);
+    issue
+        .post_comment(
+            &ctx.github,
+            &messages::workqueue_message(
+                &user.login,
+                assigned_prs,
+                review_prefs.and_then(|prefs| prefs.max_assigned_prs),
+                on_rotation,
+            ),
+        )
+        .await?;
+    Ok(())
+}
+
+/// Returns whether `user` has as many PRs assigned as their review capacity, or
+/// the `default_capacity` if they have no review preferences.
+async fn is_at_max_capacity(
+    db: &DbClient,
+    workqueue: &RwLock<ReviewerWorkqueue>,
+    config: &AssignConfig,
+    user: &User,
+) -> anyhow::Result<bool> {
+    if config.review_prefs.is_none() {
+        return Ok(false);
+    }
+    let review_prefs = get_review_prefs(db, user.id)
+        .await
+        .context("cannot get review preferences")?;
+    let capacity = match review_prefs {
+        Some(prefs) => prefs
+            .max_assigned_prs
+            .map(|capacity| u64::try_from(capacity).unwrap_or(0)),
+        None => config.default_capacity.map(u64::from),
+    };
+    let Some(capacity) = capacity else {
+        return Ok(false);
+    };
+    Ok(workqueue.read().await.assigned_pr_count(user.id) >= capacity)
+}
+
+/// Posts the review queue of each member of `team`. This is reserved to team
+/// members, as it reveals the workload of the reviewers.
+async fn show_team_work(
+    ctx: &Context,
+    config: &AssignConfig,
+    issue: &Issue,
+    team: &str,
+    is_team_member: bool,
+) -> anyhow::Result<()> {
+    if !is_team_member {
+        issue
+            .post_comment(
+                &ctx.github,
+                "Only Rust team members can look up the review queue of a team.",
+            )
+            .await?;
+        return Ok(());
+    }
+    let teams = ctx.team.teams().await?;
+    let members = match expand_teams_and_groups(&teams, issue, config, &[team.to_string()]) {
+        Ok(members) => members,
+        Err(error) => {
+            issue.post_comment(&ctx.github, &error.to_string()).await?;
+            return Ok(());
+        }
+    };
+    let mut members: Vec<&str> = members.iter().map(|m| m.name.as_str()).collect();
+    members.sort_by_key(|name| name.to_lowercase());
 
-        match issue.set_assignee(&ctx.github, &to_assign).await {
-            Ok(()) => {
-                e.apply(&ctx.github, String::new()).await?;
+    let (review_prefs, user_ids) = {
+        let db = ctx.db.get().await;
+        let review_prefs = get_review_prefs_batch(&db, &members)
+            .await
+            .context("cannot fetch review preferences")?;
+        let user_ids = get_user_ids_batch(&db, &members)
+            .await
+            .context("cannot fetch user ids")?;
+        (review_prefs, user_ids)
+    };
+    let default_capacity = config
+        .default_capacity
+        .filter(|_| config.review_prefs.is_some());
+    let workqueue = ctx.workqueue.read().await;
+    let reviewers: Vec<_> = members
+        .into_iter()
+        .map(|name| {
+            let prefs = review_prefs.get(name);
+            let user_id = prefs
+                .map(|prefs| prefs.user_id as UserId)
+                .or_else(|| user_ids.get(name).copied());
+            let assigned_prs = user_id.map_or(0, |id| workqueue.assigned_pr_count(id));
+            let capacity = match prefs {
+                Some(prefs) => prefs.max_assigned_prs,
+                None => default_capacity.map(|capacity| capacity as i32),
+            };
+            (name, assigned_prs, capacity)
+        })
+        .collect();
+    drop(workqueue);
+
+    issue
+        .post_comment(
+            &ctx.github,
+            &messages::team_workqueue_message(team, &reviewers),
+        )
+        .await?;
+    Ok(())
+}
+
+/// Sets the review capacity of the commenter, or of `username` if the
+/// commenter is a team member. `None` is an unlimited capacity.
+async fn set_review_capacity(
+    ctx: &Context,
+    event: &Event,
+    issue: &Issue,
+    username: Option<String>,
+    capacity: Option<u32>,
+    is_team_member: bool,
+) -> anyhow::Result<()> {
+    let user = match username {
+        Some(username) if !username.eq_ignore_ascii_case(&event.user().login) => {
+            if !is_team_member {
+                bail!("Only Rust team members can set the review capacity of other users.");
+            }
+            let Some(id) = ctx.team.get_gh_id_from_username(&username).await? else {
+                bail!("Cannot find `{username}` in the team database.");
+            };
+            User {
+                login: username,
+                id,
+            }
+        }
+        _ => event.user().clone(),
+    };
+
+    let db = ctx.db.get().await;
+    let rotation_mode = get_review_prefs(&db, user.id)
+        .await?
+        .map(|prefs| prefs.rotation_mode)
+        .unwrap_or_default();
+    upsert_review_prefs(&db, user.clone(), capacity, rotation_mode).await?;
+    issue
+        .post_comment(
+            &ctx.github,
+            &messages::review_capacity_message(&user.login, capacity),
+        )
+        .await?;
+    Ok(())
+}
+
+/// Puts `user` on or off the review rotation. Users off the rotation are not
+/// assigned automatically, but can still be requested with `r?`.
+async fn set_rotation_mode(
+    ctx: &Context,
+    issue: &Issue,
+    user: &User,
+    on_rotation: bool,
+) -> anyhow::Result<()> {
+    let rotation_mode = if on_rotation {
+        RotationMode::OnRotation
+    } else {
+        RotationMode::OffRotation
+    };
+    let db = ctx.db.get().await;
+    let capacity = get_review_prefs(&db, user.id)
+        .await?
+        .and_then(|prefs| prefs.max_assigned_prs)
+        .map(|capacity| capacity as u32);
+    upsert_review_prefs(&db, user.clone(), capacity, rotation_mode).await?;
+    issue
+        .post_comment(
+            &ctx.github,
+            &messages::rotation_mode_message(&user.login, on_rotation),
+        )
+        .await?;
+    Ok(())
+}
+
+/// Sets (or ends, if `dates` is `None`) the vacation of the commenter, from
+/// the first to the last day given in the `YYYY-MM-DD` format.
+async fn set_vacation_range(
+    ctx: &Context,
+    event: &Event,
+    issue: &Issue,
+    dates: Option<(String, String)>,
+) -> anyhow::Result<()> {
+    let user = event.user();
+    let vacation = match dates {
+        Some((start, end)) => {
+            let parse = |date: &str| chrono::NaiveDate::parse_from_str(date, "%Y-%m-%d");
+            let (Ok(start), Ok(end)) = (parse(&start), parse(&end)) else {
+                issue
+                    .post_comment(
+                        &ctx.github,
+                        "Invalid vacation dates, expected the `YYYY-MM-DD` format.",
+                    )
+                    .await?;
                 return Ok(());
-            } // we are done
-            Err(github::AssignmentError::InvalidAssignee) => {
+            };
+            if end < start || end < chrono::Utc::now().date_naive() {
                 issue
-                    .set_assignee(&ctx.github, &ctx.username)
-                    .await
-                    .context("self-assignment failed")?;
-                let cmt_body = format!(
-                    "This issue has been assigned to @{} via [this comment]({}).",
-                    to_assign,
-                    event.html_url().unwrap()
-                );
-                e.apply(&ctx.github, cmt_body).await?;
+                    .post_comment(
+                        &ctx.github,
+                        "The last day of the vacation must be after its first day, \
+                         and not in the past.",
+                    )
+                    .await?;
+                return Ok(());
             }
-            Err(e) => return Err(e.into()),
+            // The vacation ends at the end of its last day.
+            let end = end + chrono::Days::new(1);
+            Some((
+                start.and_time(chrono::NaiveTime::MIN).and_utc(),
+                end.and_time(chrono::NaiveTime::MIN).and_utc(),
+            ))
         }
-    }
+        None => None,
+    };
 
+    let db = ctx.db.get().await;
+    set_vacation(&db, user.clone(), vacation).await?;
+
+    let reply = match vacation {
+        Some((start, end)) => format!(
+            "@{} is now on vacation from {} to {}, and won't be assigned PRs automatically \
+             meanwhile.",
+            user.login,
+            start.format("%Y-%m-%d"),
+            (end - chrono::Days::new(1)).format("%Y-%m-%d")
+        ),
+        None => format!("@{} is no longer on vacation.", user.login),
+    };
+    issue.post_comment(&ctx.github, &reply).await?;
     Ok(())
 }
 
@@ -698,30 +2579,67 @@ fn get_team_name<'a>(teams: &Teams, issue: &Issue, name: &'a str) -> Option<&'a
     teams.teams.get(team_name).map(|_| team_name)
 }
 
+/// Returns the names of the teams that `username` is a direct member of.
+fn teams_of_user<'a>(teams: &'a Teams, username: &str) -> HashSet<&'a str> {
+    teams
+        .teams
+        .iter()
+        .filter(|(_, team)| {
+            team.members
+                .iter()
+                .any(|member| member.github.eq_ignore_ascii_case(username))
+        })
+        .map(|(name, _)| name.as_str())
+        .collect()
+}
+
 #[derive(PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
 enum FindReviewerError {
     /// User specified something like `r? foo/bar` where that team name could
     /// not be found.
     TeamNotFound(String),
+    /// The requested team exists, but it does not have any members.
+    EmptyTeam { team: String },
     /// No reviewer could be found.
     ///
     /// This could happen if there is a cyclical group or other misconfiguration.
     /// `initial` is the initial list of candidate names.
     NoReviewer { initial: Vec<String> },
+    /// All the reviewers of a team or group request have too many PRs
+    /// assigned to them. `initial` is the initial list of candidate names.
+    NoReviewerHasCapacity { initial: Vec<String> },
+    /// All the reviewers of a team or group request are off the review
+    /// rotation, e.g. on vacation. `off_rotation` are their usernames.
+    NoReviewerOnRotation {
+        initial: Vec<String>,
+        off_rotation: Vec<String>,
+    },
     /// Requested reviewer is off the review rotation (e.g. on a vacation).
     /// Either the username is in [users_on_vacation] in `triagebot.toml` or the user has
     /// configured [RotationMode::OffRotation] in their reviewer preferences.
     ReviewerOffRotation { username: String },
     /// Requested reviewer is PR author
     ReviewerIsPrAuthor { username: String },
+    /// Requested reviewer is the bot itself
+    ReviewerIsBot { username: String },
     /// Requested reviewer is already assigned to that PR
     ReviewerAlreadyAssigned { username: String },
     /// Requested reviewer was already assigned previously to that PR.
     ReviewerPreviouslyAssigned { username: String },
+    /// Requested reviewer declined this PR with `not-me` before.
+    ReviewerDeclined { username: String },
     /// Data required for assignment could not be loaded from the DB.
     DatabaseError(String),
     /// The reviewer has too many PRs already assigned.
     ReviewerAtMaxCapacity { username: String },
+    /// The reviewer has set an away status with a custom message.
+    ReviewerAway { username: String, message: String },
+    /// The reviewer opted out of reviewing PRs of the PR author.
+    /// The reason is private, so it must not be revealed in the error message.
+    ReviewerUnavailable { username: String },
+    /// The reviewer has an assigned PR without any recent activity
+    /// (see `stale_review_days`).
+    ReviewerHasStaleReviews { username: String },
 }
 
 impl std::error::Error for FindReviewerError {}
@@ -738,6 +2656,13 @@ impl fmt::Display for FindReviewerError {
                     Reviewer group names can be found in `triagebot.toml` in this repo."
                 )
             }
+            FindReviewerError::EmptyTeam { team } => {
+                write!(
+                    f,
+                    "Team `{team}` has no assignable members.\n\
+                     Use `r?` to specify someone else to assign."
+                )
+            }
             FindReviewerError::NoReviewer { initial } => {
                 write!(
                     f,
@@ -747,18 +2672,45 @@ impl fmt::Display for FindReviewerError {
                     initial.join(",")
                 )
             }
+            FindReviewerError::NoReviewerHasCapacity { initial } => {
+                write!(
+                    f,
+                    "All the reviewers of `{}` have too many PRs assigned to them.\n\
+                     Use `r?` to specify someone else to assign.",
+                    initial.join(",")
+                )
+            }
+            FindReviewerError::NoReviewerOnRotation { initial, .. } => {
+                write!(
+                    f,
+                    "All the reviewers of `{}` are off the review rotation at the moment.\n\
+                     Use `r?` to specify someone else to assign.",
+                    initial.join(",")
+                )
+            }
             FindReviewerError::ReviewerOffRotation { username } => {
                 write!(f, "{}", messages::reviewer_off_rotation_message(username))
             }
             FindReviewerError::ReviewerIsPrAuthor { .. } => {
                 write!(f, "{}", messages::REVIEWER_IS_PR_AUTHOR)
             }
+            FindReviewerError::ReviewerIsBot { username } => {
+                write!(
+                    f,
+                    r"`{username}` is a bot and cannot review PRs.
+
+Please select a different reviewer.",
+                )
+            }
             FindReviewerError::ReviewerAlreadyAssigned { .. } => {
                 write!(f, "{}", messages::REVIEWER_ALREADY_ASSIGNED)
             }
             FindReviewerError::ReviewerPreviouslyAssigned { username } => {
                 write!(f, "{}", messages::reviewer_assigned_before(username))
             }
+            FindReviewerError::ReviewerDeclined { username } => {
+                write!(f, "{}", messages::reviewer_declined_before(username))
+            }
             FindReviewerError::DatabaseError(error) => {
                 write!(f, "Database error: {error}")
             }
@@ -767,6 +2719,20 @@ impl fmt::Display for FindReviewerError {
                     f,
                     r"`{username}` has too many PRs assigned to them.
 
+Please select a different reviewer.",
+                )
+            }
+            FindReviewerError::ReviewerAway { username, message } => {
+                write!(f, "{}", messages::reviewer_away_message(username, message))
+            }
+            FindReviewerError::ReviewerUnavailable { username } => {
+                write!(f, "{}", messages::reviewer_unavailable_message(username))
+            }
+            FindReviewerError::ReviewerHasStaleReviews { username } => {
+                write!(
+                    f,
+                    r"`{username}` has PRs assigned to them without any recent activity.
+
 Please select a different reviewer.",
                 )
             }
@@ -781,6 +2747,9 @@ Please select a different reviewer.",
 struct ReviewerSelection {
     name: String,
     suppressed_error: Option<FindReviewerError>,
+    /// The `strict-alternate` rotation the reviewer was picked from. It only
+    /// moves on to the next reviewer once this one is assigned.
+    rotation: Option<String>,
 }
 
 impl ReviewerSelection {
@@ -788,6 +2757,7 @@ impl ReviewerSelection {
         Self {
             name,
             suppressed_error: None,
+            rotation: None,
         }
     }
 }
@@ -797,7 +2767,7 @@ impl ReviewerSelection {
 /// The `names` is a list of candidate reviewers `r?`, such as `compiler` or
 /// `@octocat`, or names from the owners map. It can contain GitHub usernames,
 /// auto-assign groups, or rust-lang team names. It must have at least one
-/// entry.
+/// entry. The `bot_username` is never picked.
 async fn find_reviewer_from_names(
     db: &mut DbClient,
     workqueue: Arc<RwLock<ReviewerWorkqueue>>,
@@ -805,17 +2775,23 @@ async fn find_reviewer_from_names(
     config: &AssignConfig,
     issue: &Issue,
     requested_by: &str,
+    bot_username: &str,
     names: &[String],
 ) -> Result<ReviewerSelection, FindReviewerError> {
-    // Fast path for self-assign, which is always allowed.
+    // Fast path for self-assign, which is always allowed, unless the PR author
+    // requests their own review and `allow_self_review` is disabled. The
+    // candidate checks below then reject them.
     if let [name] = names {
-        if is_self_assign(&name, requested_by) {
+        if is_self_assign(&name, requested_by)
+            && (config.allow_self_review || !is_self_assign(&name, &issue.user.login))
+        {
             return Ok(ReviewerSelection::from_name(name.clone()));
         }
     }
 
     let candidates =
-        candidate_reviewers_from_names(db, workqueue, teams, config, issue, names).await?;
+        candidate_reviewers_from_names(db, workqueue, teams, config, issue, bot_username, names)
+            .await?;
     assert!(!candidates.is_empty());
 
     // This uses a relatively primitive random choice algorithm.
@@ -836,7 +2812,9 @@ async fn find_reviewer_from_names(
     // configuring CODEOWNERS as well.
     //
     // These are all ideas for improving the selection here. However, I'm not
-    // sure they are really worth the effort.
+    // sure they are really worth the effort. The `least-recently-active` and
+    // `round-robin` selection modes already narrow down the candidates before
+    // this point.
 
     log::info!(
         "[#{}] Filtered list of candidates: {:?}",
@@ -845,16 +2823,36 @@ async fn find_reviewer_from_names(
     );
 
     // Select a random reviewer from the filtered list
-    Ok(candidates
-        .into_iter()
-        .choose(&mut rand::thread_rng())
-        .expect("candidate_reviewers_from_names should return at least one entry"))
+    Ok(choose_reviewer(config, candidates, &mut rand::thread_rng()))
+}
+
+/// Randomly picks one of the `candidates`, which must not be empty, according
+/// to their `reviewer_weights`.
+fn choose_reviewer<R: Rng>(
+    config: &AssignConfig,
+    candidates: HashSet<ReviewerSelection>,
+    rng: &mut R,
+) -> ReviewerSelection {
+    let mut candidates: Vec<_> = candidates.into_iter().collect();
+    // Sort the candidates so that the choice only depends on `rng`.
+    candidates.sort_by(|a, b| a.name.cmp(&b.name));
+    let weights = candidates
+        .iter()
+        .map(|candidate| config.reviewer_weight(&candidate.name));
+    // All the weights may be zero, fall back to a uniform choice in that case.
+    let index = match WeightedIndex::new(weights) {
+        Ok(distribution) => distribution.sample(rng),
+        Err(_) => rng.gen_range(0..candidates.len()),
+    };
+    candidates.swap_remove(index)
 }
 
 #[derive(Eq, PartialEq, Hash, Debug)]
 struct ReviewerCandidate {
     name: String,
     origin: ReviewerCandidateOrigin,
+    /// This reviewer was expanded from a group with `ignore_capacity`.
+    ignore_capacity: bool,
 }
 
 #[derive(Eq, PartialEq, Hash, Copy, Clone, Debug)]
@@ -880,6 +2878,14 @@ fn expand_teams_and_groups(
     // team multiple times.
     let mut seen_names = HashSet::new();
 
+    // Teams that were found, but do not have any members.
+    let mut empty_teams = Vec::new();
+
+    // The members each team or group was expanded into. This is only used to
+    // log the expansion tree, so it is only filled when that log is enabled.
+    let log_expansion = log::enabled!(target: EXPANSION_LOG_TARGET, log::Level::DEBUG);
+    let mut expansions: HashMap<&str, Vec<&str>> = HashMap::new();
+
     enum Candidate<'a> {
         Direct(&'a str),
         Expanded(&'a str),
@@ -898,6 +2904,15 @@ fn expand_teams_and_groups(
     // should have priority.
     let mut directly_requested: HashSet<&str> = HashSet::new();
 
+    // Each alias is only resolved once, so that cycles end. An alias seen
+    // again, like `compiler = "rust-lang/compiler"`, is resolved as a regular
+    // name.
+    let mut seen_aliases: HashSet<&str> = HashSet::new();
+
+    // The names reached through a group with `ignore_capacity`, whose members
+    // are assigned regardless of their review capacity.
+    let mut uncapped: HashSet<&str> = HashSet::new();
+
     // Loop over names to recursively expand them.
     while let Some(candidate) = to_be_expanded.pop() {
         let name_to_expand = match &candidate {
@@ -910,10 +2925,63 @@ fn expand_teams_and_groups(
         let maybe_group = strip_organization_prefix(issue, name_to_expand);
         let maybe_user = name_to_expand.strip_prefix('@').unwrap_or(name_to_expand);
 
+        if let Some(target) = config.aliases.get(maybe_group)
+            && seen_aliases.insert(maybe_group)
+        {
+            if log_expansion {
+                expansions.insert(name_to_expand, vec![target.as_str()]);
+            }
+            if uncapped.contains(name_to_expand) {
+                uncapped.insert(target.as_str());
+            }
+            // The target of an alias is as directly requested as the alias.
+            to_be_expanded.push(match candidate {
+                Candidate::Direct(_) => Candidate::Direct(target.as_str()),
+                Candidate::Expanded(_) => Candidate::Expanded(target.as_str()),
+            });
+            continue;
+        }
+
+        // Rotations are explicitly prefixed, so they can't be confused with
+        // other names.
+        if name_to_expand.starts_with("rotation:") {
+            let Some(rotation) = config.rotation(name_to_expand) else {
+                return Err(FindReviewerError::TeamNotFound(name_to_expand.to_string()));
+            };
+            if uncapped.contains(name_to_expand) {
+                uncapped.extend(rotation.reviewers.iter().map(|s| s.as_str()));
+            }
+            if seen_names.insert(*name_to_expand) {
+                if log_expansion {
+                    expansions.insert(
+                        *name_to_expand,
+                        rotation.reviewers.iter().map(|s| s.as_str()).collect(),
+                    );
+                }
+                to_be_expanded.extend(
+                    rotation
+                        .reviewers
+                        .iter()
+                        .map(|s| Candidate::Expanded(s.as_str())),
+                );
+            }
+            continue;
+        }
+
         // Try ad-hoc groups first.
-        if let Some(group_members) = config.adhoc_groups.get(maybe_group) {
+        if let Some(group) = config.adhoc_groups.get(maybe_group) {
+            let group_members = group.members();
+            if group.ignore_capacity() || uncapped.contains(name_to_expand) {
+                uncapped.extend(group_members.iter().map(|s| s.as_str()));
+            }
             // If a group has already been expanded, don't expand it again.
             if seen_names.insert(maybe_group) {
+                if log_expansion {
+                    expansions.insert(
+                        name_to_expand,
+                        group_members.iter().map(|s| s.as_str()).collect(),
+                    );
+                }
                 to_be_expanded.extend(
                     group_members
                         .iter()
@@ -928,9 +2996,40 @@ fn expand_teams_and_groups(
         // team name of `rust-lang/rustdoc` (though this does not check if
         // that is a real GitHub team name).
         //
-        // This ignores subteam relationships (it only uses direct members).
+        // Subteams are only expanded with `expand_subteams`, otherwise only the
+        // direct members are used.
         if let Some(team) = maybe_team.and_then(|t| teams.teams.get(t)) {
+            // Like groups, the subteams of a team are only expanded once.
+            let subteams: Vec<&str> =
+                if config.expand_subteams && seen_names.insert(team.name.as_str()) {
+                    teams
+                        .teams
+                        .values()
+                        .filter(|t| t.subteam_of.as_deref() == Some(team.name.as_str()))
+                        .map(|t| t.name.as_str())
+                        .collect()
+                } else {
+                    vec![]
+                };
+            if team.members.is_empty() {
+                empty_teams.push(team.name.clone());
+            }
+            if uncapped.contains(name_to_expand) {
+                uncapped.extend(team.members.iter().map(|m| m.github.as_str()));
+                uncapped.extend(subteams.iter().copied());
+            }
+            if log_expansion {
+                expansions.insert(
+                    name_to_expand,
+                    team.members
+                        .iter()
+                        .map(|m| m.github.as_str())
+                        .chain(subteams.iter().copied())
+                        .collect(),
+                );
+            }
             selected_candidates.extend(team.members.iter().map(|member| member.github.clone()));
+            to_be_expanded.extend(subteams.into_iter().map(Candidate::Expanded));
             continue;
         }
 
@@ -943,12 +3042,36 @@ fn expand_teams_and_groups(
         // Assume it is a user.
         let username = maybe_user.to_string();
         selected_candidates.insert(username);
+        if uncapped.contains(name_to_expand) {
+            uncapped.insert(maybe_user);
+        }
 
         if let Candidate::Direct(_) = candidate {
             directly_requested.insert(maybe_user);
         }
     }
 
+    if log_expansion {
+        let mut rendered = HashSet::new();
+        let tree: Vec<String> = names
+            .iter()
+            .map(|name| format_expansion(name, &expansions, &mut rendered))
+            .collect();
+        log::debug!(
+            target: EXPANSION_LOG_TARGET,
+            "[#{}] expanded review request: {}",
+            issue.number,
+            tree.join(", ")
+        );
+    }
+
+    // If nobody else was found, report the empty team instead of a generic error.
+    if selected_candidates.is_empty()
+        && let Some(team) = empty_teams.pop()
+    {
+        return Err(FindReviewerError::EmptyTeam { team });
+    }
+
     // Now that we have a unique set of candidates, figure out which ones of them were requested
     // directly.
     Ok(selected_candidates
@@ -959,11 +3082,35 @@ fn expand_teams_and_groups(
             } else {
                 ReviewerCandidateOrigin::Expanded
             };
-            ReviewerCandidate { name, origin }
+            let ignore_capacity = uncapped.contains(name.as_str());
+            ReviewerCandidate {
+                name,
+                origin,
+                ignore_capacity,
+            }
         })
         .collect())
 }
 
+/// Formats how `name` was expanded into its members, e.g. `c -> [a -> [pnkfelix], b -> [nrc]]`.
+/// Names that were already formatted (or that were not expanded) are printed as-is.
+fn format_expansion<'a>(
+    name: &'a str,
+    expansions: &HashMap<&'a str, Vec<&'a str>>,
+    rendered: &mut HashSet<&'a str>,
+) -> String {
+    match expansions.get(name) {
+        Some(members) if rendered.insert(name) => {
+            let members: Vec<String> = members
+                .iter()
+                .map(|member| format_expansion(member, expansions, rendered))
+                .collect();
+            format!("{name} -> [{}]", members.join(", "))
+        }
+        _ => name.to_string(),
+    }
+}
+
 /// Returns a list of candidate usernames (from relevant teams) to choose as a reviewer.
 /// If no reviewer is available, returns an error.
 async fn candidate_reviewers_from_names<'a>(
@@ -972,8 +3119,12 @@ async fn candidate_reviewers_from_names<'a>(
     teams: &'a Teams,
     config: &'a AssignConfig,
     issue: &Issue,
+    bot_username: &str,
     names: &'a [String],
 ) -> Result<HashSet<ReviewerSelection>, FindReviewerError> {
+    // Rotations can choose their reviewers differently than the rest of the repository.
+    let selection_mode = config.selection_mode_for(names);
+
     // Step 1: expand teams and groups into candidate names
     let expanded = expand_teams_and_groups(teams, issue, config, names)?;
     let expanded_count = expanded.len();
@@ -988,24 +3139,34 @@ async fn candidate_reviewers_from_names<'a>(
     // Set of candidate usernames to choose from.
     // We go through each expanded candidate and store either success or an error for them.
     let mut candidates: Vec<Result<ReviewerCandidate, FindReviewerError>> = Vec::new();
-    let previous_reviewer_names = get_previous_reviewer_names(db, issue).await;
+    let previous_reviewers = get_previous_reviewers(db, issue).await;
+    // Number of PRs assigned to the candidates, filled when checking their capacity.
+    let mut assigned_pr_counts: HashMap<String, u64> = HashMap::new();
+    // Candidates that are currently outside of their office hours.
+    let mut outside_office_hours: HashSet<String> = HashSet::new();
 
     // Step 2: pre-filter candidates based on checks that we can perform quickly
     for reviewer_candidate in expanded {
         let candidate = &reviewer_candidate.name;
         let name_lower = candidate.to_lowercase();
         let is_pr_author = name_lower == issue.user.login.to_lowercase();
+        let is_bot = name_lower == bot_username.to_lowercase();
         let is_on_vacation = config.is_on_vacation(&candidate);
         let is_already_assigned = issue
             .assignees
             .iter()
             .any(|assignee| name_lower == assignee.login.to_lowercase());
 
-        let is_previously_assigned = previous_reviewer_names.contains(&name_lower);
+        let is_previously_assigned = previous_reviewers.names.contains(&name_lower);
+        let has_declined = previous_reviewers.declined.contains(&name_lower);
 
         // Record the reason why the candidate was filtered out
         let reason = {
-            if is_pr_author {
+            if is_bot {
+                Some(FindReviewerError::ReviewerIsBot {
+                    username: candidate.clone(),
+                })
+            } else if is_pr_author {
                 Some(FindReviewerError::ReviewerIsPrAuthor {
                     username: candidate.clone(),
                 })
@@ -1017,6 +3178,12 @@ async fn candidate_reviewers_from_names<'a>(
                 Some(FindReviewerError::ReviewerAlreadyAssigned {
                     username: candidate.clone(),
                 })
+            } else if reviewer_candidate.origin == ReviewerCandidateOrigin::Expanded && has_declined
+            {
+                // Like below, declines are only considered when r? group is expanded.
+                Some(FindReviewerError::ReviewerDeclined {
+                    username: candidate.clone(),
+                })
             } else if reviewer_candidate.origin == ReviewerCandidateOrigin::Expanded
                 && is_previously_assigned
             {
@@ -1049,6 +3216,16 @@ async fn candidate_reviewers_from_names<'a>(
             .await
             .context("cannot fetch review preferences")
             .map_err(|e| FindReviewerError::DatabaseError(e.to_string()))?;
+        // The user ids of the reviewers without review prefs are only needed
+        // to check their default capacity.
+        let user_ids = if config.default_capacity.is_some() {
+            get_user_ids_batch(db, &usernames)
+                .await
+                .context("cannot fetch user ids")
+                .map_err(|e| FindReviewerError::DatabaseError(e.to_string()))?
+        } else {
+            HashMap::new()
+        };
 
         let workqueue = workqueue.read().await;
 
@@ -1060,13 +3237,54 @@ async fn candidate_reviewers_from_names<'a>(
                 let candidate = candidate?;
                 let username = &candidate.name;
 
-                // If no review prefs were found, we assume the default unlimited
-                // review capacity and being on rotation.
+                // If no review prefs were found, we assume the default review
+                // capacity, unlimited unless configured, and being on rotation.
                 let Some(review_prefs) = review_prefs.get(username.as_str()) else {
+                    if let Some(capacity) = config.default_capacity
+                        && let Some(user_id) = user_ids.get(username.as_str())
+                        && selection_mode != ReviewerSelectionMode::StrictAlternate
+                    {
+                        let assigned_prs = workqueue.assigned_pr_count(*user_id as UserId);
+                        assigned_pr_counts.insert(username.clone(), assigned_prs);
+                        if assigned_prs >= u64::from(capacity) && !candidate.ignore_capacity {
+                            return Err(FindReviewerError::ReviewerAtMaxCapacity {
+                                username: username.clone(),
+                            });
+                        }
+                    }
                     return Ok(candidate);
                 };
-                if let Some(capacity) = review_prefs.max_assigned_prs {
-                    let assigned_prs = workqueue.assigned_pr_count(review_prefs.user_id as UserId);
+                let assigned_prs = workqueue.assigned_pr_count(review_prefs.user_id as UserId);
+                assigned_pr_counts.insert(username.clone(), assigned_prs);
+                if let Some(office_hours) = review_prefs.office_hours
+                    && !office_hours.contains(chrono::Utc::now())
+                {
+                    outside_office_hours.insert(username.clone());
+                }
+                // Approvers are only assigned when they are requested directly.
+                if review_prefs.approver_only
+                    && candidate.origin == ReviewerCandidateOrigin::Expanded
+                {
+                    return Err(FindReviewerError::ReviewerOffRotation {
+                        username: username.clone(),
+                    });
+                }
+                if review_prefs.is_on_vacation(chrono::Utc::now()) {
+                    return Err(FindReviewerError::ReviewerOffRotation {
+                        username: username.clone(),
+                    });
+                }
+                if review_prefs.blocks_author(&issue.user.login) {
+                    return Err(FindReviewerError::ReviewerUnavailable {
+                        username: username.clone(),
+                    });
+                }
+                // The strict alternation ignores the review capacity on purpose,
+                // like the groups with `ignore_capacity`.
+                if let Some(capacity) = review_prefs.max_assigned_prs
+                    && selection_mode != ReviewerSelectionMode::StrictAlternate
+                    && !candidate.ignore_capacity
+                {
                     // Is the reviewer at max capacity?
                     if (assigned_prs as i32) >= capacity {
                         return Err(FindReviewerError::ReviewerAtMaxCapacity {
@@ -1074,11 +3292,25 @@ async fn candidate_reviewers_from_names<'a>(
                         });
                     }
                 }
+                if let Some(days) = config.stale_review_days {
+                    let cutoff = chrono::Utc::now() - chrono::Duration::days(days.into());
+                    if workqueue.has_pr_not_updated_since(review_prefs.user_id as UserId, cutoff) {
+                        return Err(FindReviewerError::ReviewerHasStaleReviews {
+                            username: username.clone(),
+                        });
+                    }
+                }
                 if review_prefs.rotation_mode == RotationMode::OffRotation {
                     return Err(FindReviewerError::ReviewerOffRotation {
                         username: username.clone(),
                     });
                 }
+                if let Some(message) = review_prefs.active_away_message() {
+                    return Err(FindReviewerError::ReviewerAway {
+                        username: username.clone(),
+                        message: message.to_string(),
+                    });
+                }
 
                 return Ok(candidate);
             })
@@ -1086,11 +3318,19 @@ async fn candidate_reviewers_from_names<'a>(
     }
     assert_eq!(candidates.len(), expanded_count);
 
-    let valid_candidates: HashSet<&str> = candidates
+    let mut valid_candidates: HashSet<&str> = candidates
         .iter()
         .filter_map(|res| res.as_ref().ok().map(|c| c.name.as_str()))
         .collect();
 
+    // Candidates outside of their office hours are only picked if nobody else is available.
+    if valid_candidates
+        .iter()
+        .any(|name| !outside_office_hours.contains(*name))
+    {
+        valid_candidates.retain(|name| !outside_office_hours.contains(*name));
+    }
+
     log::debug!(
         "Candidate reviewer results for review request `{}` on `{}`: {:?}",
         names.join(", "),
@@ -1110,39 +3350,262 @@ async fn candidate_reviewers_from_names<'a>(
                 // If the reviewer is at capacity or off rotation, allow them to be requested,
                 // but store the suppressed error.
                 FindReviewerError::ReviewerOffRotation { username }
-                | FindReviewerError::ReviewerAtMaxCapacity { username } => username,
+                | FindReviewerError::ReviewerAtMaxCapacity { username }
+                | FindReviewerError::ReviewerHasStaleReviews { username }
+                | FindReviewerError::ReviewerAway { username, .. } => username,
                 _ => return Err(error),
             };
             Ok(HashSet::from([ReviewerSelection {
                 name: username.to_string(),
                 suppressed_error: Some(error),
+                rotation: None,
             }]))
         } else {
             // If it was a request for a team or a group, and no one is available, simply
-            // return `NoReviewer`.
+            // return `NoReviewer`, unless they were all filtered out because of their
+            // capacity or are off the rotation. The PR author and the bot are not taken into
+            // account, as they can never be picked.
             log::warn!(
                 "No valid candidates found for review request on {}. Reasons: {:?}",
                 issue.global_id(),
                 candidates
             );
-            Err(FindReviewerError::NoReviewer {
-                initial: names.to_vec(),
-            })
+            let errors: Vec<_> = candidates
+                .iter()
+                .filter_map(|res| res.as_ref().err())
+                .filter(|e| {
+                    !matches!(
+                        e,
+                        FindReviewerError::ReviewerIsPrAuthor { .. }
+                            | FindReviewerError::ReviewerIsBot { .. }
+                    )
+                })
+                .collect();
+            let mut off_rotation: Vec<String> = errors
+                .iter()
+                .filter_map(|e| match e {
+                    FindReviewerError::ReviewerOffRotation { username } => Some(username.clone()),
+                    _ => None,
+                })
+                .collect();
+            off_rotation.sort();
+            let at_capacity = !errors.is_empty()
+                && errors
+                    .iter()
+                    .all(|e| matches!(e, FindReviewerError::ReviewerAtMaxCapacity { .. }));
+            if !errors.is_empty() && off_rotation.len() == errors.len() {
+                Err(FindReviewerError::NoReviewerOnRotation {
+                    initial: names.to_vec(),
+                    off_rotation,
+                })
+            } else if at_capacity {
+                Err(FindReviewerError::NoReviewerHasCapacity {
+                    initial: names.to_vec(),
+                })
+            } else {
+                Err(FindReviewerError::NoReviewer {
+                    initial: names.to_vec(),
+                })
+            }
         }
     } else {
+        // Step 5: narrow down the candidates according to the selection mode
+        if matches!(
+            selection_mode,
+            ReviewerSelectionMode::LeastAssigned | ReviewerSelectionMode::LoadBalance
+        ) {
+            fill_assigned_pr_counts(db, &workqueue, &valid_candidates, &mut assigned_pr_counts)
+                .await?;
+        }
+        let valid_candidates = match selection_mode {
+            ReviewerSelectionMode::Random => valid_candidates,
+            ReviewerSelectionMode::LeastAssigned => {
+                least_assigned(valid_candidates, &assigned_pr_counts)
+            }
+            ReviewerSelectionMode::LeastRecentlyActive => {
+                least_recently_active(db, valid_candidates).await?
+            }
+            ReviewerSelectionMode::RoundRobin => {
+                least_recently_assigned(db, valid_candidates).await?
+            }
+            ReviewerSelectionMode::LoadBalance => load_balance(
+                valid_candidates,
+                &assigned_pr_counts,
+                &mut rand::thread_rng(),
+            ),
+            ReviewerSelectionMode::StrictAlternate => {
+                strict_alternate(db, issue, names, valid_candidates).await?
+            }
+        };
+        let rotation = (selection_mode == ReviewerSelectionMode::StrictAlternate)
+            .then(|| rotation_key(issue, names));
         Ok(valid_candidates
             .into_iter()
-            .map(|s| ReviewerSelection::from_name(s.to_string()))
+            .map(|s| ReviewerSelection {
+                rotation: rotation.clone(),
+                ..ReviewerSelection::from_name(s.to_string())
+            })
             .collect())
     }
 }
 
-async fn get_previous_reviewer_names(db: &mut DbClient, issue: &Issue) -> HashSet<String> {
+/// Adds the number of PRs assigned to the `candidates` whose capacity was not
+/// checked, e.g. because they have no review preferences. Candidates unknown
+/// to the database are left out.
+async fn fill_assigned_pr_counts(
+    db: &DbClient,
+    workqueue: &RwLock<ReviewerWorkqueue>,
+    candidates: &HashSet<&str>,
+    assigned_pr_counts: &mut HashMap<String, u64>,
+) -> Result<(), FindReviewerError> {
+    let missing: Vec<&str> = candidates
+        .iter()
+        .copied()
+        .filter(|name| !assigned_pr_counts.contains_key(*name))
+        .collect();
+    if missing.is_empty() {
+        return Ok(());
+    }
+    let user_ids = get_user_ids_batch(db, &missing)
+        .await
+        .context("cannot fetch user ids")
+        .map_err(|e| FindReviewerError::DatabaseError(e.to_string()))?;
+    let workqueue = workqueue.read().await;
+    for (name, user_id) in user_ids {
+        let assigned_prs = workqueue.assigned_pr_count(user_id as UserId);
+        assigned_pr_counts.insert(name.to_string(), assigned_prs);
+    }
+    Ok(())
+}
+
+/// Keeps only the candidates with the fewest assigned PRs. Candidates missing
+/// from `assigned_pr_counts` are considered to have no assigned PRs.
+fn least_assigned<'a>(
+    candidates: HashSet<&'a str>,
+    assigned_pr_counts: &HashMap<String, u64>,
+) -> HashSet<&'a str> {
+    let count = |name: &str| assigned_pr_counts.get(name).copied().unwrap_or(0);
+    let fewest = candidates.iter().map(|name| count(name)).min();
+    candidates
+        .into_iter()
+        .filter(|name| Some(count(name)) == fewest)
+        .collect()
+}
+
+/// Keeps one of the candidates, picked randomly with a probability inversely
+/// proportional to their number of assigned PRs plus one. Candidates missing
+/// from `assigned_pr_counts` are considered to have no assigned PRs.
+fn load_balance<'a, R: Rng>(
+    candidates: HashSet<&'a str>,
+    assigned_pr_counts: &HashMap<String, u64>,
+    rng: &mut R,
+) -> HashSet<&'a str> {
+    let mut candidates: Vec<&str> = candidates.into_iter().collect();
+    // Sort the candidates so that the choice only depends on `rng`.
+    candidates.sort();
+    let weights = candidates.iter().map(|name| {
+        let count = assigned_pr_counts.get(*name).copied().unwrap_or(0);
+        1.0 / (count + 1) as f64
+    });
+    let index = WeightedIndex::new(weights)
+        .expect("the weights are positive")
+        .sample(rng);
+    HashSet::from([candidates[index]])
+}
+
+/// Keeps only the candidates whose last activity on their assigned PRs is the oldest.
+/// Candidates without any recorded activity are considered to be the least recently active.
+async fn least_recently_active<'a>(
+    db: &DbClient,
+    candidates: HashSet<&'a str>,
+) -> Result<HashSet<&'a str>, FindReviewerError> {
+    let usernames: Vec<&str> = candidates.iter().copied().collect();
+    let activity = get_last_activity_batch(db, &usernames)
+        .await
+        .context("cannot fetch reviewer activity")
+        .map_err(|e| FindReviewerError::DatabaseError(e.to_string()))?;
+
+    // `None` sorts before any timestamp, so reviewers that were never active win.
+    let oldest = candidates
+        .iter()
+        .map(|name| activity.get(name).copied())
+        .min()
+        .flatten();
+    Ok(candidates
+        .into_iter()
+        .filter(|name| activity.get(name).copied() == oldest)
+        .collect())
+}
+
+/// Keeps only the candidates that were assigned a PR the least recently. Candidates
+/// that were never assigned a PR come first.
+async fn least_recently_assigned<'a>(
+    db: &DbClient,
+    candidates: HashSet<&'a str>,
+) -> Result<HashSet<&'a str>, FindReviewerError> {
+    let usernames: Vec<&str> = candidates.iter().copied().collect();
+    let last_assigned = get_last_assigned_batch(db, &usernames)
+        .await
+        .context("cannot fetch the last assignments of reviewers")
+        .map_err(|e| FindReviewerError::DatabaseError(e.to_string()))?;
+
+    // `None` sorts before any timestamp, so reviewers that were never assigned win.
+    let oldest = candidates
+        .iter()
+        .map(|name| last_assigned.get(name).copied())
+        .min()
+        .flatten();
+    Ok(candidates
+        .into_iter()
+        .filter(|name| last_assigned.get(name).copied() == oldest)
+        .collect())
+}
+
+/// Returns the key of the `strict-alternate` rotation of a review request for
+/// `names` in the repository of `issue`.
+fn rotation_key(issue: &Issue, names: &[String]) -> String {
+    let mut names = names.to_vec();
+    names.sort();
+    format!("{}:{}", issue.repository(), names.join(","))
+}
+
+/// Keeps only the candidate that follows the last reviewer assigned from the
+/// same review request, in alphabetical order, so that the candidates are
+/// assigned in turn.
+///
+/// The rotation is only recorded by [`set_assignee`], once the candidate is
+/// actually assigned.
+async fn strict_alternate<'a>(
+    db: &DbClient,
+    issue: &Issue,
+    names: &[String],
+    candidates: HashSet<&'a str>,
+) -> Result<HashSet<&'a str>, FindReviewerError> {
+    let rotation = rotation_key(issue, names);
+    let last_reviewer = get_last_reviewer(db, &rotation)
+        .await
+        .map_err(|e| FindReviewerError::DatabaseError(e.to_string()))?
+        .map(|name| name.to_lowercase());
+
+    let mut candidates: Vec<&str> = candidates.into_iter().collect();
+    candidates.sort_by_key(|name| name.to_lowercase());
+    let next = last_reviewer
+        .and_then(|last| {
+            candidates
+                .iter()
+                .find(|name| name.to_lowercase() > last)
+                .copied()
+        })
+        .unwrap_or(candidates[0]);
+    Ok(HashSet::from([next]))
+}
+
+async fn get_previous_reviewers(db: &mut DbClient, issue: &Issue) -> Reviewers {
     let state: IssueData<'_, Reviewers> =
         match IssueData::load(db, &issue, PREVIOUS_REVIEWERS_KEY).await {
             Ok(state) => state,
-            Err(_) => return HashSet::new(),
+            Err(_) => return Reviewers::default(),
         };
 
-    state.data.names
+    state.data
 }
@@ -0,0 +1,160 @@
+//! Reassignment of stale reviews, used with the `auto_reassign_days` option.
+//!
+//! A scheduled job looks for the PRs waiting on a review whose reviewer has
+//! neither commented on them nor been assigned to them for that many days,
+//! and picks another reviewer from the diff, like `@rustbot not-me` does.
+
+use super::{
+    AssignConfig, PREVIOUS_REVIEWERS_KEY, Reviewers, assign_replacement, messages, proposal,
+};
+use crate::db::issue_data::IssueData;
+use crate::github::{Issue, Query};
+use crate::handlers::Context;
+use crate::handlers::reviewer_activity::PR_ACTIVITY_KEY;
+use crate::jobs::Job;
+use anyhow::Context as _;
+use async_trait::async_trait;
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use tracing as log;
+
+#[derive(Serialize, Deserialize)]
+pub struct AutoReassignMetadata {
+    /// Repositories whose PRs are checked, e.g. `rust-lang/rust`.
+    pub repos: Vec<String>,
+}
+
+impl AutoReassignMetadata {
+    /// The metadata of the scheduled job, with the comma-separated
+    /// repositories of the `AUTO_REASSIGN_REPOS` environment variable, or
+    /// rust-lang/rust if unset.
+    pub fn from_env() -> Self {
+        let repos = std::env::var("AUTO_REASSIGN_REPOS")
+            .map(|repos| {
+                repos
+                    .split(',')
+                    .map(|repo| repo.trim().to_string())
+                    .filter(|repo| !repo.is_empty())
+                    .collect()
+            })
+            .unwrap_or_else(|_| vec!["rust-lang/rust".to_string()]);
+        Self { repos }
+    }
+}
+
+/// A scheduled job that reassigns the PRs whose reviewer has been inactive
+/// for more than `auto_reassign_days`.
+pub struct AutoReassignJob;
+
+#[async_trait]
+impl Job for AutoReassignJob {
+    fn name(&self) -> &'static str {
+        "auto_reassign_stale_reviews"
+    }
+
+    async fn run(&self, ctx: &Context, metadata: &serde_json::Value) -> anyhow::Result<()> {
+        let metadata: AutoReassignMetadata = serde_json::from_value(metadata.clone())
+            .context("unable to deserialize the metadata in auto reassign job")?;
+
+        for repo in &metadata.repos {
+            let repository = ctx.github.repository(repo).await?;
+            let config = match crate::config::get(&ctx.github, &repository).await {
+                Ok(config) => config,
+                Err(err) => {
+                    log::warn!("cannot load the config of {repo}: {err:?}");
+                    continue;
+                }
+            };
+            let Some(config) = &config.assign else {
+                continue;
+            };
+            let Some(days) = config.auto_reassign_days else {
+                continue;
+            };
+            let query = Query {
+                filters: vec![
+                    ("state", "open"),
+                    ("is", "pull-request"),
+                    ("draft", "false"),
+                ],
+                include_labels: config
+                    .waiting_on_review_label
+                    .iter()
+                    .map(|l| l.as_str())
+                    .collect(),
+                exclude_labels: vec![],
+            };
+            for pr in repository.get_issues(&ctx.github, &query).await? {
+                if pr.assignees.len() != 1 {
+                    continue;
+                }
+                // The issues API does not return the base and head commits
+                // needed to pick another reviewer from the diff.
+                let result = match repository.get_pr(&ctx.github, pr.number).await {
+                    Ok(pr) => reassign_if_stale(ctx, config, &pr, days).await,
+                    Err(err) => Err(err),
+                };
+                if let Err(err) = result {
+                    log::warn!("cannot reassign {repo}#{}: {err:?}", pr.number);
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Picks another reviewer for `pr` if its only assignee has neither commented
+/// on it nor been assigned to it for `days` days.
+///
+/// Returns the new reviewer, or None if the review is not stale or no other
+/// reviewer could be found.
+pub(super) async fn reassign_if_stale(
+    ctx: &Context,
+    config: &AssignConfig,
+    pr: &Issue,
+    days: u32,
+) -> anyhow::Result<Option<String>> {
+    let [reviewer] = pr.assignees.as_slice() else {
+        return Ok(None);
+    };
+    // A replacement was already proposed, and is replaced if it does not answer.
+    if reviewer == &pr.user || proposal::has_proposal(ctx, pr).await? {
+        return Ok(None);
+    }
+    let cutoff = Utc::now() - Duration::days(days.into());
+    if last_touched(ctx, pr).await? >= cutoff {
+        return Ok(None);
+    }
+
+    log::info!(
+        "review of {} by {} is stale, reassigning",
+        pr.global_id(),
+        reviewer.login
+    );
+    let Some(replacement) = assign_replacement(ctx, config, pr).await? else {
+        return Ok(None);
+    };
+    pr.post_comment(
+        &ctx.github,
+        &messages::auto_reassign_message(&reviewer.login, &replacement, days),
+    )
+    .await?;
+    Ok(Some(replacement))
+}
+
+/// Returns when an assignee of `pr` last commented on it, or when its reviewer
+/// was last assigned, falling back to when it was opened.
+async fn last_touched(ctx: &Context, pr: &Issue) -> anyhow::Result<DateTime<Utc>> {
+    let mut db = ctx.db.get().await;
+    let assigned_at = IssueData::<'_, Reviewers>::load(&mut db, pr, PREVIOUS_REVIEWERS_KEY)
+        .await?
+        .data
+        .assigned_at;
+    let commented_at = IssueData::<'_, Option<DateTime<Utc>>>::load(&mut db, pr, PR_ACTIVITY_KEY)
+        .await?
+        .data;
+    Ok([assigned_at, commented_at]
+        .into_iter()
+        .flatten()
+        .fold(pr.created_at, DateTime::max))
+}
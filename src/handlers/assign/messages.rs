@@ -24,6 +24,15 @@ when appropriate:
     )
 }
 
+pub fn short_welcome_message(assignee: Option<&str>) -> String {
+    match assignee {
+        Some(assignee) => {
+            format!("Thanks for the pull request! It has been assigned to @{assignee}.")
+        }
+        None => "Thanks for the pull request!".to_string(),
+    }
+}
+
 pub fn welcome_with_reviewer(assignee: &str) -> String {
     format!("@{assignee} (or someone else)")
 }
@@ -40,6 +49,73 @@ Use `r?` to explicitly pick a reviewer"
     )
 }
 
+pub fn assigned_pr_count_message(assignee: &str, count: u64) -> String {
+    match count {
+        1 => format!("@{assignee} is currently reviewing 1 other PR."),
+        _ => format!("@{assignee} is currently reviewing {count} other PRs."),
+    }
+}
+
+pub fn diff_size_message(added: u32, removed: u32, files: usize) -> String {
+    match files {
+        1 => format!("(+{added}/-{removed} in 1 file)"),
+        _ => format!("(+{added}/-{removed} across {files} files)"),
+    }
+}
+
+pub fn large_diff_message(lines: u32) -> String {
+    format!("(large diff: {lines}+ lines)")
+}
+
+pub fn next_pr_assigned_message(reviewer: &str, pr_url: &str) -> String {
+    format!("@{reviewer} has been assigned {pr_url}.")
+}
+
+pub const NO_NEXT_PR: &str =
+    "No unassigned pull request modifying an area owned by you or your teams could be found.";
+
+pub fn no_replacement_reviewer_message(declined: &str) -> String {
+    format!(
+        "@{declined} has been unassigned, but no other reviewer could be found. \
+         Please use `r?` to pick one."
+    )
+}
+
+pub fn review_proposal_message(reviewer: &str, bot: &str, timeout_days: u32) -> String {
+    format!(
+        "Thanks for the pull request! @{reviewer} was picked to review it.
+
+@{reviewer}: please reply with `@{bot} accept` to be assigned, or with `@{bot} pass` to let \
+another reviewer be picked. Another reviewer is picked if there is no answer within \
+{timeout_days} days."
+    )
+}
+
+pub fn review_proposal_timeout_message(reviewer: &str) -> String {
+    format!("`{reviewer}` did not answer the review proposal in time, picking another reviewer.")
+}
+
+pub fn no_replacement_after_pass_message(reviewer: &str) -> String {
+    format!(
+        "`{reviewer}` passed on reviewing this PR, but no other reviewer could be found. \
+         Please use `r?` to pick one."
+    )
+}
+
+pub fn fallback_after_decline_message(assignee: &str) -> String {
+    format!(
+        "All the reviewers of the modified areas have declined this PR or are unavailable, \
+         so @{assignee} was picked from the fallback reviewers."
+    )
+}
+
+pub fn auto_reassign_message(reviewer: &str, replacement: &str, days: u32) -> String {
+    format!(
+        "`{reviewer}` has not looked at this PR for {days} days, \
+         so the review was handed over to @{replacement}."
+    )
+}
+
 pub fn returning_user_welcome_message_no_reviewer(pr_author: &str) -> String {
     format!("@{pr_author}: no appropriate reviewer found, use `r?` to override")
 }
@@ -52,6 +128,199 @@ Please choose another assignee."
     )
 }
 
+pub fn workqueue_message(
+    user: &str,
+    assigned_prs: u64,
+    capacity: Option<i32>,
+    on_rotation: bool,
+) -> String {
+    let capacity = match capacity {
+        Some(capacity) => capacity.to_string(),
+        None => "unlimited".to_string(),
+    };
+    let rotation = if on_rotation {
+        "on rotation"
+    } else {
+        "off rotation"
+    };
+    format!(
+        "@{user} has {assigned_prs} PR(s) in their review queue.\n\
+         \n\
+         - Review capacity: {capacity}\n\
+         - Rotation mode: {rotation}"
+    )
+}
+
+/// Lists the `(reviewer, assigned PRs, capacity)` of the members of `team`.
+///
+/// The reviewers are not mentioned, to avoid pinging the whole team.
+pub fn team_workqueue_message(team: &str, reviewers: &[(&str, u64, Option<i32>)]) -> String {
+    let mut message = format!(
+        "Review queue of `{team}`:\n\
+         \n\
+         | Reviewer | Assigned PRs | Review capacity |\n\
+         |---|---|---|\n"
+    );
+    for (reviewer, assigned_prs, capacity) in reviewers {
+        let capacity = match capacity {
+            Some(capacity) => capacity.to_string(),
+            None => "unlimited".to_string(),
+        };
+        message.push_str(&format!("| {reviewer} | {assigned_prs} | {capacity} |\n"));
+    }
+    message
+}
+
+pub fn review_capacity_message(user: &str, capacity: Option<u32>) -> String {
+    match capacity {
+        Some(capacity) => format!("The review capacity of @{user} is now {capacity} PR(s)."),
+        None => format!("The review capacity of @{user} is now unlimited."),
+    }
+}
+
+pub fn rotation_mode_message(user: &str, on_rotation: bool) -> String {
+    if on_rotation {
+        format!("@{user} is now on the review rotation.")
+    } else {
+        format!(
+            "@{user} is now off the review rotation, and won't be assigned PRs automatically. \
+             They can still be requested with `r?`."
+        )
+    }
+}
+
+pub fn suggested_reviewer_message(reviewer: Option<&str>) -> String {
+    match reviewer {
+        Some(reviewer) => format!(
+            "`{reviewer}` would currently be picked to review this pull request.\n\
+             \n\
+             Use `r? {reviewer}` to assign them."
+        ),
+        None => "No reviewer would currently be picked automatically for this pull request.\n\
+                 \n\
+                 Use `r?` to specify someone to assign."
+            .to_string(),
+    }
+}
+
+pub fn reviewers_off_rotation_message(usernames: &[String]) -> String {
+    let usernames: Vec<_> = usernames.iter().map(|name| format!("`{name}`")).collect();
+    format!(
+        "No reviewer was assigned automatically: the reviewers of the modified files \
+         ({}) are off the review rotation at the moment, e.g. on vacation.\n\
+         \n\
+         Use `r?` to specify someone else to assign.",
+        usernames.join(", ")
+    )
+}
+
+pub fn reviewer_away_message(username: &str, message: &str) -> String {
+    // Every line is quoted, so that a multi-line message stays in the quote.
+    let message: Vec<String> = message
+        .lines()
+        .map(|line| match line {
+            "" => ">".to_string(),
+            line => format!("> {line}"),
+        })
+        .collect();
+    format!(
+        r"`{username}` is away at the moment and left the following message:
+
+{}",
+        message.join("\n")
+    )
+}
+
+pub fn reviewer_unavailable_message(username: &str) -> String {
+    format!(
+        r"`{username}` cannot be assigned to this PR.
+
+Please choose another assignee."
+    )
+}
+
+pub fn multiple_review_requests_message(names: &[String]) -> String {
+    let names: Vec<String> = names.iter().map(|name| format!("`r? {name}`")).collect();
+    format!(
+        "This pull request contains several review requests ({}), so none of them was used. \
+Please pick one with a new `r?` comment.",
+        names.join(", ")
+    )
+}
+
+pub fn no_reviewer_message(requested_by: &str) -> String {
+    format!(
+        "@{requested_by} marked this pull request as not needing a reviewer, \
+it won't be assigned automatically."
+    )
+}
+
+pub fn multi_area_message(areas: &[(&str, &[String])]) -> String {
+    let mut message = "This pull request modifies several areas, so no reviewer was \
+assigned automatically:\n"
+        .to_string();
+    for (pattern, reviewers) in areas {
+        let reviewers: Vec<String> = reviewers.iter().map(|r| format!("`{r}`")).collect();
+        message.push_str(&format!("\n- `{pattern}`: {}", reviewers.join(", ")));
+    }
+    message.push_str("\n\nUse `r?` to pick a reviewer.");
+    message
+}
+
+pub fn unlabeled_teams_message(labels: &[String]) -> String {
+    let labels: Vec<String> = labels.iter().map(|l| format!("`{l}`")).collect();
+    format!(
+        "To avoid cluttering the labels, these were not added: {}.",
+        labels.join(", ")
+    )
+}
+
+/// Message listing the `owners` patterns matching `path`, and the `pool` of
+/// reviewers they expand to, or why it could not be computed.
+pub fn path_owners_message(
+    path: &str,
+    owners: &[(&str, &[String])],
+    pool: Result<Vec<String>, String>,
+) -> String {
+    if owners.is_empty() {
+        return format!("No `owners` pattern matches `{path}`.");
+    }
+    let mut message = format!("`{path}` matches the following `owners` patterns:\n");
+    for (pattern, reviewers) in owners {
+        let reviewers: Vec<String> = reviewers.iter().map(|r| format!("`{r}`")).collect();
+        message.push_str(&format!("\n- `{pattern}`: {}", reviewers.join(", ")));
+    }
+    match pool {
+        Ok(pool) => {
+            let pool: Vec<String> = pool.iter().map(|r| format!("`{r}`")).collect();
+            message.push_str(&format!("\n\nReviewer pool: {}", pool.join(", ")));
+        }
+        Err(err) => message.push_str(&format!("\n\nNo reviewer pool: {err}")),
+    }
+    message
+}
+
+pub fn owners_message(owners: &[(&str, &[String])]) -> String {
+    if owners.is_empty() {
+        return "No `owners` pattern matches the files modified by this pull request.".to_string();
+    }
+    let mut message =
+        "The files modified by this pull request match the following `owners` patterns:\n"
+            .to_string();
+    for (pattern, reviewers) in owners {
+        let reviewers: Vec<String> = reviewers.iter().map(|r| format!("`{r}`")).collect();
+        message.push_str(&format!("\n- `{pattern}`: {}", reviewers.join(", ")));
+    }
+    message
+}
+
+pub fn assign_config_message(config: &str) -> String {
+    format!(
+        "The `[assign]` configuration of this repository was loaded as:\n\n```toml\n{}\n```",
+        config.trim_end()
+    )
+}
+
 pub fn reviewer_assigned_before(username: &str) -> String {
     format!(
         "Requested reviewer @{username} was already assigned before.
@@ -60,8 +329,49 @@ Please choose another assignee by using `r? @reviewer`."
     )
 }
 
+pub fn shadow_reviewer_message(shadow: &str, reviewer: &str) -> String {
+    format!(
+        "@{shadow} will shadow the review of @{reviewer}. \
+         Shadowing does not count as an assigned PR in the review queue."
+    )
+}
+
+pub fn reviewer_declined_before(username: &str) -> String {
+    format!(
+        "Requested reviewer @{username} has declined this PR before.
+
+Please choose another assignee by using `r? @reviewer`."
+    )
+}
+
 pub const WELCOME_WITHOUT_REVIEWER: &str = "@Mark-Simulacrum (NB. this repo may be misconfigured)";
 
+/// Confirms the assignment of the reviewers of `r? @a @b`, and lists the
+/// names that could not be assigned, with the reason.
+pub fn several_reviewers_message(reviewers: &[&str], failures: &[(String, String)]) -> String {
+    let mut message = if reviewers.is_empty() {
+        "None of the requested reviewers could be assigned.".to_string()
+    } else {
+        let reviewers: Vec<_> = reviewers.iter().map(|name| format!("@{name}")).collect();
+        format!(
+            "Assigned {} to review this pull request.",
+            reviewers.join(", ")
+        )
+    };
+    for (name, error) in failures {
+        message.push_str(&format!("\n\nCould not assign `{name}`: {error}"));
+    }
+    message
+}
+
+pub const SELF_ASSIGN_HAS_NO_CAPACITY: &str = "You have insufficient capacity to be assigned \
+     this pull request at the moment, so it was not assigned to you.
+
+Please choose another assignee, increase your review capacity with `@rustbot work capacity`, \
+or use `@rustbot claim --force` to claim it anyway.";
+
+pub const SHADOW_IS_PR_AUTHOR: &str = "Pull request author cannot shadow the review of their PR.";
+
 pub const REVIEWER_IS_PR_AUTHOR: &str = "Pull request author cannot be assigned as reviewer.
 
 
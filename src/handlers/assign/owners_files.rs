@@ -0,0 +1,227 @@
+//! Support for `OWNERS`-style files committed in the repository.
+//!
+//! When `owners_file` is set in the `[assign]` config, every modified file is
+//! owned by the reviewers listed in the nearest owners file found in its
+//! directory or one of its parent directories. Those override the
+//! `[assign.owners]` table for the whole subtree.
+//!
+//! An owners file lists one reviewer (a username or a team/group name, like in
+//! `[assign.owners]`) per line. Empty lines and `#` comments are ignored.
+//!
+//! To bound the number of requests made for large PRs, each directory is only
+//! looked up once, at most [`MAX_LOOKUPS`] directories are looked up, and the
+//! owners files are cached for the head commit of the PR.
+//!
+//! When `use_codeowners` is set, the patterns of the GitHub `CODEOWNERS` file
+//! are used like additional `[assign.owners]` entries.
+
+use crate::github::{FileDiff, GithubClient, Issue};
+use futures::{StreamExt as _, TryStreamExt as _};
+use ignore::gitignore::GitignoreBuilder;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::path::Path;
+use std::sync::Mutex;
+use tracing as log;
+
+/// Locations of the `CODEOWNERS` file supported by GitHub, in the order
+/// GitHub looks for it.
+const CODEOWNERS_PATHS: &[&str] = &[".github/CODEOWNERS", "CODEOWNERS", "docs/CODEOWNERS"];
+
+/// Maximum number of directories looked up for owners files per PR.
+const MAX_LOOKUPS: usize = 100;
+
+/// Number of owners files fetched concurrently.
+const CONCURRENT_LOOKUPS: usize = 10;
+
+/// Number of PRs whose owners files are kept in the [`OwnersFilesCache`].
+const CACHE_CAPACITY: usize = 100;
+
+/// The owners files recently loaded for PRs, so that the events of a PR don't
+/// fetch them again until it is updated.
+#[derive(Default)]
+pub struct OwnersFilesCache {
+    /// Owners files keyed by repository, owners file name and base and head
+    /// commits of the PR, most recently used first.
+    entries: VecDeque<(String, OwnersFiles)>,
+}
+
+impl OwnersFilesCache {
+    fn get(&mut self, key: &str) -> Option<OwnersFiles> {
+        let pos = self.entries.iter().position(|(k, _)| k == key)?;
+        let entry = self.entries.remove(pos).unwrap();
+        let owners_files = entry.1.clone();
+        self.entries.push_front(entry);
+        Some(owners_files)
+    }
+
+    fn put(&mut self, key: String, owners_files: OwnersFiles) {
+        self.entries.retain(|(k, _)| *k != key);
+        self.entries.truncate(CACHE_CAPACITY - 1);
+        self.entries.push_front((key, owners_files));
+    }
+}
+
+/// The owners files of a repository that apply to a diff.
+#[derive(Debug, Default, Clone)]
+pub(super) struct OwnersFiles {
+    /// Name of the owners files, e.g. `OWNERS`.
+    file_name: String,
+    /// Map of owners file path to its reviewers, or `None` if there is no
+    /// such file. Paths that were not looked up are missing.
+    files: HashMap<String, Option<Vec<String>>>,
+    /// Map of `CODEOWNERS` pattern to its reviewers.
+    codeowners: HashMap<String, Vec<String>>,
+}
+
+impl OwnersFiles {
+    pub(super) fn new(file_name: &str) -> Self {
+        Self {
+            file_name: file_name.to_string(),
+            files: HashMap::new(),
+            codeowners: HashMap::new(),
+        }
+    }
+
+    /// Fetches the owners files that apply to the files of `diff`, at the base
+    /// commit of the `issue` PR.
+    ///
+    /// The base commit is used so that PR authors cannot pick their reviewer
+    /// by modifying an owners file.
+    pub(super) async fn load(
+        client: &GithubClient,
+        cache: &Mutex<OwnersFilesCache>,
+        issue: &Issue,
+        file_name: &str,
+        diff: &[FileDiff],
+    ) -> anyhow::Result<Self> {
+        let mut owners_files = Self::new(file_name);
+        let (Some(base), Some(head)) = (&issue.base, &issue.head) else {
+            return Ok(owners_files);
+        };
+        let repo = issue.repository().to_string();
+        let key = format!("{repo}:{file_name}:{}...{}", base.sha, head.sha);
+        if let Some(cached) = cache.lock().unwrap().get(&key) {
+            return Ok(cached);
+        }
+
+        let paths: HashSet<String> = diff
+            .iter()
+            .flat_map(|file_diff| owners_files.candidate_paths(&file_diff.filename))
+            .collect();
+        let mut paths: Vec<String> = paths.into_iter().collect();
+        // Shallow directories apply to more files, so they are looked up first.
+        paths.sort_by(|a, b| {
+            (a.matches('/').count(), a.as_str()).cmp(&(b.matches('/').count(), b.as_str()))
+        });
+        if paths.len() > MAX_LOOKUPS {
+            log::warn!(
+                "only looking up {MAX_LOOKUPS} of the {} `{file_name}` files of PR {}",
+                paths.len(),
+                issue.global_id()
+            );
+            paths.truncate(MAX_LOOKUPS);
+        }
+
+        let files: Vec<(String, Option<String>)> = futures::stream::iter(paths)
+            .map(|path| {
+                let repo = &repo;
+                async move {
+                    let content = client.raw_file(repo, &base.sha, &path).await?;
+                    let content =
+                        content.map(|content| String::from_utf8_lossy(&content).into_owned());
+                    anyhow::Ok((path, content))
+                }
+            })
+            .buffer_unordered(CONCURRENT_LOOKUPS)
+            .try_collect()
+            .await?;
+        for (path, content) in files {
+            owners_files.insert(path, content.as_deref());
+        }
+
+        cache.lock().unwrap().put(key, owners_files.clone());
+        Ok(owners_files)
+    }
+
+    /// Fetches the `CODEOWNERS` file of the repository, at the base commit of
+    /// the `issue` PR.
+    pub(super) async fn load_codeowners(
+        &mut self,
+        client: &GithubClient,
+        issue: &Issue,
+    ) -> anyhow::Result<()> {
+        let Some(base) = &issue.base else {
+            return Ok(());
+        };
+        let repo = issue.repository().to_string();
+        for path in CODEOWNERS_PATHS {
+            if let Some(content) = client.raw_file(&repo, &base.sha, path).await? {
+                self.set_codeowners(&String::from_utf8_lossy(&content));
+                break;
+            }
+        }
+        Ok(())
+    }
+
+    /// Records the `content` of the `CODEOWNERS` file.
+    ///
+    /// Each line is a pattern followed by its owners. `@user` and `@org/team`
+    /// owners are kept without the `@`, while email owners, which cannot be
+    /// assigned, are ignored. So are patterns without any remaining owner,
+    /// and invalid patterns.
+    pub(super) fn set_codeowners(&mut self, content: &str) {
+        self.codeowners = content
+            .lines()
+            .filter_map(|line| {
+                let mut words = line.split('#').next().unwrap().split_whitespace();
+                let pattern = words.next()?;
+                if GitignoreBuilder::new("/").add_line(None, pattern).is_err() {
+                    return None;
+                }
+                let owners: Vec<String> = words
+                    .filter_map(|owner| owner.strip_prefix('@'))
+                    .filter(|owner| !owner.is_empty())
+                    .map(|owner| owner.to_string())
+                    .collect();
+                (!owners.is_empty()).then(|| (pattern.to_string(), owners))
+            })
+            .collect();
+    }
+
+    /// Returns the patterns of the `CODEOWNERS` file, with their reviewers.
+    pub(super) fn codeowners(&self) -> &HashMap<String, Vec<String>> {
+        &self.codeowners
+    }
+
+    /// Records the `content` of the owners file at `path`, or that there is no
+    /// such file if `content` is `None`.
+    pub(super) fn insert(&mut self, path: String, content: Option<&str>) {
+        let reviewers = content.map(|content| {
+            content
+                .lines()
+                .map(|line| line.split('#').next().unwrap().trim())
+                .filter(|line| !line.is_empty())
+                .map(|line| line.to_string())
+                .collect()
+        });
+        self.files.insert(path, reviewers);
+    }
+
+    /// Returns the path of the nearest owners file of `filename`, together
+    /// with its reviewers.
+    pub(super) fn nearest(&self, filename: &str) -> Option<(&str, &[String])> {
+        self.candidate_paths(filename).into_iter().find_map(|path| {
+            let (path, reviewers) = self.files.get_key_value(&path)?;
+            Some((path.as_str(), reviewers.as_deref()?))
+        })
+    }
+
+    /// Returns the paths where an owners file of `filename` could be, nearest first.
+    fn candidate_paths(&self, filename: &str) -> Vec<String> {
+        Path::new(filename)
+            .ancestors()
+            .skip(1)
+            .map(|dir| dir.join(&self.file_name).to_string_lossy().into_owned())
+            .collect()
+    }
+}
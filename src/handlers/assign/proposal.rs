@@ -0,0 +1,240 @@
+//! Review proposals, used with the `require_acceptance` option.
+//!
+//! Instead of being assigned directly, a reviewer picked automatically is
+//! asked to confirm with `@rustbot accept`. If they answer `@rustbot pass`, or
+//! do not answer in time, another reviewer is proposed.
+
+use super::{
+    AssignConfig, PREVIOUS_REVIEWERS_KEY, ReviewerSelection, Reviewers, assign_replacement,
+    messages, set_assignee,
+};
+use crate::db::assignment_events::AssignmentSource;
+use crate::db::issue_data::IssueData;
+use crate::github::{Event, Issue};
+use crate::handlers::Context;
+use crate::jobs::Job;
+use anyhow::{Context as _, bail};
+use async_trait::async_trait;
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use tokio_postgres::Client as DbClient;
+use tracing as log;
+
+/// Key for the pending review proposal of a PR in the database.
+const PROPOSAL_KEY: &str = "review-proposal";
+
+const PROPOSAL_TIMEOUT_JOB_NAME: &str = "review_proposal_timeout";
+
+/// Number of days a reviewer has to answer a proposal, unless configured.
+const DEFAULT_TIMEOUT_DAYS: u32 = 3;
+
+/// A review proposal waiting for an answer of the reviewer.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct Proposal {
+    reviewer: String,
+    proposed_at: DateTime<Utc>,
+    /// The `strict-alternate` rotation the reviewer was picked from, which
+    /// moves on once they accept.
+    #[serde(default)]
+    rotation: Option<String>,
+}
+
+/// Metadata of the job replacing a reviewer who did not answer a proposal.
+#[derive(Debug, Serialize, Deserialize)]
+struct ProposalTimeout {
+    repo: String,
+    pr: u64,
+    proposal: Proposal,
+}
+
+/// Asks `reviewer` to accept the review of `issue`, and schedules their
+/// replacement if they do not answer in time.
+pub(super) async fn propose_reviewer(
+    ctx: &Context,
+    config: &AssignConfig,
+    issue: &Issue,
+    reviewer: &ReviewerSelection,
+) -> anyhow::Result<()> {
+    let proposal = Proposal {
+        reviewer: reviewer.name.clone(),
+        proposed_at: Utc::now(),
+        rotation: reviewer.rotation.clone(),
+    };
+    {
+        let mut db = ctx.db.get().await;
+        let mut state: IssueData<'_, Option<Proposal>> =
+            IssueData::load(&mut db, issue, PROPOSAL_KEY).await?;
+        state.data = Some(proposal.clone());
+        state.save().await?;
+    }
+
+    let timeout_days = config
+        .acceptance_timeout_days
+        .unwrap_or(DEFAULT_TIMEOUT_DAYS);
+    let timeout_at = proposal.proposed_at + Duration::days(timeout_days.into());
+    let timeout = ProposalTimeout {
+        repo: issue.repository().full_repo_name(),
+        pr: issue.number,
+        proposal,
+    };
+    crate::db::schedule_job(
+        &*ctx.db.get().await,
+        PROPOSAL_TIMEOUT_JOB_NAME,
+        serde_json::to_value(timeout).context("unable to serialize the proposal timeout")?,
+        timeout_at,
+    )
+    .await
+    .context("failed to schedule the timeout of the review proposal")?;
+
+    issue
+        .post_comment(
+            &ctx.github,
+            &messages::review_proposal_message(&reviewer.name, &ctx.username, timeout_days),
+        )
+        .await?;
+    Ok(())
+}
+
+/// Handles `@rustbot accept` and `@rustbot pass` from the reviewer the review
+/// of `issue` was proposed to.
+pub(super) async fn answer_proposal(
+    ctx: &Context,
+    config: &AssignConfig,
+    event: &Event,
+    issue: &Issue,
+    accept: bool,
+) -> anyhow::Result<()> {
+    let user = event.user();
+    let is_proposed = |proposal: &Proposal| proposal.reviewer.eq_ignore_ascii_case(&user.login);
+    let proposal = if issue.is_pr() && issue.is_open() {
+        take_proposal(ctx, issue, is_proposed).await?
+    } else {
+        None
+    };
+    let Some(proposal) = proposal else {
+        bail!("There is no pending review proposal for you on this pull request.");
+    };
+    if accept {
+        let reviewer = ReviewerSelection {
+            rotation: proposal.rotation,
+            ..ReviewerSelection::from_name(user.login.clone())
+        };
+        set_assignee(ctx, issue, &ctx.github, &reviewer, AssignmentSource::Diff).await
+    } else {
+        // Comment events don't include what is needed to compute the diff.
+        let pr = event.repo().get_pr(&ctx.github, issue.number).await?;
+        pass(ctx, config, &pr, &user.login).await
+    }
+}
+
+/// Proposes the review of `issue` to another reviewer than `reviewer`, who is
+/// not picked again.
+async fn pass(
+    ctx: &Context,
+    config: &AssignConfig,
+    issue: &Issue,
+    reviewer: &str,
+) -> anyhow::Result<()> {
+    {
+        let mut db = ctx.db.get().await;
+        let mut state: IssueData<'_, Reviewers> =
+            IssueData::load(&mut db, issue, PREVIOUS_REVIEWERS_KEY).await?;
+        state.data.declined.insert(reviewer.to_lowercase());
+        state.save().await?;
+    }
+    if assign_replacement(ctx, config, issue).await?.is_none() {
+        issue
+            .post_comment(
+                &ctx.github,
+                &messages::no_replacement_after_pass_message(reviewer),
+            )
+            .await?;
+    }
+    Ok(())
+}
+
+/// Returns whether the review of `issue` was proposed to a reviewer who did
+/// not answer yet.
+pub(super) async fn has_proposal(ctx: &Context, issue: &Issue) -> anyhow::Result<bool> {
+    let mut db = ctx.db.get().await;
+    let state: IssueData<'_, Option<Proposal>> =
+        IssueData::load(&mut db, issue, PROPOSAL_KEY).await?;
+    Ok(state.data.is_some())
+}
+
+/// Removes the pending proposal of `issue` once someone is assigned to it, so
+/// that neither its timeout nor a late answer replaces them.
+pub(super) async fn clear_proposal(db: &mut DbClient, issue: &Issue) -> anyhow::Result<()> {
+    let mut state: IssueData<'_, Option<Proposal>> =
+        IssueData::load(db, issue, PROPOSAL_KEY).await?;
+    if state.data.take().is_some() {
+        state.save().await?;
+    }
+    Ok(())
+}
+
+/// Removes the pending proposal of `issue` if it `matches`. Returns the
+/// removed proposal.
+async fn take_proposal(
+    ctx: &Context,
+    issue: &Issue,
+    matches: impl FnOnce(&Proposal) -> bool,
+) -> anyhow::Result<Option<Proposal>> {
+    let mut db = ctx.db.get().await;
+    let mut state: IssueData<'_, Option<Proposal>> =
+        IssueData::load(&mut db, issue, PROPOSAL_KEY).await?;
+    if !state.data.as_ref().is_some_and(matches) {
+        return Ok(None);
+    }
+    let proposal = state.data.take();
+    state.save().await?;
+    Ok(proposal)
+}
+
+/// Replaces the reviewer of a proposal that was not answered in time.
+pub(crate) struct ReviewProposalTimeoutJob;
+
+#[async_trait]
+impl Job for ReviewProposalTimeoutJob {
+    fn name(&self) -> &'static str {
+        PROPOSAL_TIMEOUT_JOB_NAME
+    }
+
+    async fn run(&self, ctx: &Context, metadata: &serde_json::Value) -> anyhow::Result<()> {
+        let timeout: ProposalTimeout = serde_json::from_value(metadata.clone())
+            .context("unable to deserialize the metadata in review proposal timeout job")?;
+
+        let repo = ctx.github.repository(&timeout.repo).await?;
+        // The base and head commits are needed to pick another reviewer from the diff.
+        let issue = repo.get_pr(&ctx.github, timeout.pr).await?;
+        // The proposal was answered, or replaced by a newer one.
+        if take_proposal(ctx, &issue, |proposal| proposal == &timeout.proposal)
+            .await?
+            .is_none()
+        {
+            return Ok(());
+        }
+        if !issue.is_open() {
+            return Ok(());
+        }
+
+        let config = crate::config::get(&ctx.github, &repo)
+            .await
+            .context("failed to get triagebot configuration")?;
+        let Some(config) = &config.assign else {
+            return Ok(());
+        };
+        log::info!(
+            "review proposal of {} to {} timed out",
+            issue.global_id(),
+            timeout.proposal.reviewer
+        );
+        issue
+            .post_comment(
+                &ctx.github,
+                &messages::review_proposal_timeout_message(&timeout.proposal.reviewer),
+            )
+            .await?;
+        pass(ctx, config, &issue, &timeout.proposal.reviewer).await
+    }
+}
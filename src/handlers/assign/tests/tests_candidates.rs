@@ -1,14 +1,20 @@
 //! Tests for `candidate_reviewers_from_names`
 
 use super::super::*;
-use crate::db::review_prefs::{RotationMode, upsert_review_prefs};
+use crate::db::review_prefs::{
+    OfficeHours, RotationMode, set_approver_only, set_away_status, set_blocked_authors,
+    set_office_hours, upsert_review_prefs,
+};
+use crate::db::reviewer_activity::record_reviewer_activity;
+use crate::db::reviewer_assignments::record_last_assigned;
+use crate::db::users::record_username;
 use crate::github::{PullRequestNumber, User};
 use crate::handlers::pr_tracking::{AssignedPullRequest, ReviewerWorkqueue};
 use crate::tests::github::{issue, user};
 use crate::tests::{TestContext, run_db_test};
 
 #[must_use]
-struct AssignCtx {
+pub(super) struct AssignCtx {
     test_ctx: TestContext,
     teams: Teams,
     config: AssignConfig,
@@ -29,7 +35,7 @@ impl AssignCtx {
         }
     }
 
-    fn teams(mut self, table: &toml::Table) -> Self {
+    pub(super) fn teams(mut self, table: &toml::Table) -> Self {
         let teams: serde_json::Value = table.clone().try_into().unwrap();
         let mut teams_config = serde_json::json!({});
         for (team_name, members) in teams.as_object().unwrap() {
@@ -49,13 +55,20 @@ impl AssignCtx {
         self
     }
 
-    fn assign_prs(mut self, user_id: UserId, count: u64) -> Self {
+    /// Makes `team` a subteam of `parent`, both must be set with [`Self::teams`].
+    fn subteam(mut self, team: &str, parent: &str) -> Self {
+        self.teams.teams.get_mut(team).unwrap().subteam_of = Some(parent.to_string());
+        self
+    }
+
+    pub(super) fn assign_prs(mut self, user_id: UserId, count: u64) -> Self {
         let prs = (0..count)
             .map(|pr_number| {
                 (
                     pr_number,
                     AssignedPullRequest {
                         title: format!("PR {pr_number}"),
+                        updated_at: chrono::Utc::now(),
                     },
                 )
             })
@@ -64,7 +77,20 @@ impl AssignCtx {
         self
     }
 
-    async fn set_review_prefs(
+    /// Assigns a PR to `user_id` that was last updated `days_ago` days ago.
+    fn assign_old_pr(mut self, user_id: UserId, days_ago: i64) -> Self {
+        let pr = AssignedPullRequest {
+            title: "Old PR".to_string(),
+            updated_at: chrono::Utc::now() - chrono::Duration::days(days_ago),
+        };
+        self.reviewer_workqueue
+            .entry(user_id)
+            .or_default()
+            .insert(1000, pr);
+        self
+    }
+
+    pub(super) async fn set_review_prefs(
         self,
         user: &User,
         capacity: Option<u32>,
@@ -81,6 +107,87 @@ impl AssignCtx {
         self
     }
 
+    /// Makes `user` known to the database, without review preferences.
+    async fn record_user(self, user: &User) -> Self {
+        record_username(self.test_ctx.db_client(), user.id, &user.login)
+            .await
+            .unwrap();
+        self
+    }
+
+    async fn set_away(self, user: &User, message: &str) -> Self {
+        set_away_status(self.test_ctx.db_client(), user.clone(), Some(message), None)
+            .await
+            .unwrap();
+        self
+    }
+
+    /// Sets office hours of `user` that do (or don't) include the current time.
+    async fn set_office_hours(self, user: &User, now_included: bool) -> Self {
+        use chrono::Timelike;
+        // Keep a margin of a few hours around now, so that the test can't be flaky.
+        let hour = chrono::Utc::now().hour();
+        let (start, end) = if now_included {
+            (hour + 23, hour + 2)
+        } else {
+            (hour + 3, hour + 20)
+        };
+        let office_hours = OfficeHours {
+            start: start % 24,
+            end: end % 24,
+            utc_offset: 0,
+        };
+        set_office_hours(self.test_ctx.db_client(), user.clone(), Some(office_hours))
+            .await
+            .unwrap();
+        self
+    }
+
+    async fn set_approver_only(self, user: &User) -> Self {
+        set_approver_only(self.test_ctx.db_client(), user.clone(), true)
+            .await
+            .unwrap();
+        self
+    }
+
+    async fn block_authors(self, user: &User, authors: &[&str]) -> Self {
+        let authors: Vec<String> = authors.iter().map(|a| a.to_string()).collect();
+        set_blocked_authors(self.test_ctx.db_client(), user.clone(), &authors)
+            .await
+            .unwrap();
+        self
+    }
+
+    async fn set_last_activity(self, user: &User, days_ago: i64) -> Self {
+        let time = chrono::Utc::now() - chrono::Duration::days(days_ago);
+        record_reviewer_activity(self.test_ctx.db_client(), user, time)
+            .await
+            .unwrap();
+        self
+    }
+
+    async fn set_last_assigned(self, user: &User, days_ago: i64) -> Self {
+        let time = chrono::Utc::now() - chrono::Duration::days(days_ago);
+        record_last_assigned(self.test_ctx.db_client(), &user.login, time)
+            .await
+            .unwrap();
+        self
+    }
+
+    /// Records that `reviewer` was assigned from the `strict-alternate`
+    /// rotation of `names`, like [`set_assignee`] does.
+    async fn assigned_from_rotation(self, names: &[&str], reviewer: &str) -> Self {
+        let names: Vec<String> = names.iter().map(|n| n.to_string()).collect();
+        record_last_reviewer(
+            self.test_ctx.db_client(),
+            &rotation_key(&self.issue, &names),
+            reviewer,
+        )
+        .await
+        .unwrap();
+        self
+    }
+
     async fn set_previous_reviewers(mut self, users: HashSet<&User>) -> Self {
         let mut db = self.test_ctx.db_client_mut();
         let mut state: IssueData<'_, Reviewers> =
@@ -94,7 +201,7 @@ impl AssignCtx {
         self
     }
 
-    async fn check(
+    pub(super) async fn check(
         mut self,
         names: &[&str],
         expected: Result<&[ReviewerSelection], FindReviewerError>,
@@ -102,12 +209,14 @@ impl AssignCtx {
         let names: Vec<_> = names.iter().map(|n| n.to_string()).collect();
 
         let workqueue = ReviewerWorkqueue::new(self.reviewer_workqueue.clone());
+        let bot_username = self.test_ctx.handler_ctx().username.clone();
         let reviewers = candidate_reviewers_from_names(
             self.test_ctx.db_client_mut(),
             Arc::new(RwLock::new(workqueue)),
             &self.teams,
             &self.config,
             &self.issue,
+            &bot_username,
             &names,
         )
         .await;
@@ -141,7 +250,7 @@ impl From<&str> for ReviewerSelection {
 }
 
 /// Basic test function for testing `candidate_reviewers_from_names`.
-fn basic_test(ctx: TestContext, config: toml::Table, issue: Issue) -> AssignCtx {
+pub(super) fn basic_test(ctx: TestContext, config: toml::Table, issue: Issue) -> AssignCtx {
     AssignCtx::new(ctx, config, issue)
 }
 
@@ -167,9 +276,30 @@ async fn no_assigned_prs() {
 async fn no_review_prefs() {
     run_db_test(|ctx| async move {
         ctx.add_user("martin", 1).await;
-        review_prefs_test(ctx)
+        let ctx = review_prefs_test(ctx)
             .assign_prs(1, 3)
             .check(&["martin"], Ok(&["martin".into()]))
+            .await?;
+
+        // With a default capacity, reviewers without review prefs can be at capacity.
+        let teams = toml::toml!(compiler = ["martin", "diana"]);
+        let config = toml::toml!(
+            default_capacity = 3
+            review_prefs = {}
+        );
+        ctx.test_ctx.add_user("diana", 2).await;
+        let ctx = basic_test(ctx.test_ctx, config, issue().call())
+            .teams(&teams)
+            .assign_prs(1, 3)
+            .assign_prs(2, 2)
+            .check(&["compiler"], Ok(&["diana".into()]))
+            .await?;
+
+        // Review prefs still take precedence over the default capacity.
+        let user = user("martin", 1);
+        ctx.set_review_prefs(&user, Some(5), RotationMode::OnRotation)
+            .await
+            .check(&["compiler"], Ok(&["diana".into(), "martin".into()]))
             .await
     })
     .await;
@@ -192,6 +322,7 @@ async fn at_max_capacity() {
                     suppressed_error: Some(FindReviewerError::ReviewerAtMaxCapacity {
                         username: "martin".to_string(),
                     }),
+                    rotation: None,
                 }]),
             )
             .await?
@@ -201,6 +332,57 @@ async fn at_max_capacity() {
     .await;
 }
 
+#[tokio::test]
+async fn group_ignoring_capacity() {
+    let teams = toml::toml!(compiler = ["martin", "diana"]);
+    let config = toml::toml!(
+        review_prefs = {}
+        [adhoc_groups]
+        reviewers = ["compiler"]
+        triage = { members = ["compiler"], ignore_capacity = true }
+    );
+    run_db_test(|ctx| async move {
+        let user = user("martin", 1);
+        basic_test(ctx, config, issue().call())
+            .teams(&teams)
+            .set_review_prefs(&user, Some(3), RotationMode::OnRotation)
+            .await
+            .assign_prs(user.id, 3)
+            .check(&["reviewers"], Ok(&["diana".into()]))
+            .await?
+            .check(&["triage"], Ok(&["diana".into(), "martin".into()]))
+            .await
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn whole_team_at_max_capacity() {
+    let teams = toml::toml!(compiler = ["martin", "diana", "jyn"]);
+    run_db_test(|ctx| async move {
+        let martin = user("martin", 1);
+        let diana = user("diana", 2);
+        // The PR author can never be assigned, so they don't matter.
+        let pr = issue().author(user("jyn", 3)).call();
+        basic_test(ctx, toml::toml!([review_prefs]), pr)
+            .teams(&teams)
+            .set_review_prefs(&martin, Some(3), RotationMode::OnRotation)
+            .await
+            .set_review_prefs(&diana, Some(1), RotationMode::OnRotation)
+            .await
+            .assign_prs(martin.id, 3)
+            .assign_prs(diana.id, 1)
+            .check(
+                &["compiler"],
+                Err(FindReviewerError::NoReviewerHasCapacity {
+                    initial: vec!["compiler".to_string()],
+                }),
+            )
+            .await
+    })
+    .await;
+}
+
 #[tokio::test]
 async fn below_max_capacity() {
     run_db_test(|ctx| async move {
@@ -232,6 +414,7 @@ async fn above_max_capacity() {
                     suppressed_error: Some(FindReviewerError::ReviewerAtMaxCapacity {
                         username: "martin".to_string(),
                     }),
+                    rotation: None,
                 }]),
             )
             .await?
@@ -258,6 +441,7 @@ async fn max_capacity_zero() {
                     suppressed_error: Some(FindReviewerError::ReviewerAtMaxCapacity {
                         username: "martin".to_string(),
                     }),
+                    rotation: None,
                 }]),
             )
             .await?
@@ -284,6 +468,7 @@ async fn ignore_username_case() {
                     suppressed_error: Some(FindReviewerError::ReviewerAtMaxCapacity {
                         username: "MARTIN".to_string(),
                     }),
+                    rotation: None,
                 }]),
             )
             .await?
@@ -323,6 +508,7 @@ async fn user_off_rotation() {
                     suppressed_error: Some(FindReviewerError::ReviewerOffRotation {
                         username: "martin".to_string(),
                     }),
+                    rotation: None,
                 }]),
             )
             .await?
@@ -332,6 +518,83 @@ async fn user_off_rotation() {
     .await;
 }
 
+#[tokio::test]
+async fn approver_only_user() {
+    run_db_test(|ctx| async move {
+        let teams = toml::toml!(compiler = ["martin", "diana"]);
+        let user = user("martin", 1);
+        review_prefs_test(ctx)
+            .teams(&teams)
+            .set_approver_only(&user)
+            .await
+            .check(&["compiler"], Ok(&["diana".into()]))
+            .await?
+            .check(&["martin"], Ok(&["martin".into()]))
+            .await
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn user_away() {
+    run_db_test(|ctx| async move {
+        let teams = toml::toml!(compiler = ["martin", "diana"]);
+        let user = user("martin", 1);
+        review_prefs_test(ctx)
+            .teams(&teams)
+            .set_away(&user, "back Monday")
+            .await
+            .check(
+                &["martin"],
+                Ok(&[ReviewerSelection {
+                    name: "martin".to_string(),
+                    suppressed_error: Some(FindReviewerError::ReviewerAway {
+                        username: "martin".to_string(),
+                        message: "back Monday".to_string(),
+                    }),
+                    rotation: None,
+                }]),
+            )
+            .await?
+            .check(&["compiler"], Ok(&["diana".into()]))
+            .await
+    })
+    .await;
+}
+
+#[test]
+fn multi_line_away_message() {
+    assert_eq!(
+        messages::reviewer_away_message("martin", "At a conference.\n\nBack Monday."),
+        "`martin` is away at the moment and left the following message:\n\n\
+         > At a conference.\n\
+         >\n\
+         > Back Monday."
+    );
+}
+
+#[tokio::test]
+async fn blocked_author() {
+    run_db_test(|ctx| async move {
+        let teams = toml::toml!(compiler = ["martin", "diana"]);
+        let user = user("martin", 2);
+        review_prefs_test(ctx)
+            .teams(&teams)
+            .block_authors(&user, &["Triagebot-Tester"])
+            .await
+            .check(
+                &["martin"],
+                Err(FindReviewerError::ReviewerUnavailable {
+                    username: "martin".to_string(),
+                }),
+            )
+            .await?
+            .check(&["compiler"], Ok(&["diana".into()]))
+            .await
+    })
+    .await;
+}
+
 #[tokio::test]
 async fn multiple_reviewers() {
     run_db_test(|ctx| async move {
@@ -354,6 +617,49 @@ async fn multiple_reviewers() {
     .await;
 }
 
+#[tokio::test]
+async fn least_assigned_counts_reviewers_without_prefs() {
+    run_db_test(|ctx| async move {
+        let users = &[user("martin", 1), user("jana", 2)];
+        let teams = toml::toml!(team = ["martin", "jana"]);
+        let config = toml::toml!(selection_mode = "least-assigned");
+        basic_test(ctx, config, issue().call())
+            .teams(&teams)
+            .record_user(&users[0])
+            .await
+            .record_user(&users[1])
+            .await
+            .assign_prs(users[0].id, 3)
+            .assign_prs(users[1].id, 1)
+            .check(&["team"], Ok(&["jana".into()]))
+            .await
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn load_balance_skips_reviewers_at_capacity() {
+    run_db_test(|ctx| async move {
+        let users = &[user("martin", 1), user("jana", 2)];
+        let teams = toml::toml!(team = ["martin", "jana"]);
+        let config = toml::toml!(
+            selection_mode = "load-balance"
+            review_prefs = {}
+        );
+        basic_test(ctx, config, issue().call())
+            .teams(&teams)
+            .set_review_prefs(&users[0], Some(3), RotationMode::OnRotation)
+            .await
+            .set_review_prefs(&users[1], Some(4), RotationMode::OnRotation)
+            .await
+            .assign_prs(users[0].id, 3)
+            .assign_prs(users[1].id, 3)
+            .check(&["team"], Ok(&["jana".into()]))
+            .await
+    })
+    .await;
+}
+
 #[tokio::test]
 async fn circular_groups() {
     // A cycle in the groups map.
@@ -376,6 +682,29 @@ async fn circular_groups() {
     .await;
 }
 
+#[tokio::test]
+async fn empty_team() {
+    let teams = toml::toml!(
+        compiler = []
+        libs = ["diana"]
+    );
+    run_db_test(|ctx| async move {
+        basic_test(ctx, toml::Table::new(), issue().call())
+            .teams(&teams)
+            .check(
+                &["compiler"],
+                Err(FindReviewerError::EmptyTeam {
+                    team: "compiler".to_string(),
+                }),
+            )
+            .await?
+            // Members of other requested teams are still used
+            .check(&["compiler", "libs"], Ok(&["diana".into()]))
+            .await
+    })
+    .await;
+}
+
 #[tokio::test]
 async fn nested_groups() {
     // Test choosing a reviewer from group with nested groups.
@@ -393,6 +722,20 @@ async fn nested_groups() {
     .await;
 }
 
+#[test]
+fn nested_groups_expansion_tree() {
+    let expansions = HashMap::from([
+        ("c", vec!["a", "b"]),
+        ("a", vec!["@pnkfelix"]),
+        ("b", vec!["@nrc", "c"]),
+    ]);
+    let mut rendered = HashSet::new();
+    assert_eq!(
+        format_expansion("c", &expansions, &mut rendered),
+        "c -> [a -> [@pnkfelix], b -> [@nrc, c]]"
+    );
+}
+
 #[tokio::test]
 async fn candidate_filtered_author_only_candidate() {
     // When the author is the only candidate.
@@ -432,6 +775,106 @@ async fn candidate_filtered_author() {
     .await;
 }
 
+#[tokio::test]
+async fn nested_subteams() {
+    let teams = toml::toml!(
+        compiler = ["c-user"]
+        types = ["t-user"]
+        "types-impl" = ["ti-user"]
+        lang = ["l-user"]
+    );
+    run_db_test(|ctx| async move {
+        let ctx = basic_test(ctx, toml::toml!(expand_subteams = true), issue().call())
+            .teams(&teams)
+            .subteam("types", "compiler")
+            .subteam("types-impl", "types")
+            .check(
+                &["compiler"],
+                Ok(&["c-user".into(), "t-user".into(), "ti-user".into()]),
+            )
+            .await?
+            .check(&["types"], Ok(&["t-user".into(), "ti-user".into()]))
+            .await?;
+        // Only direct members are picked by default.
+        basic_test(ctx.test_ctx, toml::Table::new(), issue().call())
+            .teams(&teams)
+            .subteam("types", "compiler")
+            .check(&["compiler"], Ok(&["c-user".into()]))
+            .await
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn subteam_cycle() {
+    let teams = toml::toml!(
+        compiler = ["c-user"]
+        types = ["t-user"]
+    );
+    run_db_test(|ctx| async move {
+        basic_test(ctx, toml::toml!(expand_subteams = true), issue().call())
+            .teams(&teams)
+            .subteam("types", "compiler")
+            .subteam("compiler", "types")
+            .check(&["compiler"], Ok(&["c-user".into(), "t-user".into()]))
+            .await
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn aliases() {
+    let teams = toml::toml!(
+        "libs-api" = ["martin", "diana"]
+        compiler = ["jana"]
+    );
+    let config = toml::toml!(
+        [aliases]
+        libs = "rust-lang/libs-api"
+        compiler = "rust-lang/compiler"
+        me = "@libs"
+        x = "y"
+        y = "x"
+    );
+    run_db_test(|ctx| async move {
+        basic_test(ctx, config, issue().call())
+            .teams(&teams)
+            .check(&["libs"], Ok(&["diana".into(), "martin".into()]))
+            .await?
+            .check(&["@rust-lang/me"], Ok(&["diana".into(), "martin".into()]))
+            .await?
+            // An alias of itself resolves to the team.
+            .check(&["compiler"], Ok(&["jana".into()]))
+            .await?
+            // A cycle ends at the first repeated alias, resolved as a username.
+            .check(&["x"], Ok(&["x".into()]))
+            .await
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn candidate_is_bot() {
+    // The bot cannot be requested, nor picked from a group.
+    let config = toml::toml!(
+        [adhoc_groups]
+        compiler = ["user1", "triagebot-test"]
+    );
+    run_db_test(|ctx| async move {
+        basic_test(ctx, config, issue().call())
+            .check(
+                &["@triagebot-test"],
+                Err(FindReviewerError::ReviewerIsBot {
+                    username: "triagebot-test".to_string(),
+                }),
+            )
+            .await?
+            .check(&["compiler"], Ok(&["user1".into()]))
+            .await
+    })
+    .await;
+}
+
 #[tokio::test]
 async fn candidate_filtered_assignee() {
     // Filter out an existing assignee from the candidates.
@@ -561,6 +1004,7 @@ async fn users_on_vacation() {
                     suppressed_error: Some(FindReviewerError::ReviewerOffRotation {
                         username: "jyn514".to_string(),
                     }),
+                    rotation: None,
                 }]),
             )
             .await?
@@ -571,6 +1015,26 @@ async fn users_on_vacation() {
     .await;
 }
 
+#[tokio::test]
+async fn whole_team_on_vacation() {
+    let teams = toml::toml!(bootstrap = ["jyn514", "Mark-Simulacrum"]);
+    let config = toml::toml!(users_on_vacation = ["jyn514", "Mark-Simulacrum"]);
+
+    run_db_test(|ctx| async move {
+        basic_test(ctx, config, issue().call())
+            .teams(&teams)
+            .check(
+                &["bootstrap"],
+                Err(FindReviewerError::NoReviewerOnRotation {
+                    initial: vec!["bootstrap".to_string()],
+                    off_rotation: vec!["Mark-Simulacrum".to_string(), "jyn514".to_string()],
+                }),
+            )
+            .await
+    })
+    .await;
+}
+
 #[tokio::test]
 async fn previous_reviewers_ignore_in_team_success() {
     let teams = toml::toml!(compiler = ["martin", "jyn514"]);
@@ -625,3 +1089,262 @@ async fn previous_reviewers_direct_assignee() {
     })
     .await
 }
+
+#[tokio::test]
+async fn least_recently_active() {
+    let teams = toml::toml!(compiler = ["martin", "jana", "diana"]);
+    let config = toml::toml!(selection_mode = "least-recently-active");
+    run_db_test(|ctx| async move {
+        let martin = user("martin", 1);
+        let jana = user("jana", 2);
+        let diana = user("diana", 3);
+        basic_test(ctx, config, issue().call())
+            .teams(&teams)
+            .set_last_activity(&martin, 1)
+            .await
+            .set_last_activity(&jana, 5)
+            .await
+            .check(&["compiler"], Ok(&["diana".into()]))
+            .await?
+            .set_last_activity(&diana, 0)
+            .await
+            .check(&["compiler"], Ok(&["jana".into()]))
+            .await
+    })
+    .await
+}
+
+#[tokio::test]
+async fn round_robin() {
+    let teams = toml::toml!(compiler = ["martin", "jana", "diana"]);
+    let config = toml::toml!(selection_mode = "round-robin");
+    run_db_test(|ctx| async move {
+        let martin = user("martin", 1);
+        let jana = user("jana", 2);
+        let diana = user("diana", 3);
+        basic_test(ctx, config, issue().call())
+            .teams(&teams)
+            .set_last_assigned(&martin, 1)
+            .await
+            // Reviewers that were never assigned are all picked from.
+            .check(&["compiler"], Ok(&["diana".into(), "jana".into()]))
+            .await?
+            .set_last_assigned(&jana, 5)
+            .await
+            .set_last_assigned(&diana, 3)
+            .await
+            .check(&["compiler"], Ok(&["jana".into()]))
+            .await
+    })
+    .await
+}
+
+#[tokio::test]
+async fn strict_alternate() {
+    let teams = toml::toml!(compiler = ["martin", "diana"]);
+    let config = toml::toml!(
+        selection_mode = "strict-alternate"
+        review_prefs = {}
+    );
+    run_db_test(|ctx| async move {
+        let martin = user("martin", 1);
+        let issue = issue().call();
+        let rotation = rotation_key(&issue, &["compiler".to_string()]);
+        let alternate = |name: &str| ReviewerSelection {
+            rotation: Some(rotation.clone()),
+            ..name.into()
+        };
+        basic_test(ctx, config, issue)
+            .teams(&teams)
+            // The review capacity is ignored.
+            .set_review_prefs(&martin, Some(0), RotationMode::OnRotation)
+            .await
+            .check(&["compiler"], Ok(&[alternate("diana")]))
+            .await?
+            // The rotation only moves on once the reviewer is assigned.
+            .check(&["compiler"], Ok(&[alternate("diana")]))
+            .await?
+            .assigned_from_rotation(&["compiler"], "diana")
+            .await
+            .check(&["compiler"], Ok(&[alternate("martin")]))
+            .await?
+            .assigned_from_rotation(&["compiler"], "martin")
+            .await
+            .check(&["compiler"], Ok(&[alternate("diana")]))
+            .await?
+            // Reviewers off rotation are skipped.
+            .assigned_from_rotation(&["compiler"], "diana")
+            .await
+            .set_review_prefs(&martin, None, RotationMode::OffRotation)
+            .await
+            .check(&["compiler"], Ok(&[alternate("diana")]))
+            .await
+    })
+    .await
+}
+
+#[tokio::test]
+async fn rotation_selection_mode() {
+    let teams = toml::toml!(compiler = ["martin", "jana", "diana"]);
+    let config = toml::toml!(
+        review_prefs = {}
+        rotations = { fast = { reviewers = ["compiler"], selection_mode = "least-assigned" } }
+    );
+    run_db_test(|ctx| async move {
+        let martin = user("martin", 1);
+        let jana = user("jana", 2);
+        let diana = user("diana", 3);
+        basic_test(ctx, config, issue().call())
+            .teams(&teams)
+            .set_review_prefs(&martin, None, RotationMode::OnRotation)
+            .await
+            .set_review_prefs(&jana, None, RotationMode::OnRotation)
+            .await
+            .set_review_prefs(&diana, None, RotationMode::OnRotation)
+            .await
+            .assign_prs(martin.id, 3)
+            .assign_prs(jana.id, 1)
+            .assign_prs(diana.id, 2)
+            .check(&["rotation:fast"], Ok(&["jana".into()]))
+            .await?
+            // The rest of the repository keeps the default selection mode.
+            .check(
+                &["compiler"],
+                Ok(&["diana".into(), "jana".into(), "martin".into()]),
+            )
+            .await?
+            .check(
+                &["rotation:slow"],
+                Err(FindReviewerError::TeamNotFound("rotation:slow".to_string())),
+            )
+            .await
+    })
+    .await
+}
+
+#[tokio::test]
+async fn office_hours() {
+    let teams = toml::toml!(compiler = ["martin", "jana", "diana"]);
+    let config = toml::toml!(review_prefs = {});
+    run_db_test(|ctx| async move {
+        let martin = user("martin", 1);
+        let jana = user("jana", 2);
+        let diana = user("diana", 3);
+        basic_test(ctx, config, issue().call())
+            .teams(&teams)
+            .set_office_hours(&martin, false)
+            .await
+            .set_office_hours(&jana, true)
+            .await
+            .check(&["compiler"], Ok(&["diana".into(), "jana".into()]))
+            .await?
+            // Reviewers outside of their office hours are still picked if nobody else is available.
+            .set_office_hours(&jana, false)
+            .await
+            .set_office_hours(&diana, false)
+            .await
+            .check(
+                &["compiler"],
+                Ok(&["diana".into(), "jana".into(), "martin".into()]),
+            )
+            .await
+    })
+    .await
+}
+
+#[tokio::test]
+async fn stale_reviews() {
+    let teams = toml::toml!(compiler = ["martin", "diana"]);
+    let config = toml::toml!(
+        stale_review_days = 14
+        review_prefs = {}
+    );
+    run_db_test(|ctx| async move {
+        let martin = user("martin", 1);
+        basic_test(ctx, config, issue().call())
+            .teams(&teams)
+            .set_review_prefs(&martin, None, RotationMode::OnRotation)
+            .await
+            .assign_old_pr(martin.id, 10)
+            .check(&["compiler"], Ok(&["diana".into(), "martin".into()]))
+            .await?
+            .assign_old_pr(martin.id, 30)
+            .check(&["compiler"], Ok(&["diana".into()]))
+            .await?
+            .check(
+                &["martin"],
+                Ok(&[ReviewerSelection {
+                    name: "martin".to_string(),
+                    suppressed_error: Some(FindReviewerError::ReviewerHasStaleReviews {
+                        username: "martin".to_string(),
+                    }),
+                    rotation: None,
+                }]),
+            )
+            .await
+    })
+    .await
+}
+
+#[test]
+fn weighted_choice() {
+    use rand::SeedableRng;
+
+    let config: AssignConfig = toml::toml!(
+        [reviewer_weights]
+        alice = 3
+        Bob = 1
+        dave = 0
+    )
+    .try_into()
+    .unwrap();
+    let candidates = || -> HashSet<_> {
+        ["alice", "bob", "carol", "dave"]
+            .into_iter()
+            .map(|name| ReviewerSelection::from_name(name.to_string()))
+            .collect()
+    };
+
+    let mut rng = rand::rngs::StdRng::seed_from_u64(0);
+    let mut counts: HashMap<String, u32> = HashMap::new();
+    for _ in 0..10_000 {
+        let reviewer = choose_reviewer(&config, candidates(), &mut rng);
+        *counts.entry(reviewer.name).or_default() += 1;
+    }
+    // Expected: 60% alice, 20% bob, 20% carol (no weight) and never dave.
+    for (name, expected) in [("alice", 6000), ("bob", 2000), ("carol", 2000)] {
+        let count = counts[name];
+        assert!(
+            count.abs_diff(expected) < 300,
+            "{name} was picked {count} times"
+        );
+    }
+    assert!(!counts.contains_key("dave"));
+}
+
+#[test]
+fn load_balanced_choice() {
+    use rand::SeedableRng;
+
+    let assigned_pr_counts: HashMap<String, u64> =
+        HashMap::from([("martin".to_string(), 3), ("jana".to_string(), 1)]);
+    let mut rng = rand::rngs::StdRng::seed_from_u64(0);
+    let mut counts: HashMap<&str, u32> = HashMap::new();
+    for _ in 0..10_000 {
+        let candidates = HashSet::from(["martin", "jana", "mark"]);
+        let chosen: Vec<&str> = load_balance(candidates, &assigned_pr_counts, &mut rng)
+            .into_iter()
+            .collect();
+        assert_eq!(chosen.len(), 1);
+        *counts.entry(chosen[0]).or_default() += 1;
+    }
+    // Weights are 1/4 for martin, 1/2 for jana and 1 for mark (no assigned PRs),
+    // so they are expected to be picked 1/7, 2/7 and 4/7 of the time.
+    for (name, expected) in [("martin", 1429), ("jana", 2857), ("mark", 5714)] {
+        let count = counts[name];
+        assert!(
+            count.abs_diff(expected) < 300,
+            "{name} was picked {count} times"
+        );
+    }
+}
@@ -1,12 +1,20 @@
 //! Tests for `find_reviewers_from_diff`
 
+use super::super::owners_files::OwnersFilesCache;
 use super::super::*;
+use super::tests_candidates::basic_test;
+use crate::db::review_prefs::RotationMode;
+use crate::github::{CommitBase, GithubClient};
+use crate::tests::github::{issue, user};
+use crate::tests::mock_github::MockGithub;
+use crate::tests::run_db_test;
 use std::fmt::Write;
+use std::sync::Mutex;
 
 fn test_from_diff(diff: &Vec<FileDiff>, config: toml::Table, expected: &[&str]) {
     let aconfig: AssignConfig = config.try_into().unwrap();
     assert_eq!(
-        find_reviewers_from_diff(&aconfig, &*diff).unwrap(),
+        find_reviewers_from_diff(&aconfig, &OwnersFiles::default(), &*diff).unwrap(),
         expected.iter().map(|x| x.to_string()).collect::<Vec<_>>()
     );
 }
@@ -33,6 +41,7 @@ fn make_fake_diff(paths: &[(&str, u32, u32)]) -> Vec<FileDiff> {
             FileDiff {
                 filename: path.to_string(),
                 patch: diff,
+                changes: added + removed,
             }
         })
         .collect()
@@ -63,6 +72,7 @@ fn from_diff_submodule() {
             +Subproject commit b001609960ca33047e5cbc5a231c1e24b6041d4b\n\
         "
         .to_string(),
+        changes: 2,
     }];
     test_from_diff(&diff, config, &["user1", "user2"]);
 }
@@ -130,10 +140,36 @@ fn empty_file_still_counts() {
         patch: "new file mode 100644\n\
                 index 0000000..e69de29\n"
             .to_string(),
+        changes: 0,
     }];
     test_from_diff(&diff, config, &["parser"]);
 }
 
+#[test]
+fn files_without_patch_still_count() {
+    let config = toml::toml!(
+        [owners]
+        "/compiler" = ["compiler"]
+        "/library" = ["libs"]
+    );
+    // GitHub omits the patch of large deleted files.
+    let mut diff = vec![FileDiff {
+        filename: "compiler/rustc_codegen_gcc/src/huge.rs".to_string(),
+        patch: String::new(),
+        changes: 200,
+    }];
+    diff.extend(make_fake_diff(&[("library/core/src/lib.rs", 10, 0)]));
+    test_from_diff(&diff, config, &["compiler"]);
+
+    // Binary files count as a file touch.
+    let diff = vec![FileDiff {
+        filename: "library/core/src/logo.png".to_string(),
+        patch: String::new(),
+        changes: 0,
+    }];
+    test_from_diff(&diff, config, &["libs"]);
+}
+
 #[test]
 fn basic_gitignore_pattern() {
     let config = toml::toml!(
@@ -145,9 +181,382 @@ fn basic_gitignore_pattern() {
     test_from_diff(&diff, config, &["javascript-reviewers"]);
 }
 
+#[test]
+fn extension_and_path_patterns() {
+    // Extension rules are matched independently of the longest path pattern.
+    let config = toml::toml!(
+        [owners]
+        "/compiler" = ["compiler"]
+        "/compiler/rustc_parse" = ["parser"]
+        [extension_owners]
+        "md" = ["docs"]
+    );
+    let aconfig: AssignConfig = config.try_into().unwrap();
+    let find = |diff: &[FileDiff]| {
+        find_reviewers_from_diff(&aconfig, &OwnersFiles::default(), diff).unwrap()
+    };
+
+    // 12 changes for `/compiler/rustc_parse` and 22 for both `/compiler` and `md`.
+    let diff = make_fake_diff(&[
+        ("compiler/rustc_parse/src/lib.rs", 11, 0),
+        ("compiler/README.md", 21, 0),
+    ]);
+    assert_eq!(find(&diff), ["compiler", "docs"]);
+    // The counts of both kinds of patterns add up: 17 for `/compiler/rustc_parse`
+    // and 11 for `/compiler`, but 22 for `md`.
+    let diff = make_fake_diff(&[
+        ("compiler/rustc_parse/src/lib.rs", 5, 0),
+        ("compiler/rustc_parse/README.md", 10, 0),
+        ("compiler/README.md", 10, 0),
+    ]);
+    assert_eq!(find(&diff), ["docs"]);
+    let owners = owner_patterns_from_diff(&aconfig, &OwnersFiles::default(), &diff).unwrap();
+    let patterns: Vec<_> = owners.iter().map(|(pattern, _)| *pattern).collect();
+    assert_eq!(patterns, ["/compiler", "/compiler/rustc_parse", "md"]);
+}
+
 #[test]
 fn empty_owners_table() {
     let config = toml::toml!([owners]);
     let diff = make_fake_diff(&[("src.js", 10, 1)]);
     test_from_diff(&diff, config, &[]);
 }
+
+#[test]
+fn owner_patterns_all_areas() {
+    // Unlike `find_reviewers_from_diff`, every matched area is reported.
+    let config = toml::toml!(
+        [owners]
+        "/compiler" = ["compiler"]
+        "/compiler/rustc_parse" = ["parser"]
+        "/library" = ["libs", "@octocat"]
+        "/src/tools" = ["tools"]
+    );
+    let aconfig: AssignConfig = config.try_into().unwrap();
+    let diff = make_fake_diff(&[
+        ("compiler/rustc_parse/src/lib.rs", 1, 0),
+        ("library/core/src/lib.rs", 50, 10),
+    ]);
+    let owners = owner_patterns_from_diff(&aconfig, &OwnersFiles::default(), &diff).unwrap();
+    assert_eq!(
+        owners,
+        vec![
+            ("/compiler/rustc_parse", &["parser".to_string()][..]),
+            (
+                "/library",
+                &["libs".to_string(), "@octocat".to_string()][..]
+            ),
+        ]
+    );
+}
+
+#[test]
+fn excluded_owners() {
+    // Excluded owners fall back to the other matched areas.
+    let config = toml::toml!(
+        [owners]
+        "/compiler" = ["compiler"]
+        "/library" = ["libs"]
+        "/src/tools" = ["tools", "@octocat"]
+    );
+    let aconfig: AssignConfig = config.try_into().unwrap();
+    let diff = make_fake_diff(&[
+        ("compiler/rustc_parse/src/lib.rs", 50, 10),
+        ("library/core/src/lib.rs", 5, 0),
+        ("src/tools/miri/src/lib.rs", 5, 0),
+    ]);
+    assert_eq!(
+        find_reviewers_from_diff_excluding(&aconfig, &OwnersFiles::default(), &diff, |owner| owner
+            == "compiler")
+        .unwrap(),
+        ["@octocat", "libs", "tools"]
+    );
+    assert_eq!(
+        find_reviewers_from_diff_excluding(&aconfig, &OwnersFiles::default(), &diff, |owner| owner
+            == "tools")
+        .unwrap(),
+        ["compiler"]
+    );
+}
+
+#[test]
+fn unassigned_owners() {
+    // Paths owned by `ghost` or nobody suppress assignment when they dominate the diff.
+    let config = toml::toml!(
+        [owners]
+        "/compiler" = ["compiler"]
+        "/src/gen" = ["ghost"]
+        "/vendor" = []
+    );
+    let diff = make_fake_diff(&[
+        ("compiler/rustc_parse/src/lib.rs", 5, 0),
+        ("vendor/foo/lib.rs", 50, 0),
+    ]);
+    test_from_diff(&diff, config.clone(), &["ghost"]);
+    let diff = make_fake_diff(&[
+        ("compiler/rustc_parse/src/lib.rs", 5, 0),
+        ("src/gen/out.rs", 50, 0),
+    ]);
+    test_from_diff(&diff, config.clone(), &["ghost"]);
+    // Otherwise, they don't affect the choice of reviewers.
+    let diff = make_fake_diff(&[
+        ("compiler/rustc_parse/src/lib.rs", 50, 0),
+        ("vendor/foo/lib.rs", 5, 0),
+    ]);
+    test_from_diff(&diff, config, &["compiler"]);
+}
+
+#[test]
+fn extension_owners() {
+    // Extension rules also apply to files that don't match any path pattern.
+    let config = toml::toml!(
+        [owners]
+        "/compiler" = ["compiler"]
+        [extension_owners]
+        "md" = ["docs"]
+        ".toml" = ["infra"]
+    );
+    let diff = make_fake_diff(&[
+        ("README.md", 20, 0),
+        ("src/doc/guide.md", 20, 0),
+        ("compiler/rustc_parse/src/lib.rs", 30, 0),
+    ]);
+    test_from_diff(&diff, config.clone(), &["docs"]);
+    let diff = make_fake_diff(&[("Cargo.toml", 1, 0), ("README.md", 1, 1)]);
+    test_from_diff(&diff, config.clone(), &["docs"]);
+    // The changes of a file count for both its path and its extension.
+    let diff = make_fake_diff(&[
+        ("compiler/rustc_parse/src/lib.rs", 30, 0),
+        ("compiler/rustc_parse/README.md", 10, 0),
+    ]);
+    test_from_diff(&diff, config, &["compiler"]);
+}
+
+#[test]
+fn multi_area() {
+    let config: AssignConfig = toml::toml!(
+        [owners]
+        "/compiler" = ["compiler"]
+        "/library" = ["libs"]
+        "/src/doc" = ["docs"]
+    )
+    .try_into()
+    .unwrap();
+    let diff = make_fake_diff(&[
+        ("compiler/rustc_parse/src/lib.rs", 40, 0),
+        ("library/core/src/lib.rs", 35, 0),
+        ("src/doc/guide.md", 25, 0),
+    ]);
+    let areas = multi_area_owners(&config, &OwnersFiles::default(), &diff, 60)
+        .unwrap()
+        .unwrap();
+    let patterns: Vec<_> = areas.iter().map(|(pattern, _)| *pattern).collect();
+    assert_eq!(patterns, ["/compiler", "/library", "/src/doc"]);
+    // A dominant area is enough to pick a reviewer.
+    assert!(
+        multi_area_owners(&config, &OwnersFiles::default(), &diff, 40)
+            .unwrap()
+            .is_none()
+    );
+    let diff = make_fake_diff(&[("compiler/rustc_parse/src/lib.rs", 40, 0)]);
+    assert!(
+        multi_area_owners(&config, &OwnersFiles::default(), &diff, 60)
+            .unwrap()
+            .is_none()
+    );
+}
+
+#[test]
+fn owners_files() {
+    let config: AssignConfig = toml::toml!(
+        owners_file = "OWNERS"
+        [owners]
+        "/compiler" = ["compiler"]
+        "/compiler/rustc_parse/src" = ["parser"]
+    )
+    .try_into()
+    .unwrap();
+    let mut owners_files = OwnersFiles::new("OWNERS");
+    owners_files.insert(
+        "compiler/rustc_parse/OWNERS".to_string(),
+        Some("# Parser reviewers\n@martin\n\ndiana # lead\n"),
+    );
+    owners_files.insert("compiler/rustc_parse/src/OWNERS".to_string(), None);
+    owners_files.insert("compiler/OWNERS".to_string(), None);
+    owners_files.insert("OWNERS".to_string(), None);
+
+    // The nearest owners file overrides the `owners` table for its whole subtree.
+    let diff = make_fake_diff(&[("compiler/rustc_parse/src/lib.rs", 10, 0)]);
+    assert_eq!(
+        find_reviewers_from_diff(&config, &owners_files, &diff).unwrap(),
+        ["@martin", "diana"]
+    );
+    // Files without an owners file still use the `owners` table.
+    let diff = make_fake_diff(&[("compiler/rustc_lexer/src/lib.rs", 10, 0)]);
+    assert_eq!(
+        find_reviewers_from_diff(&config, &owners_files, &diff).unwrap(),
+        ["compiler"]
+    );
+}
+
+#[test]
+fn codeowners() {
+    let config: AssignConfig = toml::toml!(
+        use_codeowners = true
+        [owners]
+        "/compiler" = ["compiler"]
+    )
+    .try_into()
+    .unwrap();
+    let mut owners_files = OwnersFiles::default();
+    owners_files.set_codeowners(
+        "# Default owners\n\
+         /compiler @rust-lang/wg-compiler\n\
+         /compiler/rustc_parse @martin @rust-lang/parser # the parser\n\
+         /library/core docs@example.com\n\
+         *.md @diana\n",
+    );
+
+    // The longest pattern wins, and `@org/team` owners keep their organization.
+    let diff = make_fake_diff(&[("compiler/rustc_parse/src/lib.rs", 10, 0)]);
+    assert_eq!(
+        find_reviewers_from_diff(&config, &owners_files, &diff).unwrap(),
+        ["martin", "rust-lang/parser"]
+    );
+    // The `owners` table takes precedence over the same CODEOWNERS pattern.
+    let diff = make_fake_diff(&[("compiler/rustc_lexer/src/lib.rs", 10, 0)]);
+    assert_eq!(
+        find_reviewers_from_diff(&config, &owners_files, &diff).unwrap(),
+        ["compiler"]
+    );
+    // Email owners are ignored.
+    let diff = make_fake_diff(&[("library/core/src/lib.rs", 10, 0)]);
+    assert!(
+        find_reviewers_from_diff(&config, &owners_files, &diff)
+            .unwrap()
+            .is_empty()
+    );
+    let diff = make_fake_diff(&[("README.md", 10, 0)]);
+    assert_eq!(
+        find_reviewers_from_diff(&config, &owners_files, &diff).unwrap(),
+        ["diana"]
+    );
+}
+
+#[test]
+fn diff_size() {
+    let file_diff = |filename: &str, patch: &str| FileDiff {
+        filename: filename.to_string(),
+        patch: patch.to_string(),
+        changes: 0,
+    };
+    let diff = vec![
+        file_diff("src/lib.rs", "@@ -1,2 +1,3 @@\n line\n-old\n+new\n+newer\n"),
+        file_diff("README.md", "@@ -1 +1 @@\n+docs\n"),
+    ];
+    assert_eq!(diff_size_message(&diff), "(+3/-1 across 2 files)");
+    assert_eq!(diff_size_message(&diff[1..]), "(+1/-0 in 1 file)");
+
+    let large_patch = "+line\n".repeat(LARGE_DIFF_LINES as usize);
+    let diff = vec![file_diff("src/lib.rs", &large_patch)];
+    assert_eq!(diff_size_message(&diff), "(large diff: 5000+ lines)");
+}
+
+#[test]
+fn max_diff_lines() {
+    let diff = make_fake_diff(&[
+        ("compiler/rustc_parse/src/lib.rs", 50, 0),
+        ("library/core/src/lib.rs", 1, 0),
+        ("library/std/src/lib.rs", 1, 0),
+    ]);
+    let config = toml::toml!(
+        [owners]
+        "/compiler" = ["compiler"]
+        "/library" = ["libs"]
+    );
+    test_from_diff(&diff, config, &["compiler"]);
+
+    // Above the limit, only the number of modified files matters.
+    let config = toml::toml!(
+        max_diff_lines = 20
+        [owners]
+        "/compiler" = ["compiler"]
+        "/library" = ["libs"]
+    );
+    test_from_diff(&diff, config, &["libs"]);
+}
+
+#[tokio::test]
+async fn team_owners() {
+    // Teams, optionally prefixed with the organization, are expanded into
+    // their members, and only then is the capacity of each member checked.
+    let config = toml::toml!(
+        review_prefs = {}
+        [owners]
+        "/compiler/rustc_llvm" = ["rust-lang/wg-llvm"]
+    );
+    let teams = toml::toml!("wg-llvm" = ["martin", "diana"]);
+    let diff = make_fake_diff(&[("compiler/rustc_llvm/src/lib.rs", 5, 0)]);
+    test_from_diff(&diff, config.clone(), &["rust-lang/wg-llvm"]);
+
+    run_db_test(|ctx| async move {
+        let user = user("martin", 1);
+        basic_test(ctx, config, issue().call())
+            .teams(&teams)
+            .set_review_prefs(&user, Some(1), RotationMode::OnRotation)
+            .await
+            .assign_prs(user.id, 1)
+            .check(&["rust-lang/wg-llvm"], Ok(&["diana".into()]))
+            .await
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn owners_files_are_looked_up_once() {
+    let mock = MockGithub::start().await;
+    let client = GithubClient::new(
+        "gh-test-fake-token".to_string(),
+        mock.url().to_string(),
+        format!("{}/graphql", mock.url()),
+        mock.url().to_string(),
+    );
+    let commit = |sha: &str| CommitBase {
+        sha: sha.to_string(),
+        git_ref: "master".to_string(),
+        repo: None,
+    };
+    let pr = Issue {
+        base: Some(commit("base")),
+        head: Some(commit("head")),
+        ..issue().call()
+    };
+    let diff = make_fake_diff(&[
+        ("compiler/rustc_parse/src/lib.rs", 10, 0),
+        ("compiler/rustc_parse/src/parser.rs", 10, 0),
+        ("compiler/rustc_lexer/lib.rs", 10, 0),
+    ]);
+    let cache = Mutex::new(OwnersFilesCache::default());
+
+    OwnersFiles::load(&client, &cache, &pr, "OWNERS", &diff)
+        .await
+        .unwrap();
+    let mut paths: Vec<String> = mock.requests().into_iter().map(|req| req.path).collect();
+    paths.sort();
+    assert_eq!(
+        paths,
+        [
+            "/rust-lang/rust/base/OWNERS",
+            "/rust-lang/rust/base/compiler/OWNERS",
+            "/rust-lang/rust/base/compiler/rustc_lexer/OWNERS",
+            "/rust-lang/rust/base/compiler/rustc_parse/OWNERS",
+            "/rust-lang/rust/base/compiler/rustc_parse/src/OWNERS",
+        ]
+    );
+
+    // The owners files are cached for the head commit of the PR.
+    OwnersFiles::load(&client, &cache, &pr, "OWNERS", &diff)
+        .await
+        .unwrap();
+    assert_eq!(mock.requests().len(), 5);
+}
@@ -0,0 +1,2271 @@
+//! Tests for `handle_input` and `handle_command`
+
+use super::super::*;
+use crate::db::assignment_events::get_recent_assignment_events;
+use crate::db::review_prefs::{RotationMode, upsert_review_prefs};
+use crate::github::{
+    Comment, CommitBase, IssueCommentAction, IssueCommentEvent, Label, Repository, User,
+};
+use crate::handlers::pr_tracking::AssignedPullRequest;
+use crate::tests::github::{default_test_user, issue, pull_request, user};
+use crate::tests::mock_github::MockGithub;
+use crate::tests::{TestContext, run_db_test};
+use axum::http::Method;
+
+fn repository() -> Repository {
+    Repository {
+        full_name: "rust-lang/rust".to_string(),
+        default_branch: "master".to_string(),
+        fork: false,
+        parent: None,
+    }
+}
+
+fn opened_pr_event(issue: Issue) -> IssuesEvent {
+    IssuesEvent {
+        action: IssuesAction::Opened,
+        issue,
+        changes: None,
+        repository: repository(),
+        sender: default_test_user(),
+    }
+}
+
+fn comment_event(issue: Issue, author: User, body: &str) -> Event {
+    Event::IssueComment(IssueCommentEvent {
+        action: IssueCommentAction::Created,
+        changes: None,
+        comment: Comment {
+            id: 1,
+            node_id: "IC_test".to_string(),
+            body: body.to_string(),
+            html_url: format!("{}#issuecomment-1", issue.html_url),
+            user: author,
+            created_at: None,
+            updated_at: None,
+            pr_review_state: None,
+            author_association: AuthorAssociation::None,
+        },
+        issue,
+        repository: repository(),
+    })
+}
+
+/// Makes the PR #1 of jyn available from the pulls API, as needed by the
+/// commands that use its diff.
+fn respond_pr(mock: &MockGithub, draft: bool) {
+    mock.respond(
+        Method::GET,
+        "/repos/rust-lang/rust/pulls/1",
+        serde_json::json!({
+            "number": 1,
+            "body": "",
+            "created_at": "2025-01-01T00:00:00Z",
+            "updated_at": "2025-01-01T00:00:00Z",
+            "title": "PR #1",
+            "html_url": "https://github.com/rust-lang/rust/pull/1",
+            "user": { "login": "jyn", "id": 3 },
+            "labels": [],
+            "assignees": [],
+            "comments_url": "https://api.github.com/repos/rust-lang/rust/issues/1/comments",
+            "state": "open",
+            "draft": draft,
+            "author_association": "NONE",
+            "base": { "sha": "base", "ref": "master" },
+            "head": { "sha": "head", "ref": "feature" },
+        }),
+    );
+}
+
+/// Gives `pr` a diff modifying `files`, served by `mock`.
+fn set_diff(mock: &MockGithub, pr: &mut Issue, files: &[&str]) {
+    let commit = |sha: &str| CommitBase {
+        sha: sha.to_string(),
+        git_ref: "master".to_string(),
+        repo: None,
+    };
+    pr.base = Some(commit("base"));
+    pr.head = Some(commit("head"));
+
+    let github_commit = serde_json::json!({
+        "sha": "base",
+        "commit": {
+            "author": { "date": chrono::Utc::now().to_rfc3339() },
+            "message": "base commit",
+            "tree": { "sha": "tree" },
+        },
+        "parents": [],
+        "html_url": "https://github.com/rust-lang/rust/commit/base",
+    });
+    let files: Vec<_> = files
+        .iter()
+        .map(|file| serde_json::json!({ "filename": file, "patch": "+added line\n" }))
+        .collect();
+    mock.respond(
+        Method::GET,
+        "/repos/rust-lang/rust/compare/base...head",
+        serde_json::json!({
+            "base_commit": github_commit,
+            "merge_base_commit": github_commit,
+            "files": files,
+        }),
+    );
+}
+
+#[tokio::test]
+async fn missing_diff_is_not_an_error() {
+    // The test PR has no base/head commits, so its diff cannot be determined.
+    let config: AssignConfig = toml::toml!(
+        [owners]
+        "/compiler" = ["compiler"]
+    )
+    .try_into()
+    .unwrap();
+    run_db_test(|ctx| async move {
+        let event = opened_pr_event(pull_request().call());
+        handle_input(
+            ctx.handler_ctx(),
+            &config,
+            &event,
+            AssignInput::Opened { draft: false },
+        )
+        .await?;
+        Ok(ctx)
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn disabled_assign_ignores_opened_pr() {
+    run_db_test(|ctx| async move {
+        let event = opened_pr_event(pull_request().call());
+        let enabled: AssignConfig = toml::toml!(enabled_repos = ["rust-lang/rust"])
+            .try_into()
+            .unwrap();
+        assert!(
+            parse_input(ctx.handler_ctx(), &event, Some(&enabled))
+                .await
+                .unwrap()
+                .is_some()
+        );
+        for config in [
+            toml::toml!(enabled = false),
+            toml::toml!(enabled_repos = ["rust-lang/miri"]),
+        ] {
+            let config: AssignConfig = config.try_into().unwrap();
+            assert!(
+                parse_input(ctx.handler_ctx(), &event, Some(&config))
+                    .await
+                    .unwrap()
+                    .is_none()
+            );
+        }
+        Ok(ctx)
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn trigger_on_actions() {
+    run_db_test(|ctx| async move {
+        let event = |action| IssuesEvent {
+            action,
+            ..opened_pr_event(pull_request().call())
+        };
+        let triggers = async |config: &AssignConfig, action| {
+            parse_input(ctx.handler_ctx(), &event(action), Some(config))
+                .await
+                .unwrap()
+                .is_some()
+        };
+
+        // By default, opened and ready for review PRs are assigned.
+        let config: AssignConfig = toml::Table::new().try_into().unwrap();
+        assert!(triggers(&config, IssuesAction::Opened).await);
+        assert!(triggers(&config, IssuesAction::ReadyForReview).await);
+        assert!(!triggers(&config, IssuesAction::Reopened).await);
+
+        let config: AssignConfig = toml::toml!(trigger_on = ["ready_for_review", "reopened"])
+            .try_into()
+            .unwrap();
+        assert!(!triggers(&config, IssuesAction::Opened).await);
+        assert!(triggers(&config, IssuesAction::ReadyForReview).await);
+        assert!(triggers(&config, IssuesAction::Reopened).await);
+        Ok(ctx)
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn warn_once_when_diff_reviewers_are_off_rotation() {
+    let config: AssignConfig = toml::toml!(
+        users_on_vacation = ["martin", "diana"]
+        [owners]
+        "/compiler" = ["compiler"]
+    )
+    .try_into()
+    .unwrap();
+    run_db_test(|mut ctx| async move {
+        let mock = ctx.mock_github().await;
+        mock.set_teams(&[("compiler", &["martin", "diana"])]);
+
+        let mut pr = pull_request().call();
+        set_diff(&mock, &mut pr, &["compiler/rustc_parse/src/lib.rs"]);
+        let event = opened_pr_event(pr);
+        for _ in 0..2 {
+            handle_input(
+                ctx.handler_ctx(),
+                &config,
+                &event,
+                AssignInput::Opened { draft: false },
+            )
+            .await?;
+        }
+
+        assert!(mock.added_assignees().is_empty());
+        let warning =
+            messages::reviewers_off_rotation_message(&["diana".to_string(), "martin".to_string()]);
+        let comments = mock.posted_comments();
+        assert_eq!(comments.iter().filter(|c| **c == warning).count(), 1);
+        Ok(ctx)
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn reopened_pr_is_assigned_without_welcome() {
+    let config: AssignConfig = toml::toml!(
+        trigger_on = ["reopened"]
+        [owners]
+        "/compiler" = ["compiler"]
+    )
+    .try_into()
+    .unwrap();
+    run_db_test(|mut ctx| async move {
+        let mock = ctx.mock_github().await;
+        mock.set_teams(&[("compiler", &["martin"])]);
+
+        let mut pr = pull_request().call();
+        set_diff(&mock, &mut pr, &["compiler/rustc_parse/src/lib.rs"]);
+        let event = IssuesEvent {
+            action: IssuesAction::Reopened,
+            ..opened_pr_event(pr)
+        };
+        handle_input(ctx.handler_ctx(), &config, &event, AssignInput::Reopened).await?;
+
+        assert_eq!(mock.added_assignees(), ["martin"]);
+        assert!(mock.posted_comments().is_empty());
+        Ok(ctx)
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn opened_pr_is_assigned_from_diff() {
+    let config: AssignConfig = toml::toml!(
+        [owners]
+        "/compiler" = ["compiler"]
+        "/library" = ["libs"]
+    )
+    .try_into()
+    .unwrap();
+    run_db_test(|mut ctx| async move {
+        let mock = ctx.mock_github().await;
+        mock.set_teams(&[("compiler", &["martin"]), ("libs", &["diana"])]);
+
+        let mut pr = pull_request().call();
+        set_diff(&mock, &mut pr, &["compiler/rustc_parse/src/lib.rs"]);
+        handle_input(
+            ctx.handler_ctx(),
+            &config,
+            &opened_pr_event(pr),
+            AssignInput::Opened { draft: false },
+        )
+        .await?;
+
+        assert_eq!(mock.added_assignees(), ["martin"]);
+        assert_eq!(
+            mock.posted_comments(),
+            [messages::returning_user_welcome_message(
+                "martin",
+                &ctx.handler_ctx().username
+            )]
+        );
+        Ok(ctx)
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn opened_pr_is_welcomed_with_a_reaction() {
+    let config: AssignConfig = toml::toml!(
+        welcome_style = "reaction"
+        [owners]
+        "/compiler" = ["compiler"]
+    )
+    .try_into()
+    .unwrap();
+    run_db_test(|mut ctx| async move {
+        let mock = ctx.mock_github().await;
+        mock.set_teams(&[("compiler", &["martin"])]);
+
+        let mut pr = pull_request().call();
+        set_diff(&mock, &mut pr, &["compiler/rustc_parse/src/lib.rs"]);
+        handle_input(
+            ctx.handler_ctx(),
+            &config,
+            &opened_pr_event(pr),
+            AssignInput::Opened { draft: false },
+        )
+        .await?;
+
+        assert_eq!(mock.added_assignees(), ["martin"]);
+        assert!(mock.posted_comments().is_empty());
+        assert!(mock.requests().iter().any(|req| req.method == Method::POST
+            && req.path.ends_with("/reactions")
+            && req.body["content"] == "eyes"));
+        Ok(ctx)
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn reaction_welcome_falls_back_to_a_comment() {
+    let config: AssignConfig = toml::toml!(
+        welcome_style = "reaction"
+        [adhoc_groups]
+        fallback = ["martin"]
+        [owners]
+        "/compiler" = ["compiler"]
+    )
+    .try_into()
+    .unwrap();
+    run_db_test(|mut ctx| async move {
+        let mock = ctx.mock_github().await;
+        mock.set_teams(&[("compiler", &["martin"])]);
+
+        // New contributors get the full welcome.
+        let mut pr = pull_request().number(1).call();
+        pr.author_association = AuthorAssociation::FirstTimeContributor;
+        set_diff(&mock, &mut pr, &["compiler/rustc_parse/src/lib.rs"]);
+        handle_input(
+            ctx.handler_ctx(),
+            &config,
+            &opened_pr_event(pr),
+            AssignInput::Opened { draft: false },
+        )
+        .await?;
+        assert_eq!(mock.added_assignees(), ["martin"]);
+
+        // The author is told when no reviewer could be found.
+        let mut pr = pull_request().number(2).author(user("martin", 2)).call();
+        set_diff(&mock, &mut pr, &["compiler/rustc_parse/src/lib.rs"]);
+        handle_input(
+            ctx.handler_ctx(),
+            &config,
+            &opened_pr_event(pr),
+            AssignInput::Opened { draft: false },
+        )
+        .await?;
+        assert_eq!(mock.added_assignees(), ["martin"]);
+
+        let comments = mock.posted_comments();
+        assert_eq!(comments.len(), 2);
+        assert!(comments[0].contains("welcome!"));
+        assert_eq!(
+            comments[1],
+            messages::returning_user_welcome_message_no_reviewer("martin")
+        );
+        assert!(
+            !mock
+                .requests()
+                .iter()
+                .any(|req| req.path.ends_with("/reactions"))
+        );
+        Ok(ctx)
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn new_contributor_is_fully_welcomed_once() {
+    let config: AssignConfig = toml::toml!(
+        welcome_once = true
+        [owners]
+        "/compiler" = ["compiler"]
+    )
+    .try_into()
+    .unwrap();
+    run_db_test(|mut ctx| async move {
+        let mock = ctx.mock_github().await;
+        mock.set_teams(&[("compiler", &["martin"])]);
+
+        for number in [1, 2] {
+            let mut pr = pull_request().number(number).call();
+            pr.author_association = AuthorAssociation::FirstTimeContributor;
+            set_diff(&mock, &mut pr, &["compiler/rustc_parse/src/lib.rs"]);
+            handle_input(
+                ctx.handler_ctx(),
+                &config,
+                &opened_pr_event(pr),
+                AssignInput::Opened { draft: false },
+            )
+            .await?;
+        }
+
+        let comments = mock.posted_comments();
+        assert_eq!(comments.len(), 2);
+        assert!(comments[0].contains("welcome!"));
+        assert_eq!(
+            comments[1],
+            "Thanks for the pull request! It has been assigned to @martin."
+        );
+        Ok(ctx)
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn full_welcome_is_kept_until_posted() {
+    let silent: AssignConfig = toml::toml!(
+        welcome_once = true
+        welcome_style = "none"
+        [owners]
+        "/compiler" = ["compiler"]
+    )
+    .try_into()
+    .unwrap();
+    let config: AssignConfig = toml::toml!(
+        welcome_once = true
+        [owners]
+        "/compiler" = ["compiler"]
+    )
+    .try_into()
+    .unwrap();
+    run_db_test(|mut ctx| async move {
+        let mock = ctx.mock_github().await;
+        mock.set_teams(&[("compiler", &["martin"])]);
+
+        for (number, config) in [(1, &silent), (2, &config)] {
+            let mut pr = pull_request().number(number).call();
+            pr.author_association = AuthorAssociation::FirstTimeContributor;
+            set_diff(&mock, &mut pr, &["compiler/rustc_parse/src/lib.rs"]);
+            handle_input(
+                ctx.handler_ctx(),
+                config,
+                &opened_pr_event(pr),
+                AssignInput::Opened { draft: false },
+            )
+            .await?;
+        }
+
+        // The first PR was not welcomed, so the second one gets the full welcome.
+        let comments = mock.posted_comments();
+        assert_eq!(comments.len(), 1);
+        assert!(comments[0].contains("welcome!"));
+        Ok(ctx)
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn opened_pr_in_unassigned_area_is_not_assigned() {
+    let config: AssignConfig = toml::toml!(
+        [owners]
+        "/compiler" = ["compiler"]
+        "/vendor" = ["ghost"]
+    )
+    .try_into()
+    .unwrap();
+    run_db_test(|mut ctx| async move {
+        let mock = ctx.mock_github().await;
+        mock.set_teams(&[("compiler", &["martin"])]);
+
+        let mut pr = pull_request().call();
+        set_diff(&mock, &mut pr, &["vendor/foo/lib.rs"]);
+        handle_input(
+            ctx.handler_ctx(),
+            &config,
+            &opened_pr_event(pr),
+            AssignInput::Opened { draft: false },
+        )
+        .await?;
+
+        assert!(mock.added_assignees().is_empty());
+        assert!(mock.posted_comments().is_empty());
+        Ok(ctx)
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn opened_pr_spanning_several_areas_is_labeled() {
+    let config: AssignConfig = toml::toml!(
+        multi_area_threshold_percent = 60
+        [owners]
+        "/compiler" = ["compiler"]
+        "/library" = ["libs"]
+    )
+    .try_into()
+    .unwrap();
+    run_db_test(|mut ctx| async move {
+        let mock = ctx.mock_github().await;
+        mock.set_teams(&[("compiler", &["martin"]), ("libs", &["diana"])]);
+
+        let mut pr = pull_request().call();
+        set_diff(
+            &mock,
+            &mut pr,
+            &["compiler/rustc_parse/src/lib.rs", "library/core/src/lib.rs"],
+        );
+        handle_input(
+            ctx.handler_ctx(),
+            &config,
+            &opened_pr_event(pr),
+            AssignInput::Opened { draft: false },
+        )
+        .await?;
+
+        assert!(mock.added_assignees().is_empty());
+        assert_eq!(mock.added_labels(), ["T-compiler", "T-libs"]);
+        let compiler = ["compiler".to_string()];
+        let libs = ["libs".to_string()];
+        assert_eq!(
+            mock.posted_comments(),
+            [messages::multi_area_message(&[
+                ("/compiler", &compiler),
+                ("/library", &libs)
+            ])]
+        );
+        Ok(ctx)
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn team_labels_are_capped() {
+    let config: AssignConfig = toml::toml!(
+        multi_area_threshold_percent = 70
+        max_team_labels = 1
+        [owners]
+        "/compiler" = ["compiler"]
+        "/library" = ["libs"]
+    )
+    .try_into()
+    .unwrap();
+    run_db_test(|mut ctx| async move {
+        let mock = ctx.mock_github().await;
+        mock.set_teams(&[("compiler", &["martin"]), ("libs", &["diana"])]);
+
+        let mut pr = pull_request().call();
+        set_diff(
+            &mock,
+            &mut pr,
+            &[
+                "compiler/rustc_parse/src/lib.rs",
+                "library/core/src/lib.rs",
+                "library/std/src/lib.rs",
+            ],
+        );
+        handle_input(
+            ctx.handler_ctx(),
+            &config,
+            &opened_pr_event(pr),
+            AssignInput::Opened { draft: false },
+        )
+        .await?;
+
+        // Only the label of the most modified area is added.
+        assert!(mock.added_assignees().is_empty());
+        assert_eq!(mock.added_labels(), ["T-libs"]);
+        let comments = mock.posted_comments();
+        assert_eq!(comments.len(), 1);
+        assert!(comments[0].ends_with(&messages::unlabeled_teams_message(&[
+            "T-compiler".to_string()
+        ])));
+        Ok(ctx)
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn self_review_request() {
+    run_db_test(|mut ctx| async move {
+        let mock = ctx.mock_github().await;
+        mock.set_teams(&[("compiler", &["martin"])]);
+        for (number, allow_self_review, assignee) in
+            [(1, true, "triagebot-tester"), (2, false, "martin")]
+        {
+            let config: AssignConfig = toml::from_str(&format!(
+                r#"
+allow_self_review = {allow_self_review}
+[owners]
+"/compiler" = ["compiler"]
+"#
+            ))
+            .unwrap();
+            let mut pr = pull_request()
+                .number(number)
+                .body("r? @triagebot-tester")
+                .call();
+            set_diff(&mock, &mut pr, &["compiler/rustc_parse/src/lib.rs"]);
+            handle_input(
+                ctx.handler_ctx(),
+                &config,
+                &opened_pr_event(pr),
+                AssignInput::Opened { draft: false },
+            )
+            .await?;
+            assert_eq!(mock.added_assignees().last().unwrap(), assignee);
+        }
+        // The rejected self-review request is reported.
+        assert_eq!(mock.posted_comments()[0], messages::REVIEWER_IS_PR_AUTHOR);
+        Ok(ctx)
+    })
+    .await;
+}
+
+/// Opens a PR whose body contains two `r?` commands, with the given
+/// `multiple_review_requests` policy, and returns the mock server.
+async fn open_pr_with_two_review_requests(ctx: &mut TestContext, policy: &str) -> MockGithub {
+    let config: AssignConfig = toml::from_str(&format!(
+        r#"
+multiple_review_requests = "{policy}"
+[owners]
+"/compiler" = ["compiler"]
+"#
+    ))
+    .unwrap();
+    let mock = ctx.mock_github().await;
+    mock.set_teams(&[("compiler", &["martin", "diana", "jyn"])]);
+
+    let mut pr = pull_request().body("r? @diana\n\nr? @jyn").call();
+    set_diff(&mock, &mut pr, &["compiler/rustc_parse/src/lib.rs"]);
+    handle_input(
+        ctx.handler_ctx(),
+        &config,
+        &opened_pr_event(pr),
+        AssignInput::Opened { draft: false },
+    )
+    .await
+    .unwrap();
+    mock
+}
+
+#[tokio::test]
+async fn multiple_review_requests_first_wins() {
+    run_db_test(|mut ctx| async move {
+        let mock = open_pr_with_two_review_requests(&mut ctx, "first-wins").await;
+        assert_eq!(mock.added_assignees(), ["diana"]);
+        Ok(ctx)
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn multiple_review_requests_last_wins() {
+    run_db_test(|mut ctx| async move {
+        let mock = open_pr_with_two_review_requests(&mut ctx, "last-wins").await;
+        assert_eq!(mock.added_assignees(), ["jyn"]);
+        Ok(ctx)
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn multiple_review_requests_error() {
+    run_db_test(|mut ctx| async move {
+        let mock = open_pr_with_two_review_requests(&mut ctx, "error").await;
+        // The review requests are ignored, and the reviewer is picked from the diff instead.
+        assert_eq!(mock.added_assignees().len(), 1);
+        assert_eq!(
+            mock.posted_comments()[0],
+            messages::multiple_review_requests_message(&["diana".to_string(), "jyn".to_string()])
+        );
+        Ok(ctx)
+    })
+    .await;
+}
+
+/// Opens a PR modifying the compiler with the given `labels`, in a repository
+/// that routes `A-diagnostics` PRs to the diagnostics team, and returns the mock server.
+async fn open_pr_with_labels(ctx: &mut TestContext, labels: Vec<&str>) -> MockGithub {
+    let config: AssignConfig = toml::toml!(
+        [owners]
+        "/compiler" = ["compiler"]
+        [label_owners]
+        "A-diagnostics" = ["diagnostics"]
+        "A-lints" = ["diagnostics", "lints"]
+    )
+    .try_into()
+    .unwrap();
+    let mock = ctx.mock_github().await;
+    mock.set_teams(&[
+        ("compiler", &["martin"]),
+        ("diagnostics", &["diana"]),
+        ("lints", &["diana"]),
+    ]);
+
+    let mut pr = pull_request().labels(labels).call();
+    set_diff(&mock, &mut pr, &["compiler/rustc_parse/src/lib.rs"]);
+    handle_input(
+        ctx.handler_ctx(),
+        &config,
+        &opened_pr_event(pr),
+        AssignInput::Opened { draft: false },
+    )
+    .await
+    .unwrap();
+    mock
+}
+
+#[tokio::test]
+async fn ignored_author_is_not_welcomed() {
+    let config: AssignConfig = toml::toml!(
+        ignore_authors = ["*[bot]"]
+        [owners]
+        "/compiler" = ["compiler"]
+    )
+    .try_into()
+    .unwrap();
+    run_db_test(|mut ctx| async move {
+        let mock = ctx.mock_github().await;
+        mock.set_teams(&[("compiler", &["martin"])]);
+
+        let mut pr = pull_request()
+            .author(user("dependabot[bot]", 49699333))
+            .call();
+        set_diff(&mock, &mut pr, &["compiler/rustc_parse/src/lib.rs"]);
+        handle_input(
+            ctx.handler_ctx(),
+            &config,
+            &opened_pr_event(pr),
+            AssignInput::Opened { draft: false },
+        )
+        .await?;
+
+        assert_eq!(mock.added_assignees(), ["martin"]);
+        assert!(mock.posted_comments().is_empty());
+        Ok(ctx)
+    })
+    .await;
+}
+
+#[test]
+fn author_patterns() {
+    assert!(matches_author_pattern("*[bot]", "dependabot[bot]"));
+    assert!(matches_author_pattern("*[bot]", "Renovate[bot]"));
+    assert!(!matches_author_pattern("*[bot]", "bot"));
+    assert!(matches_author_pattern("rust-*-bot", "rust-timer-bot"));
+    assert!(!matches_author_pattern("rust-*-bot", "rust-bot"));
+    assert!(matches_author_pattern("bors", "bors"));
+    assert!(!matches_author_pattern("bors", "borsa"));
+}
+
+#[tokio::test]
+async fn no_assign_label() {
+    let config: AssignConfig = toml::toml!(
+        no_assign_label = "rollup"
+        [owners]
+        "/compiler" = ["compiler"]
+    )
+    .try_into()
+    .unwrap();
+    run_db_test(|mut ctx| async move {
+        let mock = ctx.mock_github().await;
+        mock.set_teams(&[("compiler", &["martin"])]);
+
+        let mut pr = pull_request().labels(vec!["rollup"]).call();
+        set_diff(&mock, &mut pr, &["compiler/rustc_parse/src/lib.rs"]);
+        handle_input(
+            ctx.handler_ctx(),
+            &config,
+            &opened_pr_event(pr),
+            AssignInput::Opened { draft: false },
+        )
+        .await?;
+        assert!(mock.added_assignees().is_empty());
+        assert!(mock.posted_comments().is_empty());
+        // The diff is not even fetched.
+        assert!(
+            !mock
+                .requests()
+                .iter()
+                .any(|request| request.path.contains("/compare/"))
+        );
+
+        let mut pr = pull_request().call();
+        set_diff(&mock, &mut pr, &["compiler/rustc_parse/src/lib.rs"]);
+        handle_input(
+            ctx.handler_ctx(),
+            &config,
+            &opened_pr_event(pr),
+            AssignInput::Opened { draft: false },
+        )
+        .await?;
+        assert_eq!(mock.added_assignees(), ["martin"]);
+        Ok(ctx)
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn blocked_pr_is_assigned_once_unblocked() {
+    let config: AssignConfig = toml::toml!(
+        blocking_labels = ["S-blocked"]
+        [owners]
+        "/compiler" = ["compiler"]
+    )
+    .try_into()
+    .unwrap();
+    run_db_test(|mut ctx| async move {
+        let mock = ctx.mock_github().await;
+        mock.set_teams(&[("compiler", &["martin"])]);
+
+        let mut pr = pull_request().labels(vec!["S-blocked"]).call();
+        set_diff(&mock, &mut pr, &["compiler/rustc_parse/src/lib.rs"]);
+        handle_input(
+            ctx.handler_ctx(),
+            &config,
+            &opened_pr_event(pr),
+            AssignInput::Opened { draft: false },
+        )
+        .await?;
+        assert!(mock.added_assignees().is_empty());
+
+        let mut pr = pull_request().labels(vec!["T-compiler"]).call();
+        set_diff(&mock, &mut pr, &["compiler/rustc_parse/src/lib.rs"]);
+        let event = IssuesEvent {
+            action: IssuesAction::Unlabeled {
+                label: Some(Label {
+                    name: "S-blocked".to_string(),
+                }),
+            },
+            ..opened_pr_event(pr)
+        };
+        let input = parse_input(ctx.handler_ctx(), &event, Some(&config))
+            .await
+            .unwrap()
+            .unwrap();
+        handle_input(ctx.handler_ctx(), &config, &event, input).await?;
+        assert_eq!(mock.added_assignees(), ["martin"]);
+        // The PR author is not welcomed again.
+        assert!(mock.posted_comments().is_empty());
+        Ok(ctx)
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn unblocked_draft_pr_is_not_assigned() {
+    let config: AssignConfig = toml::toml!(blocking_labels = ["S-blocked"])
+        .try_into()
+        .unwrap();
+    run_db_test(|ctx| async move {
+        let event = IssuesEvent {
+            action: IssuesAction::Unlabeled {
+                label: Some(Label {
+                    name: "S-blocked".to_string(),
+                }),
+            },
+            ..opened_pr_event(Issue {
+                draft: true,
+                ..pull_request().call()
+            })
+        };
+        let input = parse_input(ctx.handler_ctx(), &event, Some(&config))
+            .await
+            .unwrap();
+        assert!(input.is_none());
+        Ok(ctx)
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn opened_pr_is_assigned_from_label() {
+    run_db_test(|mut ctx| async move {
+        let mock = open_pr_with_labels(&mut ctx, vec!["A-diagnostics", "A-lints"]).await;
+        assert_eq!(mock.added_assignees(), ["diana"]);
+        Ok(ctx)
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn opened_pr_without_routing_label_is_assigned_from_diff() {
+    run_db_test(|mut ctx| async move {
+        let mock = open_pr_with_labels(&mut ctx, vec!["T-compiler"]).await;
+        assert_eq!(mock.added_assignees(), ["martin"]);
+        Ok(ctx)
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn welcome_mentions_assigned_pr_count() {
+    let config: AssignConfig = toml::toml!(
+        welcome_assigned_pr_count = true
+        [owners]
+        "/compiler" = ["compiler"]
+    )
+    .try_into()
+    .unwrap();
+    run_db_test(|mut ctx| async move {
+        let mock = ctx.mock_github().await;
+        mock.set_teams(&[("compiler", &["martin"])]);
+        let martin = user("martin", 2);
+        upsert_review_prefs(
+            ctx.db_client(),
+            martin.clone(),
+            None,
+            RotationMode::OnRotation,
+        )
+        .await?;
+        let prs = (1..=3)
+            .map(|number| {
+                let pr = AssignedPullRequest {
+                    title: format!("PR {number}"),
+                    updated_at: chrono::Utc::now(),
+                };
+                (number, pr)
+            })
+            .collect();
+        *ctx.handler_ctx().workqueue.write().await =
+            ReviewerWorkqueue::new(HashMap::from([(martin.id, prs)]));
+
+        let mut pr = pull_request().call();
+        set_diff(&mock, &mut pr, &["compiler/rustc_parse/src/lib.rs"]);
+        handle_input(
+            ctx.handler_ctx(),
+            &config,
+            &opened_pr_event(pr),
+            AssignInput::Opened { draft: false },
+        )
+        .await?;
+
+        assert_eq!(mock.added_assignees(), ["martin"]);
+        assert!(
+            mock.posted_comments()[0].ends_with(&messages::assigned_pr_count_message("martin", 3))
+        );
+        Ok(ctx)
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn review_request_comment_assigns_team_member() {
+    let config: AssignConfig = toml::Table::new().try_into().unwrap();
+    run_db_test(|mut ctx| async move {
+        let mock = ctx.mock_github().await;
+        mock.set_teams(&[("compiler", &["martin"])]);
+
+        let event = comment_event(pull_request().call(), user("diana", 2), "r? compiler");
+        handle_command(
+            ctx.handler_ctx(),
+            &config,
+            &event,
+            AssignCommand::RequestReview {
+                name: "compiler".to_string(),
+                others: vec![],
+                shadow: None,
+            },
+        )
+        .await?;
+
+        assert_eq!(mock.added_assignees(), ["martin"]);
+        assert_eq!(mock.added_labels(), ["T-compiler"]);
+        assert!(mock.posted_comments().is_empty());
+        Ok(ctx)
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn review_request_with_shadow_reviewer() {
+    let config: AssignConfig = toml::Table::new().try_into().unwrap();
+    run_db_test(|mut ctx| async move {
+        let mock = ctx.mock_github().await;
+        mock.set_teams(&[("compiler", &["martin"])]);
+
+        let event = comment_event(
+            pull_request().call(),
+            user("diana", 2),
+            "r? compiler shadow @newbie",
+        );
+        handle_command(
+            ctx.handler_ctx(),
+            &config,
+            &event,
+            AssignCommand::RequestReview {
+                name: "compiler".to_string(),
+                others: vec![],
+                shadow: Some("newbie".to_string()),
+            },
+        )
+        .await?;
+
+        // The shadow reviewer is not assigned.
+        assert_eq!(mock.added_assignees(), ["martin"]);
+        assert_eq!(
+            mock.posted_comments(),
+            [messages::shadow_reviewer_message("newbie", "martin")]
+        );
+        Ok(ctx)
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn review_request_several_reviewers() {
+    let config: AssignConfig = toml::Table::new().try_into().unwrap();
+    run_db_test(|mut ctx| async move {
+        let mock = ctx.mock_github().await;
+        mock.set_teams(&[("compiler", &["martin"])]);
+
+        let pr = pull_request().author(user("diana", 2)).call();
+        let event = comment_event(
+            pr,
+            user("jyn", 3),
+            "r? compiler @diana @rust-lang/nope @bob",
+        );
+        handle_command(
+            ctx.handler_ctx(),
+            &config,
+            &event,
+            AssignCommand::RequestReview {
+                name: "compiler".to_string(),
+                others: vec![
+                    "diana".to_string(),
+                    "rust-lang/nope".to_string(),
+                    "bob".to_string(),
+                ],
+                shadow: None,
+            },
+        )
+        .await?;
+
+        // The PR author and the unknown team are skipped.
+        assert_eq!(mock.added_assignees(), ["martin", "bob"]);
+        let comments = mock.posted_comments();
+        assert_eq!(comments.len(), 1);
+        assert!(comments[0].starts_with("Assigned @martin, @bob to review this pull request."));
+        assert!(comments[0].contains(&format!(
+            "Could not assign `diana`: {}",
+            messages::REVIEWER_IS_PR_AUTHOR
+        )));
+        assert!(comments[0].contains(
+            "Could not assign `rust-lang/nope`: Team or group `rust-lang/nope` not found."
+        ));
+        Ok(ctx)
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn review_request_replaces_bot_team_label() {
+    let config: AssignConfig = toml::Table::new().try_into().unwrap();
+    run_db_test(|mut ctx| async move {
+        let mock = ctx.mock_github().await;
+        mock.set_teams(&[("compiler", &["martin"]), ("libs", &["diana"])]);
+        let request_review = async |ctx: &TestContext, labels: Vec<&str>, name: &str| {
+            let pr = pull_request().labels(labels).call();
+            let event = comment_event(pr, user("jyn", 3), &format!("r? {name}"));
+            handle_command(
+                ctx.handler_ctx(),
+                &config,
+                &event,
+                AssignCommand::RequestReview {
+                    name: name.to_string(),
+                    others: vec![],
+                    shadow: None,
+                },
+            )
+            .await
+            .unwrap();
+        };
+
+        request_review(&ctx, vec![], "compiler").await;
+        request_review(&ctx, vec!["T-compiler"], "libs").await;
+        assert_eq!(mock.added_labels(), ["T-compiler", "T-libs"]);
+        let removed_labels: Vec<_> = mock
+            .requests()
+            .into_iter()
+            .filter(|req| req.method == Method::DELETE && req.path.contains("/labels/"))
+            .map(|req| req.path)
+            .collect();
+        assert_eq!(
+            removed_labels,
+            ["/repos/rust-lang/rust/issues/1/labels/T-compiler"]
+        );
+        Ok(ctx)
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn work_show_command() {
+    let config: AssignConfig = toml::Table::new().try_into().unwrap();
+    run_db_test(|mut ctx| async move {
+        let mock = ctx.mock_github().await;
+        let diana = user("diana", 2);
+        let show_work = async |ctx: &TestContext| {
+            let event = comment_event(issue().call(), diana.clone(), "@rustbot work show");
+            handle_command(ctx.handler_ctx(), &config, &event, AssignCommand::ShowWork)
+                .await
+                .unwrap();
+        };
+
+        // Users who never set their preferences have the defaults.
+        show_work(&ctx).await;
+        upsert_review_prefs(
+            ctx.db_client(),
+            diana.clone(),
+            Some(3),
+            RotationMode::OffRotation,
+        )
+        .await?;
+        show_work(&ctx).await;
+
+        assert_eq!(
+            mock.posted_comments(),
+            [
+                messages::workqueue_message("diana", 0, None, true),
+                messages::workqueue_message("diana", 0, Some(3), false),
+            ]
+        );
+        assert!(mock.posted_comments()[0].contains("Review capacity: unlimited"));
+        Ok(ctx)
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn work_team_command() {
+    let config: AssignConfig = toml::toml!(review_prefs = {}).try_into().unwrap();
+    run_db_test(|mut ctx| async move {
+        let mock = ctx.mock_github().await;
+        mock.set_teams(&[("compiler", &["martin", "diana"])]);
+        let martin = user("martin", 1);
+        let diana = user("diana", 2);
+        upsert_review_prefs(
+            ctx.db_client(),
+            diana.clone(),
+            Some(3),
+            RotationMode::OnRotation,
+        )
+        .await?;
+        ctx.add_user("martin", martin.id).await;
+        let prs = (1..=2)
+            .map(|number| {
+                let pr = AssignedPullRequest {
+                    title: format!("PR {number}"),
+                    updated_at: chrono::Utc::now(),
+                };
+                (number, pr)
+            })
+            .collect();
+        *ctx.handler_ctx().workqueue.write().await =
+            ReviewerWorkqueue::new(HashMap::from([(martin.id, prs)]));
+
+        for (author, team) in [
+            (&martin, "compiler"),
+            (&martin, "lang"),
+            (&user("octocat", 3), "compiler"),
+        ] {
+            let body = format!("@rustbot work team {team}");
+            let event = comment_event(issue().call(), author.clone(), &body);
+            let cmd = AssignCommand::ShowTeamWork {
+                team: team.to_string(),
+            };
+            handle_command(ctx.handler_ctx(), &config, &event, cmd).await?;
+        }
+
+        let comments = mock.posted_comments();
+        assert_eq!(
+            comments[0],
+            messages::team_workqueue_message(
+                "compiler",
+                &[("diana", 0, Some(3)), ("martin", 2, None)]
+            )
+        );
+        assert_eq!(
+            comments[1],
+            FindReviewerError::TeamNotFound("lang".to_string()).to_string()
+        );
+        assert!(comments[2].contains("Only Rust team members"));
+        Ok(ctx)
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn work_capacity_command() {
+    let config: AssignConfig = toml::Table::new().try_into().unwrap();
+    run_db_test(|mut ctx| async move {
+        let mock = ctx.mock_github().await;
+        mock.set_teams(&[("compiler", &["martin", "diana"])]);
+        let set_capacity = async |ctx: &TestContext, commenter: User, target: Option<&str>| {
+            let event = comment_event(issue().call(), commenter, "@rustbot work capacity 3");
+            let cmd = AssignCommand::SetCapacity {
+                user: target.map(|target| target.to_string()),
+                capacity: Some(3),
+            };
+            handle_command(ctx.handler_ctx(), &config, &event, cmd).await
+        };
+
+        set_capacity(&ctx, user("jyn", 3), None).await?;
+        let prefs = get_review_prefs(ctx.db_client(), 3).await?.unwrap();
+        assert_eq!(prefs.max_assigned_prs, Some(3));
+
+        // Only team members can change the capacity of someone else.
+        assert!(
+            set_capacity(&ctx, user("jyn", 3), Some("diana"))
+                .await
+                .is_err()
+        );
+        // The mock team data gives the same GitHub ID to every member.
+        set_capacity(&ctx, user("martin", 2), Some("diana")).await?;
+        let prefs = get_review_prefs(ctx.db_client(), 100).await?.unwrap();
+        assert_eq!(prefs.max_assigned_prs, Some(3));
+
+        assert_eq!(
+            mock.posted_comments(),
+            [
+                messages::review_capacity_message("jyn", Some(3)),
+                messages::review_capacity_message("diana", Some(3)),
+            ]
+        );
+        Ok(ctx)
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn work_rotation_command() {
+    let config: AssignConfig = toml::Table::new().try_into().unwrap();
+    run_db_test(|mut ctx| async move {
+        let mock = ctx.mock_github().await;
+        let diana = user("diana", 2);
+        upsert_review_prefs(
+            ctx.db_client(),
+            diana.clone(),
+            Some(5),
+            RotationMode::OnRotation,
+        )
+        .await?;
+
+        let event = comment_event(issue().call(), diana, "@rustbot work rotation off");
+        let cmd = AssignCommand::SetRotation { on_rotation: false };
+        handle_command(ctx.handler_ctx(), &config, &event, cmd).await?;
+
+        // The capacity is kept.
+        let prefs = get_review_prefs(ctx.db_client(), 2).await?.unwrap();
+        assert_eq!(prefs.rotation_mode, RotationMode::OffRotation);
+        assert_eq!(prefs.max_assigned_prs, Some(5));
+        assert_eq!(
+            mock.posted_comments(),
+            [messages::rotation_mode_message("diana", false)]
+        );
+        Ok(ctx)
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn vacation_command() {
+    let config: AssignConfig = toml::Table::new().try_into().unwrap();
+    run_db_test(|mut ctx| async move {
+        let mock = ctx.mock_github().await;
+        mock.set_teams(&[("compiler", &["diana"])]);
+        let vacation = |start: &str, end: &str| AssignCommand::Vacation {
+            start: start.to_string(),
+            end: end.to_string(),
+        };
+        for (body, cmd) in [
+            (
+                "@rustbot vacation 2999-08-01 2999-08-14",
+                vacation("2999-08-01", "2999-08-14"),
+            ),
+            (
+                "@rustbot vacation 2999-08-14 2999-08-01",
+                vacation("2999-08-14", "2999-08-01"),
+            ),
+            ("@rustbot vacation end", AssignCommand::VacationEnd),
+        ] {
+            let event = comment_event(issue().call(), user("diana", 2), body);
+            handle_command(ctx.handler_ctx(), &config, &event, cmd).await?;
+        }
+
+        assert_eq!(
+            mock.posted_comments(),
+            [
+                "@diana is now on vacation from 2999-08-01 to 2999-08-14, and won't be assigned \
+                 PRs automatically meanwhile.",
+                "The last day of the vacation must be after its first day, and not in the past.",
+                "@diana is no longer on vacation.",
+            ]
+        );
+        let prefs = get_review_prefs(ctx.db_client(), 2).await?.unwrap();
+        assert_eq!(prefs.vacation, None);
+        Ok(ctx)
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn release_keeps_team_label_added_by_someone_else() {
+    let config: AssignConfig = toml::Table::new().try_into().unwrap();
+    run_db_test(|mut ctx| async move {
+        let mock = ctx.mock_github().await;
+        mock.set_teams(&[("compiler", &["martin"])]);
+
+        let pr = pull_request().labels(vec!["T-compiler"]).call();
+        let event = comment_event(pr, user("jyn", 3), "r? compiler");
+        handle_command(
+            ctx.handler_ctx(),
+            &config,
+            &event,
+            AssignCommand::RequestReview {
+                name: "compiler".to_string(),
+                others: vec![],
+                shadow: None,
+            },
+        )
+        .await?;
+        let pr = pull_request().labels(vec!["T-compiler"]).call();
+        let event = comment_event(pr, user("jyn", 3), "@rustbot release-assignment");
+        handle_command(
+            ctx.handler_ctx(),
+            &config,
+            &event,
+            AssignCommand::ReleaseAssignment,
+        )
+        .await?;
+
+        assert!(
+            !mock
+                .requests()
+                .iter()
+                .any(|req| req.method == Method::DELETE && req.path.contains("/labels/"))
+        );
+        Ok(ctx)
+    })
+    .await;
+}
+
+/// A PR waiting for a reviewer, as returned by the search API and the pulls API.
+fn unassigned_pr_json(number: u64) -> serde_json::Value {
+    serde_json::json!({
+        "number": number,
+        "body": "",
+        "created_at": "2025-01-01T00:00:00Z",
+        "updated_at": "2025-01-01T00:00:00Z",
+        "title": format!("PR #{number}"),
+        "html_url": format!("https://github.com/rust-lang/rust/pull/{number}"),
+        "user": { "login": "jyn", "id": 3 },
+        "labels": [],
+        "assignees": [],
+        "comments_url": format!(
+            "https://api.github.com/repos/rust-lang/rust/issues/{number}/comments"
+        ),
+        "state": "open",
+        "author_association": "NONE",
+        "base": { "sha": "base", "ref": "master" },
+        "head": { "sha": "head", "ref": "feature" },
+    })
+}
+
+#[tokio::test]
+async fn assign_next_pr_owned_by_team() {
+    let config: AssignConfig = toml::toml!(
+        [owners]
+        "/compiler" = ["compiler"]
+        "/library" = ["libs"]
+    )
+    .try_into()
+    .unwrap();
+    run_db_test(|mut ctx| async move {
+        let mock = ctx.mock_github().await;
+        mock.set_teams(&[("compiler", &["martin"]), ("libs", &["diana"])]);
+
+        mock.respond(
+            Method::GET,
+            "/search/issues",
+            serde_json::json!({ "total_count": 1, "items": [unassigned_pr_json(2)] }),
+        );
+        mock.respond(
+            Method::GET,
+            "/repos/rust-lang/rust/pulls/2",
+            unassigned_pr_json(2),
+        );
+        set_diff(
+            &mock,
+            &mut pull_request().call(),
+            &["library/core/src/lib.rs"],
+        );
+
+        let assign_next = async |ctx: &TestContext, reviewer: User| {
+            let event = comment_event(issue().call(), reviewer, "@rustbot assign next");
+            handle_command(ctx.handler_ctx(), &config, &event, AssignCommand::Next)
+                .await
+                .unwrap();
+        };
+
+        // The only unassigned PR modifies an area owned by another team.
+        assign_next(&ctx, user("martin", 2)).await;
+        assert!(mock.added_assignees().is_empty());
+        assert_eq!(mock.posted_comments(), [messages::NO_NEXT_PR]);
+
+        assign_next(&ctx, user("diana", 4)).await;
+        assert_eq!(mock.added_assignees(), ["diana"]);
+        assert_eq!(
+            mock.posted_comments()[1],
+            messages::next_pr_assigned_message("diana", "https://github.com/rust-lang/rust/pull/2")
+        );
+        Ok(ctx)
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn assign_next_pr_skips_failing_prs() {
+    let config: AssignConfig = toml::toml!(
+        [owners]
+        "/library" = ["libs"]
+    )
+    .try_into()
+    .unwrap();
+    run_db_test(|mut ctx| async move {
+        let mock = ctx.mock_github().await;
+        mock.set_teams(&[("libs", &["diana"])]);
+
+        mock.respond(
+            Method::GET,
+            "/search/issues",
+            serde_json::json!({
+                "total_count": 2,
+                "items": [unassigned_pr_json(2), unassigned_pr_json(3)],
+            }),
+        );
+        mock.fail(Method::GET, "/repos/rust-lang/rust/pulls/2", 1);
+        mock.respond(
+            Method::GET,
+            "/repos/rust-lang/rust/pulls/3",
+            unassigned_pr_json(3),
+        );
+        set_diff(
+            &mock,
+            &mut pull_request().call(),
+            &["library/core/src/lib.rs"],
+        );
+
+        let event = comment_event(issue().call(), user("diana", 4), "@rustbot assign next");
+        handle_command(ctx.handler_ctx(), &config, &event, AssignCommand::Next).await?;
+        assert_eq!(mock.added_assignees(), ["diana"]);
+        assert_eq!(
+            mock.posted_comments(),
+            [messages::next_pr_assigned_message(
+                "diana",
+                "https://github.com/rust-lang/rust/pull/3"
+            )]
+        );
+        Ok(ctx)
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn suggest_reviewer_of_draft_pr() {
+    let config: AssignConfig = toml::toml!(
+        [owners]
+        "/compiler" = ["compiler"]
+    )
+    .try_into()
+    .unwrap();
+    run_db_test(|mut ctx| async move {
+        let mock = ctx.mock_github().await;
+        mock.set_teams(&[("compiler", &["martin"])]);
+        respond_pr(&mock, true);
+        set_diff(
+            &mock,
+            &mut pull_request().call(),
+            &["compiler/rustc_parse/src/lib.rs"],
+        );
+
+        let event = comment_event(
+            pull_request().call(),
+            user("jyn", 3),
+            "@rustbot suggest-reviewer",
+        );
+        handle_command(
+            ctx.handler_ctx(),
+            &config,
+            &event,
+            AssignCommand::SuggestReview,
+        )
+        .await?;
+
+        assert!(mock.added_assignees().is_empty());
+        assert_eq!(
+            mock.posted_comments(),
+            [messages::suggested_reviewer_message(Some("martin"))]
+        );
+        Ok(ctx)
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn suggest_reviewer_is_read_only() {
+    let config: AssignConfig = toml::toml!(
+        selection_mode = "strict-alternate"
+        [owners]
+        "/compiler" = ["compiler"]
+    )
+    .try_into()
+    .unwrap();
+    run_db_test(|mut ctx| async move {
+        let mock = ctx.mock_github().await;
+        mock.set_teams(&[("compiler", &["martin", "diana"])]);
+        respond_pr(&mock, false);
+        set_diff(
+            &mock,
+            &mut pull_request().call(),
+            &["compiler/rustc_parse/src/lib.rs"],
+        );
+
+        for _ in 0..2 {
+            let event = comment_event(
+                pull_request().call(),
+                user("jyn", 3),
+                "@rustbot suggest-reviewer",
+            );
+            handle_command(
+                ctx.handler_ctx(),
+                &config,
+                &event,
+                AssignCommand::SuggestReview,
+            )
+            .await?;
+        }
+
+        // The rotation did not move on, so the same reviewer is suggested again.
+        assert!(mock.added_assignees().is_empty());
+        let suggestion = messages::suggested_reviewer_message(Some("diana"));
+        assert_eq!(mock.posted_comments(), [suggestion.clone(), suggestion]);
+        let rotation = rotation_key(&pull_request().call(), &["compiler".to_string()]);
+        assert_eq!(get_last_reviewer(ctx.db_client(), &rotation).await?, None);
+        Ok(ctx)
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn declined_assignment_picks_another_reviewer() {
+    let config: AssignConfig = toml::toml!(
+        [owners]
+        "/compiler" = ["compiler"]
+    )
+    .try_into()
+    .unwrap();
+    run_db_test(|mut ctx| async move {
+        let mock = ctx.mock_github().await;
+        mock.set_teams(&[("compiler", &["martin", "diana"])]);
+        respond_pr(&mock, false);
+        set_diff(
+            &mock,
+            &mut pull_request().call(),
+            &["compiler/rustc_parse/src/lib.rs"],
+        );
+
+        // Like in real comment events, the PR has no base/head commits.
+        let pr = || pull_request().assignees(vec![user("martin", 2)]).call();
+
+        // Only the assignee can decline the assignment.
+        let event = comment_event(pr(), user("diana", 3), "@rustbot not-me");
+        assert!(
+            handle_command(ctx.handler_ctx(), &config, &event, AssignCommand::NotMe)
+                .await
+                .is_err()
+        );
+        assert!(mock.added_assignees().is_empty());
+
+        let event = comment_event(pr(), user("martin", 2), "@rustbot not-me");
+        handle_command(ctx.handler_ctx(), &config, &event, AssignCommand::NotMe).await?;
+        assert_eq!(mock.added_assignees(), ["diana"]);
+        assert!(mock.posted_comments().is_empty());
+        let previous_reviewers =
+            get_previous_reviewers(ctx.db_client_mut(), event.issue().unwrap()).await;
+        assert!(previous_reviewers.names.contains("martin"));
+        assert!(previous_reviewers.declined.contains("martin"));
+        Ok(ctx)
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn proposed_reviewer_accepts_or_passes() {
+    let config: AssignConfig = toml::toml!(
+        require_acceptance = true
+        [adhoc_groups]
+        fallback = ["diana"]
+        [owners]
+        "/compiler" = ["compiler"]
+    )
+    .try_into()
+    .unwrap();
+    run_db_test(|mut ctx| async move {
+        let mock = ctx.mock_github().await;
+        mock.set_teams(&[("compiler", &["martin"])]);
+        respond_pr(&mock, false);
+        let mut opened = pull_request().call();
+        set_diff(&mock, &mut opened, &["compiler/rustc_parse/src/lib.rs"]);
+
+        // Like in real comment events, the PR has no base/head commits.
+        let pr = || pull_request().call();
+        let proposal = |reviewer| messages::review_proposal_message(reviewer, "triagebot-test", 3);
+        handle_input(
+            ctx.handler_ctx(),
+            &config,
+            &opened_pr_event(opened),
+            AssignInput::Opened { draft: false },
+        )
+        .await?;
+        assert!(mock.added_assignees().is_empty());
+        assert_eq!(mock.posted_comments(), [proposal("martin")]);
+
+        // Only the proposed reviewer can answer.
+        let event = comment_event(pr(), user("diana", 3), "@rustbot accept");
+        assert!(
+            handle_command(ctx.handler_ctx(), &config, &event, AssignCommand::Accept)
+                .await
+                .is_err()
+        );
+
+        let event = comment_event(pr(), user("martin", 2), "@rustbot pass");
+        handle_command(ctx.handler_ctx(), &config, &event, AssignCommand::Pass).await?;
+        assert!(mock.added_assignees().is_empty());
+        assert_eq!(
+            mock.posted_comments()[1..],
+            [
+                proposal("diana"),
+                messages::fallback_after_decline_message("diana"),
+            ]
+        );
+
+        let event = comment_event(pr(), user("diana", 3), "@rustbot accept");
+        handle_command(ctx.handler_ctx(), &config, &event, AssignCommand::Accept).await?;
+        assert_eq!(mock.added_assignees(), ["diana"]);
+        // The proposal was answered.
+        assert!(
+            handle_command(ctx.handler_ctx(), &config, &event, AssignCommand::Accept)
+                .await
+                .is_err()
+        );
+        Ok(ctx)
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn manual_assignment_replaces_proposal() {
+    let config: AssignConfig = toml::toml!(
+        require_acceptance = true
+        [owners]
+        "/compiler" = ["compiler"]
+    )
+    .try_into()
+    .unwrap();
+    run_db_test(|mut ctx| async move {
+        let mock = ctx.mock_github().await;
+        mock.set_teams(&[("compiler", &["martin"])]);
+        let mut pr = pull_request().call();
+        set_diff(&mock, &mut pr, &["compiler/rustc_parse/src/lib.rs"]);
+        handle_input(
+            ctx.handler_ctx(),
+            &config,
+            &opened_pr_event(pr),
+            AssignInput::Opened { draft: false },
+        )
+        .await?;
+        assert!(proposal::has_proposal(ctx.handler_ctx(), &pull_request().call()).await?);
+
+        let event = comment_event(pull_request().call(), user("jyn", 3), "r? diana");
+        handle_command(
+            ctx.handler_ctx(),
+            &config,
+            &event,
+            AssignCommand::RequestReview {
+                name: "diana".to_string(),
+                others: vec![],
+                shadow: None,
+            },
+        )
+        .await?;
+        assert_eq!(mock.added_assignees(), ["diana"]);
+
+        // The proposal can't replace the reviewer who was picked by hand.
+        assert!(!proposal::has_proposal(ctx.handler_ctx(), &pull_request().call()).await?);
+        let event = comment_event(pull_request().call(), user("martin", 2), "@rustbot accept");
+        assert!(
+            handle_command(ctx.handler_ctx(), &config, &event, AssignCommand::Accept)
+                .await
+                .is_err()
+        );
+        assert_eq!(mock.added_assignees(), ["diana"]);
+        Ok(ctx)
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn declined_by_all_owners_picks_fallback_reviewer() {
+    let config: AssignConfig = toml::toml!(
+        [adhoc_groups]
+        fallback = ["jana"]
+        [owners]
+        "/compiler" = ["compiler"]
+    )
+    .try_into()
+    .unwrap();
+    run_db_test(|mut ctx| async move {
+        let mock = ctx.mock_github().await;
+        mock.set_teams(&[("compiler", &["martin", "diana"])]);
+        respond_pr(&mock, false);
+        set_diff(
+            &mock,
+            &mut pull_request().call(),
+            &["compiler/rustc_parse/src/lib.rs"],
+        );
+
+        let pr = |assignee: User| pull_request().assignees(vec![assignee]).call();
+
+        let event = comment_event(pr(user("martin", 2)), user("martin", 2), "@rustbot not-me");
+        handle_command(ctx.handler_ctx(), &config, &event, AssignCommand::NotMe).await?;
+        assert_eq!(mock.added_assignees(), ["diana"]);
+
+        let event = comment_event(pr(user("diana", 3)), user("diana", 3), "@rustbot not-me");
+        handle_command(ctx.handler_ctx(), &config, &event, AssignCommand::NotMe).await?;
+        assert_eq!(mock.added_assignees(), ["diana", "jana"]);
+        assert_eq!(
+            mock.posted_comments(),
+            [messages::fallback_after_decline_message("jana")]
+        );
+        Ok(ctx)
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn stale_review_is_reassigned() {
+    let config: AssignConfig = toml::toml!(
+        auto_reassign_days = 7
+        [owners]
+        "/compiler" = ["compiler"]
+    )
+    .try_into()
+    .unwrap();
+    run_db_test(|mut ctx| async move {
+        let mock = ctx.mock_github().await;
+        mock.set_teams(&[("compiler", &["martin", "diana"])]);
+
+        let mut pr = pull_request().assignees(vec![user("martin", 2)]).call();
+        set_diff(&mock, &mut pr, &["compiler/rustc_parse/src/lib.rs"]);
+        pr.created_at = chrono::Utc::now() - chrono::Duration::days(3);
+        let replacement = auto_reassign::reassign_if_stale(ctx.handler_ctx(), &config, &pr, 7);
+        assert_eq!(replacement.await?, None);
+        assert!(mock.added_assignees().is_empty());
+
+        pr.created_at = chrono::Utc::now() - chrono::Duration::days(10);
+        let replacement = auto_reassign::reassign_if_stale(ctx.handler_ctx(), &config, &pr, 7);
+        assert_eq!(replacement.await?.as_deref(), Some("diana"));
+        assert_eq!(mock.added_assignees(), ["diana"]);
+        assert_eq!(
+            mock.posted_comments(),
+            [messages::auto_reassign_message("martin", "diana", 7)]
+        );
+
+        // Diana was just assigned.
+        pr.assignees = vec![user("diana", 3)];
+        let replacement = auto_reassign::reassign_if_stale(ctx.handler_ctx(), &config, &pr, 7);
+        assert_eq!(replacement.await?, None);
+        Ok(ctx)
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn show_assign_config() {
+    let config: AssignConfig = toml::toml!(
+        users_on_vacation = ["jyn"]
+        [owners]
+        "/compiler" = ["compiler"]
+    )
+    .try_into()
+    .unwrap();
+    run_db_test(|mut ctx| async move {
+        let mock = ctx.mock_github().await;
+        mock.set_teams(&[("compiler", &["martin"])]);
+
+        let event = comment_event(
+            pull_request().call(),
+            user("diana", 3),
+            "@rustbot config show assign",
+        );
+        assert!(
+            handle_command(
+                ctx.handler_ctx(),
+                &config,
+                &event,
+                AssignCommand::ShowConfig
+            )
+            .await
+            .is_err()
+        );
+        assert!(mock.posted_comments().is_empty());
+
+        let event = comment_event(
+            pull_request().call(),
+            user("martin", 2),
+            "@rustbot config show assign",
+        );
+        handle_command(
+            ctx.handler_ctx(),
+            &config,
+            &event,
+            AssignCommand::ShowConfig,
+        )
+        .await?;
+        let comments = mock.posted_comments();
+        assert_eq!(comments.len(), 1);
+        assert!(comments[0].contains("users_on_vacation = [\"jyn\"]"));
+        assert!(comments[0].contains("[owners]\n\"/compiler\" = [\"compiler\"]"));
+        Ok(ctx)
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn owners_of_modified_files() {
+    let config: AssignConfig = toml::toml!(
+        [owners]
+        "/compiler" = ["compiler"]
+        "/compiler/rustc_middle" = ["compiler", "oli"]
+        "/library" = ["libs"]
+    )
+    .try_into()
+    .unwrap();
+    run_db_test(|mut ctx| async move {
+        let mock = ctx.mock_github().await;
+        respond_pr(&mock, false);
+        set_diff(
+            &mock,
+            &mut pull_request().call(),
+            &["compiler/rustc_middle/src/lib.rs"],
+        );
+
+        // Like in real comment events, the PR has no base/head commits.
+        let event = comment_event(pull_request().call(), user("martin", 2), "@rustbot owners");
+        handle_command(ctx.handler_ctx(), &config, &event, AssignCommand::Owners).await?;
+        assert_eq!(
+            mock.posted_comments(),
+            [
+                "The files modified by this pull request match the following `owners` patterns:\n\n\
+                 - `/compiler/rustc_middle`: `compiler`, `oli`"
+            ]
+        );
+        Ok(ctx)
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn preview_owners_of_path() {
+    let config: AssignConfig = toml::toml!(
+        [owners]
+        "/compiler" = ["compiler"]
+        "/compiler/rustc_middle" = ["compiler", "oli"]
+    )
+    .try_into()
+    .unwrap();
+    run_db_test(|mut ctx| async move {
+        let mock = ctx.mock_github().await;
+        mock.set_teams(&[("compiler", &["martin", "jyn"])]);
+
+        let command = |path: &str| AssignCommand::PathOwners {
+            path: path.to_string(),
+        };
+        let event = comment_event(
+            pull_request().call(),
+            user("diana", 3),
+            "@rustbot assign? compiler/rustc_middle/src/lib.rs",
+        );
+        handle_command(
+            ctx.handler_ctx(),
+            &config,
+            &event,
+            command("compiler/rustc_middle/src/lib.rs"),
+        )
+        .await?;
+        assert!(mock.posted_comments()[0].contains("Only Rust team members"));
+
+        let event = comment_event(
+            pull_request().call(),
+            user("martin", 2),
+            "@rustbot assign? compiler/rustc_middle/src/lib.rs",
+        );
+        handle_command(
+            ctx.handler_ctx(),
+            &config,
+            &event,
+            command("compiler/rustc_middle/src/lib.rs"),
+        )
+        .await?;
+        handle_command(ctx.handler_ctx(), &config, &event, command("library/core")).await?;
+        let comments = mock.posted_comments();
+        assert_eq!(
+            comments[1],
+            "`compiler/rustc_middle/src/lib.rs` matches the following `owners` patterns:\n\n\
+             - `/compiler/rustc_middle`: `compiler`, `oli`\n\n\
+             Reviewer pool: `jyn`, `martin`, `oli`"
+        );
+        assert_eq!(comments[2], "No `owners` pattern matches `library/core`.");
+        // Looking up a path does not assign anybody.
+        assert!(mock.added_assignees().is_empty());
+        Ok(ctx)
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn team_member_claims_issue_for_another_user() {
+    let config: AssignConfig = toml::Table::new().try_into().unwrap();
+    run_db_test(|mut ctx| async move {
+        let mock = ctx.mock_github().await;
+        mock.set_teams(&[("compiler", &["martin"])]);
+
+        let event = comment_event(issue().call(), user("martin", 2), "@rustbot assign @diana");
+        handle_command(
+            ctx.handler_ctx(),
+            &config,
+            &event,
+            AssignCommand::AssignUser {
+                username: "diana".to_string(),
+            },
+        )
+        .await?;
+
+        assert_eq!(mock.added_assignees(), ["diana"]);
+        let body = mock
+            .requests()
+            .into_iter()
+            .find(|req| req.method == Method::PATCH)
+            .expect("the issue body was not edited")
+            .body;
+        assert!(
+            body["body"]
+                .as_str()
+                .unwrap()
+                .contains("This issue has been claimed for @diana by @martin")
+        );
+        Ok(ctx)
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn claim_is_ignored_when_assigned_through_the_bot() {
+    let config: AssignConfig = toml::Table::new().try_into().unwrap();
+    run_db_test(|mut ctx| async move {
+        let mock = ctx.mock_github().await;
+        mock.set_teams(&[("compiler", &["martin"])]);
+        // diana could not be assigned on GitHub, so the bot assigned itself.
+        let issue = issue().assignees(vec![user("triagebot-test", 100)]).call();
+        let mut state: IssueData<'_, AssignData> =
+            IssueData::load(ctx.db_client_mut(), &issue, "ASSIGN").await?;
+        state.data.user = Some("diana".to_string());
+        state.save().await?;
+
+        let event = comment_event(issue, user("diana", 3), "@rustbot claim");
+        let cmd = AssignCommand::Claim { force: false };
+        handle_command(ctx.handler_ctx(), &config, &event, cmd).await?;
+
+        assert!(mock.added_assignees().is_empty());
+        Ok(ctx)
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn claim_beyond_capacity() {
+    let config: AssignConfig = toml::toml!(review_prefs = {}).try_into().unwrap();
+    run_db_test(|mut ctx| async move {
+        let mock = ctx.mock_github().await;
+        let martin = user("martin", 2);
+        upsert_review_prefs(
+            ctx.db_client(),
+            martin.clone(),
+            Some(1),
+            RotationMode::OnRotation,
+        )
+        .await?;
+        let pr = AssignedPullRequest {
+            title: "PR 2".to_string(),
+            updated_at: chrono::Utc::now(),
+        };
+        *ctx.handler_ctx().workqueue.write().await =
+            ReviewerWorkqueue::new(HashMap::from([(martin.id, HashMap::from([(2, pr)]))]));
+
+        let event = comment_event(pull_request().call(), martin.clone(), "@rustbot claim");
+        let cmd = AssignCommand::Claim { force: false };
+        handle_command(ctx.handler_ctx(), &config, &event, cmd).await?;
+        assert!(mock.added_assignees().is_empty());
+        assert_eq!(
+            mock.posted_comments(),
+            [messages::SELF_ASSIGN_HAS_NO_CAPACITY]
+        );
+
+        let event = comment_event(pull_request().call(), martin, "@rustbot claim --force");
+        let cmd = AssignCommand::Claim { force: true };
+        handle_command(ctx.handler_ctx(), &config, &event, cmd).await?;
+        assert_eq!(mock.added_assignees(), ["martin"]);
+        Ok(ctx)
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn assignment_is_retried() {
+    let config: AssignConfig = toml::Table::new().try_into().unwrap();
+    run_db_test(|mut ctx| async move {
+        let mock = ctx.mock_github().await;
+        let assignees = "/repos/rust-lang/rust/issues/1/assignees";
+        mock.fail(Method::POST, assignees, 2);
+        let event = comment_event(pull_request().call(), user("martin", 2), "@rustbot claim");
+        let cmd = AssignCommand::Claim { force: false };
+        handle_command(ctx.handler_ctx(), &config, &event, cmd).await?;
+        assert_eq!(mock.added_assignees(), ["martin", "martin", "martin"]);
+        assert!(mock.posted_comments().is_empty());
+
+        // The failure is only reported once all the attempts failed.
+        mock.fail(Method::POST, assignees, 3);
+        let event = comment_event(pull_request().call(), user("diana", 3), "@rustbot claim");
+        let cmd = AssignCommand::Claim { force: false };
+        handle_command(ctx.handler_ctx(), &config, &event, cmd).await?;
+        assert_eq!(mock.added_assignees()[3..], ["diana", "diana", "diana"]);
+        let comments = mock.posted_comments();
+        assert_eq!(comments.len(), 1);
+        assert!(comments[0].starts_with("Failed to set assignee to `diana`"));
+        Ok(ctx)
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn strict_alternation_moves_on_assignment() {
+    let config: AssignConfig = toml::toml!(
+        selection_mode = "strict-alternate"
+        [owners]
+        "/compiler" = ["compiler"]
+    )
+    .try_into()
+    .unwrap();
+    run_db_test(|mut ctx| async move {
+        let mock = ctx.mock_github().await;
+        mock.set_teams(&[("compiler", &["martin", "diana"])]);
+        // The assignment of the first PR fails.
+        mock.fail(Method::POST, "/repos/rust-lang/rust/issues/1/assignees", 3);
+
+        for number in [1, 2, 3] {
+            let mut pr = pull_request().number(number).call();
+            set_diff(&mock, &mut pr, &["compiler/rustc_parse/src/lib.rs"]);
+            handle_input(
+                ctx.handler_ctx(),
+                &config,
+                &opened_pr_event(pr),
+                AssignInput::Opened { draft: false },
+            )
+            .await?;
+        }
+
+        // The rotation did not move on when the first assignment failed.
+        assert_eq!(
+            mock.added_assignees(),
+            ["diana", "diana", "diana", "diana", "martin"]
+        );
+        Ok(ctx)
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn assignment_events_are_recorded() {
+    let config: AssignConfig = toml::toml!(
+        [owners]
+        "/compiler" = ["compiler"]
+    )
+    .try_into()
+    .unwrap();
+    run_db_test(|mut ctx| async move {
+        let mock = ctx.mock_github().await;
+        mock.set_teams(&[("compiler", &["martin"])]);
+
+        let mut pr = pull_request().call();
+        set_diff(&mock, &mut pr, &["compiler/rustc_parse/src/lib.rs"]);
+        handle_input(
+            ctx.handler_ctx(),
+            &config,
+            &opened_pr_event(pr),
+            AssignInput::Opened { draft: false },
+        )
+        .await?;
+
+        let event = comment_event(pull_request().call(), user("diana", 3), "r? @jana");
+        let cmd = AssignCommand::RequestReview {
+            name: "jana".to_string(),
+            others: vec![],
+            shadow: None,
+        };
+        handle_command(ctx.handler_ctx(), &config, &event, cmd).await?;
+
+        let events = get_recent_assignment_events(ctx.db_client(), "rust-lang/rust", 10).await?;
+        let events: Vec<_> = events
+            .iter()
+            .map(|event| (event.pr, event.assignee.as_str(), event.source))
+            .collect();
+        assert_eq!(
+            events,
+            [
+                (1, "jana", AssignmentSource::ReviewRequest),
+                (1, "martin", AssignmentSource::Diff),
+            ]
+        );
+        Ok(ctx)
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn draft_pr_is_assigned_once_ready_for_review() {
+    let config: AssignConfig = toml::toml!(
+        [owners]
+        "/compiler" = ["compiler"]
+    )
+    .try_into()
+    .unwrap();
+    run_db_test(|mut ctx| async move {
+        let mock = ctx.mock_github().await;
+        mock.set_teams(&[("compiler", &["martin"])]);
+
+        let mut pr = pull_request().call();
+        set_diff(&mock, &mut pr, &["compiler/rustc_parse/src/lib.rs"]);
+        let event = opened_pr_event(pr);
+        handle_input(
+            ctx.handler_ctx(),
+            &config,
+            &event,
+            AssignInput::Opened { draft: true },
+        )
+        .await?;
+        assert!(mock.added_assignees().is_empty());
+        assert!(mock.posted_comments().is_empty());
+
+        // The reviewer unassigned themselves in the meantime, so the PR has
+        // no assignee when it is marked as ready for review again.
+        let event = IssuesEvent {
+            action: IssuesAction::ReadyForReview,
+            ..event
+        };
+        for _ in 0..2 {
+            handle_input(
+                ctx.handler_ctx(),
+                &config,
+                &event,
+                AssignInput::ReadyForReview,
+            )
+            .await?;
+        }
+        assert_eq!(mock.added_assignees(), ["martin"]);
+        assert_eq!(mock.posted_comments().len(), 1);
+        Ok(ctx)
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn welcome_is_posted_once() {
+    let config: AssignConfig = toml::toml!(
+        [owners]
+        "/compiler" = ["compiler"]
+    )
+    .try_into()
+    .unwrap();
+    run_db_test(|mut ctx| async move {
+        let mock = ctx.mock_github().await;
+        mock.set_teams(&[("compiler", &["martin"])]);
+
+        let mut pr = pull_request().call();
+        set_diff(&mock, &mut pr, &["compiler/rustc_parse/src/lib.rs"]);
+        let event = opened_pr_event(pr);
+        // The PR still has no assignee when the opening is handled again.
+        for _ in 0..2 {
+            handle_input(
+                ctx.handler_ctx(),
+                &config,
+                &event,
+                AssignInput::Opened { draft: false },
+            )
+            .await?;
+        }
+        assert_eq!(mock.added_assignees(), ["martin", "martin"]);
+        assert_eq!(mock.posted_comments().len(), 1);
+        Ok(ctx)
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn rollup_is_not_assigned_when_ready_for_review() {
+    let config: AssignConfig = toml::toml!(
+        [owners]
+        "/compiler" = ["compiler"]
+    )
+    .try_into()
+    .unwrap();
+    run_db_test(|mut ctx| async move {
+        let mock = ctx.mock_github().await;
+        mock.set_teams(&[("compiler", &["martin"])]);
+
+        let pr = pull_request().assignees(vec![user("martin", 2)]).call();
+        let event = comment_event(pr, default_test_user(), "@rustbot assign rollup");
+        handle_command(ctx.handler_ctx(), &config, &event, AssignCommand::Rollup).await?;
+
+        assert!(
+            mock.requests()
+                .iter()
+                .any(|req| req.method == Method::DELETE && req.path.ends_with("/assignees"))
+        );
+        assert_eq!(
+            mock.posted_comments(),
+            [messages::no_reviewer_message(&default_test_user().login)]
+        );
+
+        let mut pr = pull_request().call();
+        set_diff(&mock, &mut pr, &["compiler/rustc_parse/src/lib.rs"]);
+        let event = IssuesEvent {
+            action: IssuesAction::ReadyForReview,
+            ..opened_pr_event(pr)
+        };
+        handle_input(
+            ctx.handler_ctx(),
+            &config,
+            &event,
+            AssignInput::ReadyForReview,
+        )
+        .await?;
+        assert!(mock.added_assignees().is_empty());
+        Ok(ctx)
+    })
+    .await;
+}
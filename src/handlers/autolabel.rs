@@ -1,7 +1,7 @@
 use crate::{
     config::AutolabelConfig,
     github::{IssuesAction, IssuesEvent, Label},
-    handlers::Context,
+    handlers::{Context, ParseInputError},
 };
 use anyhow::Context as _;
 use tracing as log;
@@ -15,7 +15,7 @@ pub(super) async fn parse_input(
     ctx: &Context,
     event: &IssuesEvent,
     config: Option<&AutolabelConfig>,
-) -> Result<Option<AutolabelInput>, String> {
+) -> Result<Option<AutolabelInput>, ParseInputError> {
     let config = match config {
         Some(config) => config,
         None => return Ok(None),
@@ -3,7 +3,7 @@ use std::sync::LazyLock;
 
 use crate::config::BackportConfig;
 use crate::github::{IssuesAction, IssuesEvent, Label};
-use crate::handlers::Context;
+use crate::handlers::{Context, ParseInputError};
 use anyhow::Context as AnyhowContext;
 use futures::future::join_all;
 use regex::Regex;
@@ -44,7 +44,7 @@ pub(super) async fn parse_input(
     _ctx: &Context,
     event: &IssuesEvent,
     config: Option<&BackportConfig>,
-) -> Result<Option<BackportInput>, String> {
+) -> Result<Option<BackportInput>, ParseInputError> {
     let config = match config {
         Some(config) => config,
         None => return Ok(None),
@@ -103,13 +103,17 @@ pub(super) async fn parse_input(
     for caps in CLOSES_ISSUE_REGEXP.captures_iter(&event.issue.body) {
         let id = caps
             .name("issue_num")
-            .ok_or_else(|| format!("failed to get issue_num from {caps:?}"))?
+            .ok_or_else(|| {
+                ParseInputError::Internal(format!("failed to get issue_num from {caps:?}"))
+            })?
             .as_str();
 
         let id = match id.parse::<u64>() {
             Ok(id) => id,
             Err(err) => {
-                return Err(format!("Failed to parse issue id `{id}`, error: {err}"));
+                return Err(ParseInputError::InvalidInput(format!(
+                    "Failed to parse issue id `{id}`, error: {err}"
+                )));
             }
         };
         if let Some(org_repo) = caps.name("org_repo")
@@ -14,7 +14,7 @@ use crate::{
 #[cfg(test)]
 use crate::github::GithubCommit;
 
-mod behind_upstream;
+pub mod behind_upstream;
 mod issue_links;
 mod modified_submodule;
 mod no_mentions;
@@ -37,6 +37,21 @@ struct CheckCommitsState {
     last_warned_comment: Option<String>,
     /// List of the last labels added.
     last_labels: Vec<String>,
+    /// Whether the behind upstream warning was snoozed with `@rustbot behind snooze`.
+    #[serde(default)]
+    behind_upstream_snoozed: bool,
+    /// The last behind upstream warning, whether it was posted or not.
+    #[serde(default)]
+    last_behind_upstream_warning: Option<String>,
+    /// Age threshold in days of the behind upstream warning for this PR, set with
+    /// `@rustbot behind threshold`. Overrides the repository `days-threshold`.
+    #[serde(default)]
+    behind_upstream_days_threshold: Option<usize>,
+    /// Date of the merge base commit when the PR was last checked. It only
+    /// changes on pushes, so the behind upstream sweep can tell from it which
+    /// PRs may have fallen behind since.
+    #[serde(default)]
+    merge_base_date: Option<chrono::DateTime<chrono::Utc>>,
 }
 
 fn should_handle_event(event: &IssuesEvent) -> bool {
@@ -90,6 +105,7 @@ pub(super) async fn handle(ctx: &Context, event: &Event, config: &Config) -> any
     let mut errors = Vec::new();
     let mut warnings = Vec::new();
     let mut labels = Vec::new();
+    let mut behind_upstream_warning = None;
 
     // Compute the warnings
     if let Some(assign_config) = &config.assign {
@@ -124,14 +140,33 @@ pub(super) async fn handle(ctx: &Context, event: &Event, config: &Config) -> any
 
     // Check if PR is behind upstream branch by a significant number of days
     if let Some(behind_upstream) = &config.behind_upstream {
-        let age_threshold = behind_upstream
-            .days_threshold
-            .unwrap_or(behind_upstream::DEFAULT_DAYS_THRESHOLD);
-
-        if let Some(warning) =
-            behind_upstream::behind_upstream(age_threshold, event, &compare).await
+        let threshold_override = {
+            let mut db = ctx.db.get().await;
+            let state: IssueData<'_, CheckCommitsState> =
+                IssueData::load(&mut db, &event.issue, CHECK_COMMITS_KEY).await?;
+            state.data.behind_upstream_days_threshold
+        };
+        let age_threshold = behind_upstream::age_threshold(behind_upstream, threshold_override);
+
+        if let Some(warning) = behind_upstream::behind_upstream(
+            &ctx.github,
+            age_threshold,
+            behind_upstream.message.as_deref(),
+            &event.issue,
+            &event.repository,
+            &compare,
+        )
+        .await
+        .context("checking if the PR is behind upstream")?
         {
-            warnings.push(warning);
+            // The label is handled like the other labels, so it is removed
+            // once the PR is no longer behind upstream.
+            labels.extend(behind_upstream.label.clone());
+            behind_upstream_warning = Some(behind_upstream::with_mention(
+                behind_upstream,
+                &event.issue,
+                warning,
+            ));
         }
     }
 
@@ -142,7 +177,22 @@ pub(super) async fn handle(ctx: &Context, event: &Event, config: &Config) -> any
             .context("validating the the triagebot config")?,
     );
 
-    handle_new_state(ctx, event, errors, warnings, labels).await
+    let merge_base_date = compare
+        .merge_base_commit
+        .commit
+        .author
+        .date
+        .with_timezone(&chrono::Utc);
+    handle_new_state(
+        ctx,
+        event,
+        errors,
+        warnings,
+        behind_upstream_warning,
+        labels,
+        merge_base_date,
+    )
+    .await
 }
 
 // Add, hide or hide&add a comment with the warnings.
@@ -150,13 +200,23 @@ async fn handle_new_state(
     ctx: &Context,
     event: &IssuesEvent,
     errors: Vec<String>,
-    warnings: Vec<String>,
+    mut warnings: Vec<String>,
+    behind_upstream_warning: Option<String>,
     labels: Vec<String>,
+    merge_base_date: chrono::DateTime<chrono::Utc>,
 ) -> anyhow::Result<()> {
     // Get the state of the warnings for this PR in the database.
     let mut db = ctx.db.get().await;
     let mut state: IssueData<'_, CheckCommitsState> =
         IssueData::load(&mut db, &event.issue, CHECK_COMMITS_KEY).await?;
+    state.data.merge_base_date = Some(merge_base_date);
+
+    // The behind upstream warning stays silent while snoozed, but we still
+    // remember it so that it can be removed when it gets snoozed.
+    if !state.data.behind_upstream_snoozed {
+        warnings.extend(behind_upstream_warning.clone());
+    }
+    state.data.last_behind_upstream_warning = behind_upstream_warning;
 
     // Handles the errors, post the new ones, hide resolved ones and don't touch the one still active
     if !state.data.last_errors.is_empty() || !errors.is_empty() {
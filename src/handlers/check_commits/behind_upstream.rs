@@ -1,16 +1,67 @@
-use crate::github::{GithubCompare, IssuesEvent};
+use super::{CHECK_COMMITS_KEY, CheckCommitsState, warning_from_warnings};
+use crate::config::BehindUpstreamConfig;
+use crate::db::issue_data::IssueData;
+use crate::github::{
+    Event, GithubClient, GithubCompare, Issue, Label, Query, ReportedContentClassifiers, Repository,
+};
+use crate::handlers::Context;
+use crate::jobs::Job;
+use anyhow::Context as _;
+use async_trait::async_trait;
+use parser::command::behind::BehindCommand;
+use serde::{Deserialize, Serialize};
 use tracing as log;
 
 /// Default threshold for parent commit age in days to trigger a warning
 pub(super) const DEFAULT_DAYS_THRESHOLD: usize = 7;
 
+/// Returns the age threshold in days of the behind upstream warning of a PR,
+/// given the threshold set on the PR with `@rustbot behind threshold`, if any.
+pub(super) fn age_threshold(config: &BehindUpstreamConfig, pr_threshold: Option<usize>) -> usize {
+    pr_threshold
+        .or(config.days_threshold)
+        .unwrap_or(DEFAULT_DAYS_THRESHOLD)
+}
+
 /// Check if the PR is based on an old parent commit
+///
+/// The warning uses the `message` template if set, see [`BehindUpstreamConfig::message`].
 pub(super) async fn behind_upstream(
+    client: &GithubClient,
     age_threshold: usize,
-    event: &IssuesEvent,
+    message: Option<&str>,
+    pr: &Issue,
+    repository: &Repository,
     compare: &GithubCompare,
-) -> Option<String> {
-    log::debug!("Checking if PR #{} is behind upstream", event.issue.number);
+) -> anyhow::Result<Option<String>> {
+    log::debug!("Checking if PR #{} is behind upstream", pr.number);
+
+    // If the base branch was renamed or removed, the age of the parent commit
+    // is meaningless, the PR needs to be re-targeted instead. Should GitHub
+    // fail to tell, the branch is assumed to exist.
+    if let Some(base) = &pr.base
+        && !repository
+            .has_branch(client, &base.git_ref)
+            .await
+            .unwrap_or_else(|err| {
+                log::warn!(
+                    "cannot check if the base branch of PR #{} exists: {err:?}",
+                    pr.number
+                );
+                true
+            })
+    {
+        log::info!(
+            "PR #{} targets the branch `{}` which no longer exists",
+            pr.number,
+            base.git_ref
+        );
+        return Ok(Some(format!(
+            "This PR targets the `{}` branch, which no longer exists. \
+Please re-target it, e.g. to the `{}` branch.",
+            base.git_ref, repository.default_branch
+        )));
+    }
 
     // Compute the number of days old the merge base commit is
     let commit_date = compare.merge_base_commit.commit.author.date;
@@ -23,18 +74,282 @@ pub(super) async fn behind_upstream(
     if days_old > age_threshold {
         log::info!(
             "PR #{} has a parent commit that is {} days old",
-            event.issue.number,
+            pr.number,
             days_old
         );
 
-        Some(format!(
+        if let Some(message) = message {
+            return Ok(Some(
+                message
+                    .replace("{days_old}", &days_old.to_string())
+                    .replace("{default_branch}", &repository.default_branch),
+            ));
+        }
+        Ok(Some(format!(
             r"This PR is based on an [upstream commit]({upstream_commit_url}) that is {days_old} days old.
 
 *It's recommended to update your branch according to the [rustc-dev-guide](https://rustc-dev-guide.rust-lang.org/contributing.html#keeping-your-branch-up-to-date).*",
-        ))
+        )))
     } else {
         // Parent commit is not too old, log and do nothing
-        log::debug!("PR #{} parent commit is not too old", event.issue.number);
-        None
+        log::debug!("PR #{} parent commit is not too old", pr.number);
+        Ok(None)
+    }
+}
+
+/// Adds the `@` mention of the PR author to a behind upstream `warning`, if configured.
+pub(super) fn with_mention(config: &BehindUpstreamConfig, pr: &Issue, warning: String) -> String {
+    if config.mention_author {
+        format!("@{} {warning}", pr.user.login)
+    } else {
+        warning
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct BehindUpstreamSweepMetadata {
+    /// Repositories whose open PRs are re-checked, e.g. `rust-lang/rust`.
+    pub repos: Vec<String>,
+}
+
+/// A scheduled job that re-checks whether the open PRs are behind upstream.
+///
+/// The check normally runs when a PR is pushed to, but a PR that is not
+/// updated also falls behind as the upstream branch moves. PRs that were
+/// already warned are skipped, their next push updates the warning.
+pub struct BehindUpstreamSweepJob;
+
+#[async_trait]
+impl Job for BehindUpstreamSweepJob {
+    fn name(&self) -> &'static str {
+        "behind_upstream_sweep"
+    }
+
+    async fn run(&self, ctx: &Context, metadata: &serde_json::Value) -> anyhow::Result<()> {
+        let metadata: BehindUpstreamSweepMetadata = serde_json::from_value(metadata.clone())
+            .context("unable to deserialize the metadata in behind upstream sweep job")?;
+
+        for repo in &metadata.repos {
+            let repository = ctx.github.repository(repo).await?;
+            let config = match crate::config::get(&ctx.github, &repository).await {
+                Ok(config) => config,
+                Err(err) => {
+                    log::warn!("cannot load the config of {repo}: {err:?}");
+                    continue;
+                }
+            };
+            let Some(behind_upstream) = &config.behind_upstream else {
+                continue;
+            };
+            let query = Query {
+                filters: vec![
+                    ("state", "open"),
+                    ("is", "pull-request"),
+                    ("draft", "false"),
+                ],
+                include_labels: vec![],
+                exclude_labels: vec![],
+            };
+            for pr in repository.get_issues(&ctx.github, &query).await? {
+                if pr.title.starts_with("Rollup of") {
+                    continue;
+                }
+                if let Err(err) = recheck_pr(ctx, behind_upstream, &repository, pr.number).await {
+                    log::warn!(
+                        "cannot re-check if {repo}#{} is behind upstream: {err:?}",
+                        pr.number
+                    );
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Warns if the PR `number` went behind upstream since it was last checked.
+///
+/// The PR is only fetched from GitHub if its stored state shows that it may
+/// have fallen behind, and the database is not held during the GitHub calls.
+async fn recheck_pr(
+    ctx: &Context,
+    config: &BehindUpstreamConfig,
+    repository: &Repository,
+    number: u64,
+) -> anyhow::Result<()> {
+    let initial_data = {
+        let mut db = ctx.db.get().await;
+        let state: IssueData<'_, CheckCommitsState> =
+            IssueData::load_by_number(&mut db, &repository.full_name, number, CHECK_COMMITS_KEY)
+                .await?;
+        state.data
+    };
+    if initial_data.last_behind_upstream_warning.is_some() {
+        return Ok(());
     }
+    let age_threshold = age_threshold(config, initial_data.behind_upstream_days_threshold);
+    // The merge base only changes on pushes, which record its date, so the PR
+    // can't be behind before that date gets older than the threshold.
+    if let Some(merge_base_date) = initial_data.merge_base_date
+        && (chrono::Utc::now() - merge_base_date).num_days() as usize <= age_threshold
+    {
+        return Ok(());
+    }
+
+    // The issues API does not return the base and head commits of PRs.
+    let pr = repository.get_pr(&ctx.github, number).await?;
+    let Some(compare) = pr.compare(&ctx.github).await? else {
+        return Ok(());
+    };
+    let Some(warning) = behind_upstream(
+        &ctx.github,
+        age_threshold,
+        config.message.as_deref(),
+        &pr,
+        repository,
+        compare,
+    )
+    .await?
+    else {
+        return Ok(());
+    };
+    let warning = with_mention(config, &pr, warning);
+    let mut data = initial_data.clone();
+    data.last_behind_upstream_warning = Some(warning.clone());
+
+    // The label is removed with the other labels, on the next push that
+    // brings the PR up to date.
+    if let Some(label) = &config.label
+        && !data.last_labels.contains(label)
+    {
+        pr.add_labels(
+            &ctx.github,
+            vec![Label {
+                name: label.clone(),
+            }],
+        )
+        .await
+        .context("failed to add the behind upstream label")?;
+        data.last_labels.push(label.clone());
+    }
+
+    // Like on pushes, the warning stays silent while snoozed.
+    if !data.behind_upstream_snoozed {
+        data.last_warnings.push(warning);
+        if let Some(last_warned_comment_id) = data.last_warned_comment.take() {
+            pr.hide_comment(
+                &ctx.github,
+                &last_warned_comment_id,
+                ReportedContentClassifiers::Resolved,
+            )
+            .await?;
+        }
+        let warning = warning_from_warnings(&data.last_warnings);
+        let comment = pr.post_comment(&ctx.github, &warning).await?;
+        data.last_warned_comment = Some(comment.node_id);
+    }
+
+    let mut db = ctx.db.get().await;
+    let mut state: IssueData<'_, CheckCommitsState> =
+        IssueData::load(&mut db, &pr, CHECK_COMMITS_KEY).await?;
+    // A push handled in the meantime has the most recent state.
+    if state.data != initial_data {
+        log::info!(
+            "{} was checked again during the behind upstream sweep",
+            pr.global_id()
+        );
+        return Ok(());
+    }
+    state.data = data;
+    state.save().await?;
+    Ok(())
+}
+
+/// Handles `@rustbot behind snooze`, `@rustbot behind unsnooze` and
+/// `@rustbot behind threshold <days>`.
+///
+/// Snoozing removes the behind upstream warning from the current warnings
+/// comment and keeps it quiet on later pushes, until it is unsnoozed.
+///
+/// Setting a threshold, which is reserved to team members, replaces the
+/// repository threshold for the later checks of this PR.
+pub(crate) async fn handle_command(
+    ctx: &Context,
+    _config: &BehindUpstreamConfig,
+    event: &Event,
+    cmd: BehindCommand,
+) -> anyhow::Result<()> {
+    let issue = event.issue().unwrap();
+    if !issue.is_pr() {
+        issue
+            .post_comment(
+                &ctx.github,
+                "The `behind` command is only available on pull requests.",
+            )
+            .await?;
+        return Ok(());
+    }
+
+    let user = event.user();
+    let is_team_member = user.is_team_member(&ctx.team).await.ok().unwrap_or(false);
+    if matches!(cmd, BehindCommand::Threshold(_)) && !is_team_member {
+        issue
+            .post_comment(
+                &ctx.github,
+                "Only team members may change the `behind` threshold.",
+            )
+            .await?;
+        return Ok(());
+    }
+    if user.login != issue.user.login && !is_team_member {
+        issue
+            .post_comment(
+                &ctx.github,
+                "Only the PR author and team members may use the `behind` command.",
+            )
+            .await?;
+        return Ok(());
+    }
+
+    let mut db = ctx.db.get().await;
+    let mut state: IssueData<'_, CheckCommitsState> =
+        IssueData::load(&mut db, issue, CHECK_COMMITS_KEY).await?;
+
+    match cmd {
+        BehindCommand::Snooze => {
+            state.data.behind_upstream_snoozed = true;
+
+            if let Some(warning) = state.data.last_behind_upstream_warning.clone()
+                && state.data.last_warnings.contains(&warning)
+            {
+                state.data.last_warnings.retain(|w| w != &warning);
+
+                // Replace the warnings comment with one without the snoozed warning.
+                if let Some(last_warned_comment_id) = state.data.last_warned_comment.take() {
+                    issue
+                        .hide_comment(
+                            &ctx.github,
+                            &last_warned_comment_id,
+                            ReportedContentClassifiers::Resolved,
+                        )
+                        .await?;
+                }
+                if !state.data.last_warnings.is_empty() {
+                    let warning = warning_from_warnings(&state.data.last_warnings);
+                    let comment = issue.post_comment(&ctx.github, &warning).await?;
+                    state.data.last_warned_comment = Some(comment.node_id);
+                }
+            }
+        }
+        BehindCommand::Unsnooze => {
+            // The warning comes back with the next push, if still relevant.
+            state.data.behind_upstream_snoozed = false;
+        }
+        BehindCommand::Threshold(days) => {
+            // Applies from the next push, like the repository threshold.
+            state.data.behind_upstream_days_threshold = Some(days as usize);
+        }
+    }
+
+    state.save().await?;
+    Ok(())
 }
@@ -0,0 +1,108 @@
+//! Bounds the number of events of the same repository that are handled
+//! concurrently.
+//!
+//! A burst of events on a repository (e.g. a mass re-label) would otherwise
+//! run all the handlers at once, hammering the GitHub API and contending on
+//! shared state such as the reviewer workqueue.
+//!
+//! The limit is configured with the `HANDLER_CONCURRENCY_PER_REPO` environment
+//! variable. It is unlimited when the variable is not set, or not a positive
+//! integer.
+
+use std::collections::HashMap;
+use std::num::NonZeroUsize;
+use std::sync::{Arc, Mutex};
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+use tracing as log;
+
+#[derive(Default)]
+pub struct RepoConcurrencyLimiter {
+    /// Maximum number of events handled concurrently for a single repository.
+    limit: Option<NonZeroUsize>,
+    semaphores: Mutex<HashMap<String, Arc<Semaphore>>>,
+}
+
+impl RepoConcurrencyLimiter {
+    pub fn new(limit: Option<NonZeroUsize>) -> Self {
+        Self {
+            limit,
+            semaphores: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn new_from_env() -> Self {
+        let limit = std::env::var("HANDLER_CONCURRENCY_PER_REPO").ok();
+        Self::new(limit.as_deref().and_then(parse_limit))
+    }
+
+    /// Waits until an event of `repo` can be handled.
+    ///
+    /// The returned permit must be held while the event is handled. Returns
+    /// `None` if there is no limit.
+    pub async fn acquire(&self, repo: &str) -> Option<OwnedSemaphorePermit> {
+        let limit = self.limit?;
+        let semaphore = self
+            .semaphores
+            .lock()
+            .unwrap()
+            .entry(repo.to_string())
+            .or_insert_with(|| Arc::new(Semaphore::new(limit.get())))
+            .clone();
+        Some(
+            semaphore
+                .acquire_owned()
+                .await
+                .expect("the semaphore is never closed"),
+        )
+    }
+}
+
+/// Parses the value of `HANDLER_CONCURRENCY_PER_REPO`. A limit of zero would
+/// block all the handlers, so it is rejected like invalid values, which are
+/// ignored with a warning.
+fn parse_limit(limit: &str) -> Option<NonZeroUsize> {
+    match limit.parse() {
+        Ok(limit) => Some(limit),
+        Err(err) => {
+            log::warn!(
+                "ignoring invalid HANDLER_CONCURRENCY_PER_REPO `{limit}`, \
+                 which must be a positive integer: {err}"
+            );
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{RepoConcurrencyLimiter, parse_limit};
+    use std::num::NonZeroUsize;
+
+    #[tokio::test]
+    async fn limit_per_repo() {
+        let limiter = RepoConcurrencyLimiter::new(NonZeroUsize::new(1));
+        let permit = limiter.acquire("rust-lang/rust").await;
+        assert!(permit.is_some());
+
+        // Other repositories are not affected.
+        assert!(limiter.acquire("rust-lang/cargo").await.is_some());
+
+        let semaphore = limiter.semaphores.lock().unwrap()["rust-lang/rust"].clone();
+        assert_eq!(semaphore.available_permits(), 0);
+        drop(permit);
+        assert_eq!(semaphore.available_permits(), 1);
+    }
+
+    #[tokio::test]
+    async fn unlimited() {
+        let limiter = RepoConcurrencyLimiter::new(None);
+        assert!(limiter.acquire("rust-lang/rust").await.is_none());
+    }
+
+    #[test]
+    fn invalid_limit_is_unlimited() {
+        assert_eq!(parse_limit("4"), NonZeroUsize::new(4));
+        assert_eq!(parse_limit("0"), None);
+        assert_eq!(parse_limit("four"), None);
+    }
+}
@@ -12,7 +12,7 @@ use regex::Regex;
 use crate::{
     config::IssueLinksConfig,
     github::{IssuesAction, IssuesEvent},
-    handlers::Context,
+    handlers::{Context, ParseInputError},
 };
 
 static LINKED_RE: LazyLock<Regex> =
@@ -24,7 +24,7 @@ pub(super) async fn parse_input(
     _ctx: &Context,
     event: &IssuesEvent,
     config: Option<&IssueLinksConfig>,
-) -> Result<Option<IssueLinksInput>, String> {
+) -> Result<Option<IssueLinksInput>, ParseInputError> {
     if !event.issue.is_pr() {
         return Ok(None);
     }
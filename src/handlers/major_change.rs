@@ -5,7 +5,7 @@ use crate::zulip::api::Recipient;
 use crate::{
     config::MajorChangeConfig,
     github::{Event, Issue, IssuesAction, IssuesEvent, Label, ZulipGitHubReference},
-    handlers::Context,
+    handlers::{Context, ParseInputError},
     interactions::ErrorComment,
 };
 use anyhow::Context as _;
@@ -29,7 +29,7 @@ pub(super) async fn parse_input(
     _ctx: &Context,
     event: &IssuesEvent,
     config: Option<&MajorChangeConfig>,
-) -> Result<Option<Invocation>, String> {
+) -> Result<Option<Invocation>, ParseInputError> {
     let config = if let Some(config) = config {
         config
     } else {
@@ -6,7 +6,7 @@ use crate::{
     config::{MentionsConfig, MentionsPathConfig},
     db::issue_data::IssueData,
     github::{IssuesAction, IssuesEvent},
-    handlers::Context,
+    handlers::{Context, ParseInputError},
 };
 use anyhow::Context as _;
 use serde::{Deserialize, Serialize};
@@ -29,7 +29,7 @@ pub(super) async fn parse_input(
     ctx: &Context,
     event: &IssuesEvent,
     config: Option<&MentionsConfig>,
-) -> Result<Option<MentionsInput>, String> {
+) -> Result<Option<MentionsInput>, ParseInputError> {
     let config = match config {
         Some(config) => config,
         None => return Ok(None),
@@ -2,7 +2,7 @@ use crate::zulip::api::Recipient;
 use crate::{
     config::{NotifyZulipConfig, NotifyZulipLabelConfig, NotifyZulipTablesConfig},
     github::{Issue, IssuesAction, IssuesEvent, Label},
-    handlers::Context,
+    handlers::{Context, ParseInputError},
 };
 use tracing as log;
 
@@ -30,7 +30,7 @@ pub(super) async fn parse_input(
     _ctx: &Context,
     event: &IssuesEvent,
     config: Option<&NotifyZulipConfig>,
-) -> Result<Option<Vec<NotifyZulipInput>>, String> {
+) -> Result<Option<Vec<NotifyZulipInput>>, ParseInputError> {
     let config = match config {
         Some(config) => config,
         None => return Ok(None),
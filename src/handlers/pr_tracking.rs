@@ -6,14 +6,16 @@
 //! - Adds the PR to the workqueue of one team member (after the PR has been assigned or reopened)
 //! - Removes the PR from the workqueue of one team member (after the PR has been unassigned or closed)
 
+use crate::github::{GithubClient, User, UserId};
 use crate::github::{Label, PullRequestNumber};
-use crate::github::{User, UserId};
 use crate::{
     config::ReviewPrefsConfig,
     github::{IssuesAction, IssuesEvent},
-    handlers::Context,
+    handlers::{Context, ParseInputError},
 };
+use chrono::{DateTime, Utc};
 use futures::TryStreamExt;
+use github_graphql::open_pull_requests::Author;
 use octocrab::Octocrab;
 use octocrab::models::IssueState;
 use octocrab::params::pulls::Sort;
@@ -25,14 +27,16 @@ use tracing as log;
 #[derive(Clone, Debug)]
 pub struct AssignedPullRequest {
     pub title: String,
+    /// When the PR was last updated, as far as triagebot knows.
+    pub updated_at: DateTime<Utc>,
 }
 
 /// Maps users to a set of currently assigned open non-draft pull requests.
-/// We store this map in memory, rather than in the DB, because it can get desynced when webhooks
+/// We use this map in memory, rather than from the DB, because it can get desynced when webhooks
 /// are missed.
-/// It is thus reloaded when triagebot starts and also periodically, so it is not needed to store it
-/// in the DB.
-#[derive(Debug, Default)]
+/// It is thus reloaded when triagebot starts and also periodically. A snapshot is saved in the DB
+/// after each periodic reload, and used on startup if the workqueue cannot be loaded from GitHub.
+#[derive(Clone, Debug, Default)]
 pub struct ReviewerWorkqueue {
     reviewers: HashMap<UserId, HashMap<PullRequestNumber, AssignedPullRequest>>,
 }
@@ -44,12 +48,37 @@ impl ReviewerWorkqueue {
         Self { reviewers }
     }
 
+    pub fn reviewers(&self) -> &HashMap<UserId, HashMap<PullRequestNumber, AssignedPullRequest>> {
+        &self.reviewers
+    }
+
     pub fn assigned_pr_count(&self, user_id: UserId) -> u64 {
         self.reviewers
             .get(&user_id)
             .map(|prs| prs.len() as u64)
             .unwrap_or(0)
     }
+
+    /// Returns true if `user_id` has an assigned PR that wasn't updated since `cutoff`.
+    pub fn has_pr_not_updated_since(&self, user_id: UserId, cutoff: DateTime<Utc>) -> bool {
+        self.reviewers
+            .get(&user_id)
+            .is_some_and(|prs| prs.values().any(|pr| pr.updated_at < cutoff))
+    }
+
+    /// Moves a PR from the queue of `from` to the queue of `to`.
+    pub fn transfer_pr(&mut self, from: UserId, to: UserId, pr: PullRequestNumber) {
+        if let Some(assigned_pr) = self
+            .reviewers
+            .get_mut(&from)
+            .and_then(|queue| queue.remove(&pr))
+        {
+            self.reviewers
+                .entry(to)
+                .or_default()
+                .insert(pr, assigned_pr);
+        }
+    }
 }
 
 pub(super) enum ReviewPrefsInput {
@@ -62,7 +91,7 @@ pub(super) async fn parse_input(
     _ctx: &Context,
     event: &IssuesEvent,
     config: Option<&ReviewPrefsConfig>,
-) -> Result<Option<ReviewPrefsInput>, String> {
+) -> Result<Option<ReviewPrefsInput>, ParseInputError> {
     // NOTE: this config check MUST exist. Else, the triagebot will emit an error
     // about this feature not being enabled
     if config.is_none() {
@@ -121,6 +150,7 @@ pub(super) async fn handle_input<'a>(
 
     let assigned_pr = AssignedPullRequest {
         title: pr.title.clone(),
+        updated_at: pr.updated_at,
     };
 
     match input {
@@ -165,12 +195,74 @@ pub(super) async fn handle_input<'a>(
     Ok(())
 }
 
+/// Organization and name of the repository whose PRs are tracked in the workqueue.
+pub const WORKQUEUE_REPOSITORY: (&str, &str) = ("rust-lang", "rust");
+
 /// Loads the workqueue (mapping of open PRs assigned to users) from GitHub
 pub async fn load_workqueue(client: &Octocrab) -> anyhow::Result<ReviewerWorkqueue> {
-    tracing::debug!("Loading workqueue for rust-lang/rust");
-    let prs = retrieve_pull_request_assignments("rust-lang", "rust", &client).await?;
+    let (org, repo) = WORKQUEUE_REPOSITORY;
+    tracing::debug!("Loading workqueue for {org}/{repo}");
+    let prs = retrieve_pull_request_assignments(org, repo, &client).await?;
+    Ok(workqueue_from_assignments(prs))
+}
+
+/// Rebuilds the workqueue from GitHub on demand, e.g. with `work sync`.
+///
+/// This fetches the open PRs together with their assignees with GraphQL,
+/// which only needs one request per 100 PRs.
+pub async fn sync_workqueue(client: &GithubClient) -> anyhow::Result<ReviewerWorkqueue> {
+    let (org, repo) = WORKQUEUE_REPOSITORY;
+    tracing::debug!("Synchronizing workqueue for {org}/{repo}");
+    let mut assignments = vec![];
+    for pr in client.open_pull_requests(org, repo).await? {
+        let labels = pr
+            .labels
+            .map(|l| l.nodes)
+            .unwrap_or_default()
+            .into_iter()
+            .map(|l| Label { name: l.name })
+            .collect::<Vec<Label>>();
+        let assignees = pr
+            .assignees
+            .nodes
+            .into_iter()
+            .filter_map(|user| {
+                Some(User {
+                    login: user.login,
+                    id: user.database_id? as UserId,
+                })
+            })
+            .collect::<Vec<_>>();
+        let author = match pr.author {
+            Some(Author::User(author)) => User {
+                login: author.login,
+                id: author.database_id.unwrap_or_default() as UserId,
+            },
+            _ => User {
+                login: "ghost".to_string(),
+                id: 0,
+            },
+        };
+        if waits_for_a_review(&labels, &assignees, &author, true, pr.is_draft) {
+            for user in assignees {
+                assignments.push((
+                    user,
+                    pr.number as PullRequestNumber,
+                    AssignedPullRequest {
+                        title: pr.title.clone(),
+                        updated_at: pr.updated_at,
+                    },
+                ));
+            }
+        }
+    }
+    Ok(workqueue_from_assignments(assignments))
+}
 
-    // Aggregate PRs by user
+/// Aggregates (user, PR number) assignments into a workqueue.
+fn workqueue_from_assignments(
+    prs: Vec<(User, PullRequestNumber, AssignedPullRequest)>,
+) -> ReviewerWorkqueue {
     let aggregated: HashMap<UserId, HashMap<PullRequestNumber, AssignedPullRequest>> = prs
         .into_iter()
         .fold(HashMap::new(), |mut acc, (user, pr_number, pr)| {
@@ -179,7 +271,7 @@ pub async fn load_workqueue(client: &Octocrab) -> anyhow::Result<ReviewerWorkque
             acc
         });
     tracing::debug!("PR assignments\n{aggregated:?}");
-    Ok(ReviewerWorkqueue::new(aggregated))
+    ReviewerWorkqueue::new(aggregated)
 }
 
 /// Retrieve tuples of (user, PR number) where
@@ -243,6 +335,7 @@ pub async fn retrieve_pull_request_assignments(
                     pr.number,
                     AssignedPullRequest {
                         title: pr.title.clone().unwrap_or_default(),
+                        updated_at: pr.updated_at.or(pr.created_at).unwrap_or_else(Utc::now),
                     },
                 ));
             }
@@ -556,6 +649,7 @@ mod tests {
                     pr,
                     AssignedPullRequest {
                         title: format!("PR {pr}"),
+                        updated_at: chrono::Utc::now(),
                     },
                 );
             }
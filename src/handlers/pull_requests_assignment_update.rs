@@ -1,3 +1,4 @@
+use crate::db::workqueue::save_workqueue;
 use crate::handlers::pr_tracking::load_workqueue;
 use crate::jobs::Job;
 use async_trait::async_trait;
@@ -13,7 +14,12 @@ impl Job for PullRequestAssignmentUpdate {
     async fn run(&self, ctx: &super::Context, _metadata: &serde_json::Value) -> anyhow::Result<()> {
         tracing::trace!("starting pull_request_assignment_update");
         let workqueue = load_workqueue(&ctx.octocrab).await?;
-        *ctx.workqueue.write().await = workqueue;
+        *ctx.workqueue.write().await = workqueue.clone();
+        // The snapshot is only a fallback for the next startup, failing to
+        // save it must not prevent the refresh of the live workqueue.
+        if let Err(err) = save_workqueue(&mut *ctx.db.get().await, &workqueue).await {
+            tracing::warn!("failed to save the workqueue snapshot: {err:?}");
+        }
         tracing::trace!("finished pull_request_assignment_update");
 
         Ok(())
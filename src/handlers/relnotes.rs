@@ -2,42 +2,158 @@
 //! `relnotes`. Any such tagging will open a new issue in rust-lang/rust responsible for tracking
 //! the inclusion in releases notes.
 //!
+//! Other repositories can collect their release notes with a `[relnotes]` config, which selects
+//! the repository of the tracking issues and the labels that open them.
+//!
 //! The new issue will be closed when T-release has added the text proposed (tracked in the issue
-//! description) into the final release notes PR.
+//! description) into the final release notes PR. If the origin issue or PR is labeled again after
+//! that, the existing tracking issue is reopened instead of creating a new one. If the origin PR
+//! is closed without being merged, the tracking issue is closed as well, and reopened with the PR.
 //!
-//! The issue description will be edited manually by teams through the GitHub UI -- in the future,
-//! we might add triagebot support for maintaining that text via commands or similar.
+//! The issue description can be edited manually by teams through the GitHub UI, or the release notes
+//! text can be replaced by a team member with `@rustbot relnotes-text` on the origin issue or PR,
+//! followed by the new text in a fenced code block.
 //!
-//! These issues will also be automatically milestoned when their corresponding PR or issue is. In
+//! These issues will also be automatically milestoned when their corresponding PR or issue is, and
+//! demilestoned when it is. In
 //! the absence of a milestone, T-release is responsible for ascertaining which release is
-//! associated with the issue.
+//! associated with the issue. Milestone changes are debounced with a scheduled job, so that only the
+//! last one of a burst is applied to the release notes issue.
 
+use async_trait::async_trait;
+use chrono::{Duration, Utc};
 use serde::{Deserialize, Serialize};
 
+use anyhow::Context as _;
+use parser::command::relnotes::RelnotesCommand;
+
 use crate::{
+    config::RelnotesConfig,
     db::issue_data::IssueData,
-    github::{Event, IssuesAction},
+    github::{
+        Event, IssueRepository, IssueState, IssuesAction, IssuesEvent, Label, Milestone, PrState,
+        UnknownLabels,
+    },
     handlers::Context,
+    jobs::Job,
 };
+use tracing as log;
 
 const RELNOTES_KEY: &str = "relnotes";
 
 #[derive(Debug, Default, Deserialize, Serialize, Clone, PartialEq)]
 struct RelnotesState {
     relnotes_issue: Option<u64>,
+    /// Whether the release notes issue was closed because the origin PR was
+    /// closed without being merged.
+    #[serde(default)]
+    closed_unmerged: bool,
+    /// Number of milestone changes of the origin issue. Only the job of the
+    /// last change applies it.
+    #[serde(default)]
+    milestone_generation: u64,
 }
 
 const TITLE_PREFIX: &str = "Tracking issue for release notes";
 
-pub(super) async fn handle(ctx: &Context, event: &Event) -> anyhow::Result<()> {
+/// How long the milestone of an origin issue must stay unchanged before it is
+/// applied to its release notes issue.
+#[cfg(not(test))]
+const MILESTONE_QUIET_PERIOD: Duration = Duration::seconds(30);
+#[cfg(test)]
+const MILESTONE_QUIET_PERIOD: Duration = Duration::milliseconds(10);
+
+const MILESTONE_JOB_NAME: &str = "relnotes_milestone";
+
+/// Metadata of the job applying a milestone change of an origin issue to its
+/// release notes issue.
+#[derive(Debug, Serialize, Deserialize)]
+struct MilestoneChange {
+    /// Repository of the origin issue, e.g. `rust-lang/rust`.
+    origin_repo: String,
+    origin: u64,
+    /// Repository of the release notes issue.
+    tracking_repo: String,
+    paired: u64,
+    /// The new milestone, or `None` if it was removed.
+    milestone: Option<Milestone>,
+    /// The `milestone_generation` of the origin issue for this change.
+    generation: u64,
+}
+
+/// Applies a milestone change to a release notes issue after the quiet period,
+/// unless another milestone change arrived in the meantime.
+pub(crate) struct RelnotesMilestoneJob;
+
+#[async_trait]
+impl Job for RelnotesMilestoneJob {
+    fn name(&self) -> &'static str {
+        MILESTONE_JOB_NAME
+    }
+
+    async fn run(&self, ctx: &Context, metadata: &serde_json::Value) -> anyhow::Result<()> {
+        let change: MilestoneChange = serde_json::from_value(metadata.clone())
+            .context("unable to deserialize the metadata in relnotes milestone job")?;
+        let is_latest = {
+            let mut db = ctx.db.get().await;
+            let state: IssueData<'_, RelnotesState> = IssueData::load_by_number(
+                &mut db,
+                &change.origin_repo,
+                change.origin,
+                RELNOTES_KEY,
+            )
+            .await?;
+            state.data.milestone_generation == change.generation
+        };
+        let MilestoneChange {
+            tracking_repo: repo,
+            paired,
+            ..
+        } = &change;
+        if !is_latest {
+            log::trace!("skipping superseded milestone change of {repo}#{paired}");
+            return Ok(());
+        }
+        match &change.milestone {
+            Some(milestone) => ctx.github.set_milestone(repo, milestone, *paired).await,
+            None => ctx.github.remove_milestone(repo, *paired).await,
+        }
+        .with_context(|| format!("failed to set the milestone of {repo}#{paired}"))
+    }
+}
+
+/// Returns the config used for `event` without a `[relnotes]` section.
+///
+/// Release notes are then only collected in rust-lang/rust.
+pub(super) fn default_config(event: &Event) -> Option<RelnotesConfig> {
+    let repo = event.issue()?.repository();
+    (repo.organization == "rust-lang" && repo.repository == "rust").then(RelnotesConfig::default)
+}
+
+pub(super) async fn handle(
+    ctx: &Context,
+    event: &Event,
+    config: Option<&RelnotesConfig>,
+) -> anyhow::Result<()> {
     let Event::Issue(e) = event else {
         return Ok(());
     };
 
-    let repo = e.issue.repository();
-    if !(repo.organization == "rust-lang" && repo.repository == "rust") {
-        return Ok(());
-    }
+    let default;
+    let config = match config {
+        Some(config) => config,
+        None => {
+            let Some(config) = default_config(event) else {
+                return Ok(());
+            };
+            default = config;
+            &default
+        }
+    };
+    let tracking_repo = tracking_repo(config, e.issue.repository())?;
+    // Milestones are specific to a repository, they are only carried over to
+    // tracking issues in the same repository.
+    let same_repo = &tracking_repo == e.issue.repository();
 
     if e.issue.title.starts_with(TITLE_PREFIX) {
         // Ignore these issues -- they're otherwise potentially self-recursive.
@@ -51,10 +167,74 @@ pub(super) async fn handle(ctx: &Context, event: &Event) -> anyhow::Result<()> {
     if let Some(paired) = state.data.relnotes_issue {
         // Already has a paired release notes issue.
 
-        if let IssuesAction::Milestoned = &e.action {
-            if let Some(milestone) = &e.issue.milestone {
+        // The milestone of the origin is `None` once demilestoned.
+        if let IssuesAction::Milestoned | IssuesAction::Demilestoned = &e.action
+            && same_repo
+        {
+            // The change supersedes those still waiting for the quiet period.
+            state.data.milestone_generation += 1;
+            let change = MilestoneChange {
+                origin_repo: e.issue.repository().to_string(),
+                origin: e.issue.number,
+                tracking_repo: tracking_repo.to_string(),
+                paired,
+                milestone: e.issue.milestone.clone(),
+                generation: state.data.milestone_generation,
+            };
+            crate::db::schedule_job(
+                &*ctx.db.get().await,
+                MILESTONE_JOB_NAME,
+                serde_json::to_value(change).context("unable to serialize the milestone change")?,
+                Utc::now() + MILESTONE_QUIET_PERIOD,
+            )
+            .await
+            .context("failed to schedule the milestone change")?;
+        }
+
+        // A PR closed without being merged will not be released, close its paired issue. It is
+        // reopened if the PR gets reopened, or merged after all.
+        match &e.action {
+            IssuesAction::Closed if e.issue.is_pr() && !e.issue.merged => {
+                let repo = &tracking_repo;
+                let paired_issue = ctx.github.get_issue(repo, paired).await?;
+                if paired_issue.state == IssueState::Open {
+                    log::info!("closing release notes issue {repo}#{paired}");
+                    paired_issue
+                        .post_comment(
+                            &ctx.github,
+                            &format!(
+                                "Closing, as #{} was closed without being merged.",
+                                e.issue.number
+                            ),
+                        )
+                        .await?;
+                    paired_issue.close(&ctx.github).await?;
+                    state.data.closed_unmerged = true;
+                }
+            }
+            IssuesAction::Reopened | IssuesAction::Closed if state.data.closed_unmerged => {
+                let repo = &tracking_repo;
+                log::info!("reopening release notes issue {repo}#{paired}");
+                ctx.github
+                    .set_issue_state(repo, paired, PrState::Open)
+                    .await?;
+                state.data.closed_unmerged = false;
+            }
+            _ => {}
+        }
+        state.save().await?;
+
+        // If the origin was labeled again, reopen the paired issue if it was closed in the
+        // meantime, instead of creating a new one.
+        if let IssuesAction::Labeled { label } = &e.action
+            && is_relnotes_trigger(config, e, label)
+        {
+            let repo = &tracking_repo;
+            let paired_issue = ctx.github.get_issue(repo, paired).await?;
+            if paired_issue.state == IssueState::Closed {
+                log::info!("reopening release notes issue {repo}#{paired}");
                 ctx.github
-                    .set_milestone(&e.issue.repository().to_string(), &milestone, paired)
+                    .set_issue_state(repo, paired, PrState::Open)
                     .await?;
             }
         }
@@ -63,17 +243,17 @@ pub(super) async fn handle(ctx: &Context, event: &Event) -> anyhow::Result<()> {
     }
 
     if let IssuesAction::Labeled { label } = &e.action {
-        let is_fcp_merge = label.name == "finished-final-comment-period"
-            && e.issue
-                .labels
-                .iter()
-                .any(|label| label.name == "disposition-merge");
-
-        if label.name == "relnotes" || label.name == "relnotes-perf" || is_fcp_merge {
-            let title = format!("{TITLE_PREFIX} of #{}: {}", e.issue.number, e.issue.title);
+        if is_relnotes_trigger(config, e, label) {
+            // Issues of other repositories need the full reference.
+            let origin = if same_repo {
+                format!("#{}", e.issue.number)
+            } else {
+                format!("{}#{}", e.issue.repository(), e.issue.number)
+            };
+            let title = format!("{TITLE_PREFIX} of {origin}: {}", e.issue.title);
             let body = format!(
                 "
-This issue tracks the release notes text for #{pr_number}.
+This issue tracks the release notes text for {origin}.
 
 cc {people} -- original issue/PR authors and assignees for drafting text
 
@@ -106,41 +286,290 @@ If this change is notable enough for inclusion in the blog post then this sectio
 >
 > If a blog post section is required the `release-blog-post` label should be added (`@rustbot label +release-blog-post`) to this issue as otherwise it may be missed by the release team.
 ",
-                pr_number = e.issue.number,
                 people = [&e.issue.user].into_iter().chain(e.issue.assignees.iter())
                     .map(|v| format!("@{}", v.login)).collect::<Vec<_>>().join(", "),
                 pr_title = e.issue.title,
                 pr_url = e.issue.html_url,
             );
+            // The labels are added after creating the issue, so that a missing label
+            // does not prevent the tracking issue from being created.
             let resp = ctx
                 .github
-                .new_issue(
-                    &e.issue.repository(),
-                    &title,
-                    &body,
-                    ["relnotes", "relnotes-tracking-issue"]
-                        .into_iter()
-                        .chain(e.issue.labels.iter().map(|l| &*l.name).filter(|l| {
-                            l.starts_with("A-") // A-* (area)
-                            || l.starts_with("F-") // F-* (feature)
-                            || l.starts_with("L-") // L-* (lint)
-                            || l.starts_with("O-") // O-* (OS)
-                            || l.starts_with("T-") // T-* (team)
-                            || l.starts_with("WG-") // WG-* (working group)
-                        }))
-                        .map(ToOwned::to_owned)
-                        .collect::<Vec<_>>(),
-                )
+                .new_issue(&tracking_repo, &title, &body, vec![])
                 .await?;
-            if let Some(milestone) = &e.issue.milestone {
+            state.data.relnotes_issue = Some(resp.number);
+            state.save().await?;
+
+            let labels = ["relnotes", "relnotes-tracking-issue"]
+                .into_iter()
+                .chain(e.issue.labels.iter().map(|l| &*l.name).filter(|l| {
+                    l.starts_with("A-") // A-* (area)
+                    || l.starts_with("F-") // F-* (feature)
+                    || l.starts_with("L-") // L-* (lint)
+                    || l.starts_with("O-") // O-* (OS)
+                    || l.starts_with("T-") // T-* (team)
+                    || l.starts_with("WG-") // WG-* (working group)
+                }))
+                .map(ToOwned::to_owned)
+                .collect::<Vec<_>>();
+            if let Err(err) = ctx
+                .github
+                .add_labels(&tracking_repo, resp.number, labels)
+                .await
+            {
+                if let Some(UnknownLabels { .. }) = err.downcast_ref() {
+                    log::warn!("Error adding labels to release notes issue: {err}");
+                } else {
+                    return Err(err);
+                }
+            }
+
+            if let Some(milestone) = &e.issue.milestone
+                && same_repo
+            {
                 ctx.github
-                    .set_milestone(&e.issue.repository().to_string(), &milestone, resp.number)
+                    .set_milestone(&tracking_repo.to_string(), &milestone, resp.number)
                     .await?;
             }
-            state.data.relnotes_issue = Some(resp.number);
-            state.save().await?;
         }
     }
 
     Ok(())
 }
+
+/// Handles `@rustbot relnotes-text`, replacing the release notes text of the
+/// tracking issue with the text of the command.
+pub(super) async fn handle_command(
+    ctx: &Context,
+    config: &RelnotesConfig,
+    event: &Event,
+    cmd: RelnotesCommand,
+) -> anyhow::Result<()> {
+    let issue = event.issue().unwrap();
+    if !event
+        .user()
+        .is_team_member(&ctx.team)
+        .await
+        .ok()
+        .unwrap_or(false)
+    {
+        issue
+            .post_comment(
+                &ctx.github,
+                "Only team members may edit the release notes text.",
+            )
+            .await?;
+        return Ok(());
+    }
+
+    let paired = {
+        let mut db = ctx.db.get().await;
+        let state: IssueData<'_, RelnotesState> =
+            IssueData::load(&mut db, issue, RELNOTES_KEY).await?;
+        state.data.relnotes_issue
+    };
+    let Some(paired) = paired else {
+        issue
+            .post_comment(
+                &ctx.github,
+                "There is no release notes tracking issue for this issue or PR.",
+            )
+            .await?;
+        return Ok(());
+    };
+
+    let tracking_repo = tracking_repo(config, issue.repository())?;
+    let tracking_issue = ctx.github.get_issue(&tracking_repo, paired).await?;
+    let Some(body) = with_relnotes_text(&tracking_issue.body, &cmd.text) else {
+        issue
+            .post_comment(
+                &ctx.github,
+                &format!(
+                    "Cannot find the release notes text section of {}.",
+                    tracking_issue.html_url
+                ),
+            )
+            .await?;
+        return Ok(());
+    };
+    tracking_issue.edit_body(&ctx.github, &body).await?;
+    issue
+        .post_comment(
+            &ctx.github,
+            &format!(
+                "Updated the release notes text of {}.",
+                tracking_issue.html_url
+            ),
+        )
+        .await?;
+    Ok(())
+}
+
+/// Replaces the content of the `markdown` block of the "Release notes text"
+/// section of a tracking issue `body` with `text`.
+fn with_relnotes_text(body: &str, text: &str) -> Option<String> {
+    let section = body.find("### Release notes text")?;
+    let fence = section + body[section..].find("````markdown")?;
+    let start = fence + body[fence..].find('\n')? + 1;
+    let end = start + body[start..].find("````")?;
+    Some(format!("{}{text}\n{}", &body[..start], &body[end..]))
+}
+
+/// Returns the repository where the release notes issues of `origin` are opened.
+fn tracking_repo(
+    config: &RelnotesConfig,
+    origin: &IssueRepository,
+) -> anyhow::Result<IssueRepository> {
+    let Some(tracking_repo) = &config.tracking_repo else {
+        return Ok(origin.clone());
+    };
+    let (organization, repository) = tracking_repo
+        .split_once('/')
+        .with_context(|| format!("invalid relnotes tracking repository `{tracking_repo}`"))?;
+    Ok(IssueRepository {
+        organization: organization.to_string(),
+        repository: repository.to_string(),
+    })
+}
+
+/// Returns `true` if adding `label` should create a release notes issue.
+fn is_relnotes_trigger(config: &RelnotesConfig, e: &IssuesEvent, label: &Label) -> bool {
+    if !config.trigger_labels.contains(&label.name) {
+        return false;
+    }
+    // A finished FCP only needs release notes if the decision was to merge.
+    label.name != "finished-final-comment-period"
+        || e.issue
+            .labels
+            .iter()
+            .any(|label| label.name == "disposition-merge")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{handle, with_relnotes_text};
+    use crate::db::run_scheduled_jobs;
+    use crate::github::{Event, Issue, IssuesAction, IssuesEvent, Label, Repository};
+    use crate::tests::github::{default_test_user, issue};
+    use crate::tests::run_db_test;
+    use axum::http::Method;
+    use serde_json::{Value, json};
+    use std::time::Duration;
+
+    fn issues_event(action: IssuesAction, issue: Issue) -> Event {
+        Event::Issue(IssuesEvent {
+            action,
+            issue,
+            changes: None,
+            repository: Repository {
+                full_name: "rust-lang/rust".to_string(),
+                default_branch: "master".to_string(),
+                fork: false,
+                parent: None,
+            },
+            sender: default_test_user(),
+        })
+    }
+
+    fn origin_issue(milestone: Option<Value>) -> Issue {
+        let mut origin = issue().number(100).labels(vec!["relnotes"]).call();
+        origin.milestone = milestone.map(|milestone| serde_json::from_value(milestone).unwrap());
+        origin
+    }
+
+    #[tokio::test]
+    async fn milestoned_after_labeled() {
+        run_db_test(|mut ctx| async move {
+            let mock = ctx.mock_github().await;
+            mock.respond(
+                Method::POST,
+                "/repos/rust-lang/rust/issues",
+                json!({ "number": 200 }),
+            );
+            let milestone = json!({ "number": 5, "title": "1.90.0" });
+            let label = Label {
+                name: "relnotes".to_string(),
+            };
+            let milestone_changes = || {
+                mock.requests()
+                    .into_iter()
+                    .filter(|req| {
+                        req.method == Method::PATCH
+                            && req.path == "/repos/rust-lang/rust/issues/200"
+                    })
+                    .map(|req| req.body["milestone"].clone())
+                    .collect::<Vec<_>>()
+            };
+
+            // Not milestoned yet when labeled.
+            let event = issues_event(IssuesAction::Labeled { label }, origin_issue(None));
+            handle(ctx.handler_ctx(), &event, None).await?;
+            assert!(milestone_changes().is_empty());
+
+            let event = issues_event(IssuesAction::Milestoned, origin_issue(Some(milestone)));
+            handle(ctx.handler_ctx(), &event, None).await?;
+            tokio::time::sleep(Duration::from_millis(100)).await;
+            run_scheduled_jobs(ctx.handler_ctx()).await?;
+            assert_eq!(milestone_changes(), [json!(5)]);
+
+            let event = issues_event(IssuesAction::Demilestoned, origin_issue(None));
+            handle(ctx.handler_ctx(), &event, None).await?;
+            tokio::time::sleep(Duration::from_millis(100)).await;
+            run_scheduled_jobs(ctx.handler_ctx()).await?;
+            assert_eq!(milestone_changes(), [json!(5), Value::Null]);
+            Ok(ctx)
+        })
+        .await;
+    }
+
+    #[tokio::test]
+    async fn only_last_milestone_change_is_applied() {
+        run_db_test(|mut ctx| async move {
+            let mock = ctx.mock_github().await;
+            mock.respond(
+                Method::POST,
+                "/repos/rust-lang/rust/issues",
+                json!({ "number": 200 }),
+            );
+            let label = Label {
+                name: "relnotes".to_string(),
+            };
+            let event = issues_event(IssuesAction::Labeled { label }, origin_issue(None));
+            handle(ctx.handler_ctx(), &event, None).await?;
+
+            for number in [5, 6] {
+                let milestone = json!({ "number": number, "title": format!("1.9{number}.0") });
+                let event = issues_event(IssuesAction::Milestoned, origin_issue(Some(milestone)));
+                handle(ctx.handler_ctx(), &event, None).await?;
+            }
+            tokio::time::sleep(Duration::from_millis(100)).await;
+            run_scheduled_jobs(ctx.handler_ctx()).await?;
+
+            let milestone_changes: Vec<Value> = mock
+                .requests()
+                .into_iter()
+                .filter(|req| {
+                    req.method == Method::PATCH && req.path == "/repos/rust-lang/rust/issues/200"
+                })
+                .map(|req| req.body["milestone"].clone())
+                .collect();
+            assert_eq!(milestone_changes, [json!(6)]);
+            Ok(ctx)
+        })
+        .await;
+    }
+
+    #[test]
+    fn replaces_relnotes_text() {
+        let body = "### Release notes text\n\nSome advice.\n\n````markdown\n# Category\n- [Old](url)\n````\n\n\
+            ### Release blog section\n\n````markdown\n````\n";
+        assert_eq!(
+            with_relnotes_text(body, "# Libraries\n- [New](url)").as_deref(),
+            Some(
+                "### Release notes text\n\nSome advice.\n\n````markdown\n# Libraries\n- [New](url)\n````\n\n\
+                ### Release blog section\n\n````markdown\n````\n"
+            )
+        );
+        assert_eq!(with_relnotes_text("Edited away", "text"), None);
+    }
+}
@@ -1,6 +1,6 @@
 use crate::config::ReviewRequestedConfig;
 use crate::github::{IssuesAction, IssuesEvent, Label};
-use crate::handlers::Context;
+use crate::handlers::{Context, ParseInputError};
 
 pub(crate) struct ReviewRequestedInput {}
 
@@ -8,7 +8,7 @@ pub(crate) async fn parse_input(
     _ctx: &Context,
     event: &IssuesEvent,
     config: Option<&ReviewRequestedConfig>,
-) -> Result<Option<ReviewRequestedInput>, String> {
+) -> Result<Option<ReviewRequestedInput>, ParseInputError> {
     // PR author requests a review from one of the assignees
 
     if config.is_none() {
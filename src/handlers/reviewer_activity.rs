@@ -0,0 +1,44 @@
+//! Records when assigned reviewers comment on or review a pull request.
+//!
+//! The recorded activity is used by the `least-recently-active` reviewer
+//! selection mode of the `assign` handler. The last activity on each PR is
+//! also recorded, for the `auto_reassign_days` option.
+
+use crate::db::issue_data::IssueData;
+use crate::db::reviewer_activity::record_reviewer_activity;
+use crate::github::{Event, Issue, IssueCommentAction, IssueCommentEvent};
+use crate::handlers::Context;
+use chrono::{DateTime, Utc};
+
+/// Key for the last activity of the assignees of a PR in the database.
+pub(crate) const PR_ACTIVITY_KEY: &str = "assignee-activity";
+
+pub(crate) async fn handle(ctx: &Context, event: &Event) -> anyhow::Result<()> {
+    // Review comments and submitted reviews are also delivered as issue comments.
+    let Event::IssueComment(
+        event @ IssueCommentEvent {
+            action: IssueCommentAction::Created,
+            issue: Issue {
+                pull_request: Some(_),
+                ..
+            },
+            ..
+        },
+    ) = event
+    else {
+        return Ok(());
+    };
+
+    if !event.issue.assignees.contains(&event.comment.user) {
+        return Ok(());
+    }
+
+    let time = event.comment.created_at.unwrap_or_else(Utc::now);
+    let mut db = ctx.db.get().await;
+    record_reviewer_activity(&db, &event.comment.user, time).await?;
+
+    let mut state: IssueData<'_, Option<DateTime<Utc>>> =
+        IssueData::load(&mut db, &event.issue, PR_ACTIVITY_KEY).await?;
+    state.data = state.data.max(Some(time));
+    state.save().await
+}
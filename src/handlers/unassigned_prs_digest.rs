@@ -0,0 +1,148 @@
+//! A scheduled job that posts a digest of open PRs without any reviewer.
+//!
+//! New PRs are normally assigned by the `assign` handler when they are opened.
+//! This catches the PRs that slipped through, for example because the bot was
+//! down or no reviewer could be determined at the time.
+
+use crate::github::{Issue, Query};
+use crate::handlers::assign::is_ghost_requested;
+use crate::jobs::Job;
+use crate::zulip::MessageApiRequest;
+use crate::zulip::api::Recipient;
+use anyhow::Context as _;
+use async_trait::async_trait;
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use std::fmt::Write;
+
+/// Maximum number of PRs listed for a single repository, to keep the message readable.
+const MAX_LISTED_PRS: usize = 50;
+
+#[derive(Serialize, Deserialize)]
+pub struct UnassignedPrsDigestMetadata {
+    /// Repositories to look for unassigned PRs in, e.g. `rust-lang/rust`.
+    pub repos: Vec<String>,
+    /// Only PRs opened at least this many days ago are listed.
+    pub min_age_days: i64,
+    /// Zulip stream where the digest is posted.
+    pub stream: u64,
+    /// Zulip topic where the digest is posted.
+    pub topic: String,
+}
+
+impl UnassignedPrsDigestMetadata {
+    /// The metadata of the scheduled digest of rust-lang/rust.
+    ///
+    /// The digest is posted to the Zulip stream id in the
+    /// `UNASSIGNED_PRS_DIGEST_STREAM` environment variable, and in the
+    /// `UNASSIGNED_PRS_DIGEST_TOPIC` topic, if set. It is not scheduled when
+    /// no stream is set.
+    pub fn from_env() -> Option<Self> {
+        let stream = std::env::var("UNASSIGNED_PRS_DIGEST_STREAM").ok()?;
+        let stream = match stream.parse() {
+            Ok(stream) => stream,
+            Err(err) => {
+                tracing::error!("invalid UNASSIGNED_PRS_DIGEST_STREAM `{stream}`: {err}");
+                return None;
+            }
+        };
+        Some(Self {
+            repos: vec!["rust-lang/rust".to_string()],
+            min_age_days: 7,
+            stream,
+            topic: std::env::var("UNASSIGNED_PRS_DIGEST_TOPIC")
+                .unwrap_or_else(|_| "unassigned PRs".to_string()),
+        })
+    }
+}
+
+pub struct UnassignedPrsDigestJob;
+
+#[async_trait]
+impl Job for UnassignedPrsDigestJob {
+    fn name(&self) -> &'static str {
+        "unassigned_prs_digest"
+    }
+
+    async fn run(&self, ctx: &super::Context, metadata: &serde_json::Value) -> anyhow::Result<()> {
+        let metadata: UnassignedPrsDigestMetadata = serde_json::from_value(metadata.clone())
+            .context("unable to deserialize the metadata in unassigned PRs digest job")?;
+
+        let cutoff = Utc::now() - Duration::days(metadata.min_age_days);
+        let mut message = String::new();
+        for repo in &metadata.repos {
+            let repository = ctx.github.repository(repo).await?;
+            let query = Query {
+                filters: vec![
+                    ("state", "open"),
+                    ("is", "pull-request"),
+                    ("no", "assignee"),
+                    ("draft", "false"),
+                ],
+                include_labels: vec![],
+                exclude_labels: vec![],
+            };
+            let prs: Vec<Issue> = repository
+                .get_issues(&ctx.github, &query)
+                .await?
+                .into_iter()
+                .filter(|pr| pr.created_at <= cutoff && !is_ghost_requested(ctx, pr))
+                .collect();
+            if prs.is_empty() {
+                continue;
+            }
+            message.push_str(&repo_digest(repo, &prs));
+        }
+
+        if message.is_empty() {
+            tracing::trace!("no unassigned PRs found");
+            return Ok(());
+        }
+
+        let content = format!(
+            "The following PRs have been open for at least {} days without a reviewer:\n{message}",
+            metadata.min_age_days
+        );
+        MessageApiRequest {
+            recipient: Recipient::Stream {
+                id: metadata.stream,
+                topic: &metadata.topic,
+            },
+            content: &content,
+        }
+        .send(&ctx.zulip)
+        .await?;
+
+        Ok(())
+    }
+}
+
+/// Formats the list of unassigned `prs` of `repo`, oldest first.
+fn repo_digest(repo: &str, prs: &[Issue]) -> String {
+    let mut prs: Vec<&Issue> = prs.iter().collect();
+    prs.sort_by_key(|pr| pr.created_at);
+
+    let mut digest = format!("\n**{repo}** ({} PRs)\n", prs.len());
+    for pr in prs.iter().take(MAX_LISTED_PRS) {
+        writeln!(
+            digest,
+            "- [#{}]({}) {} (opened {})",
+            pr.number,
+            pr.html_url,
+            pr.title,
+            format_age(pr.created_at)
+        )
+        .unwrap();
+    }
+    if prs.len() > MAX_LISTED_PRS {
+        writeln!(digest, "- and {} more", prs.len() - MAX_LISTED_PRS).unwrap();
+    }
+    digest
+}
+
+fn format_age(created_at: DateTime<Utc>) -> String {
+    match (Utc::now() - created_at).num_days() {
+        1 => "1 day ago".to_string(),
+        days => format!("{days} days ago"),
+    }
+}
@@ -46,7 +46,14 @@ use std::str::FromStr;
 use async_trait::async_trait;
 use cron::Schedule;
 
+use crate::assignment_events::AssignmentEventsPruneJob;
+use crate::handlers::assign::{AutoReassignJob, AutoReassignMetadata, ReviewProposalTimeoutJob};
+use crate::handlers::check_commits::behind_upstream::{
+    BehindUpstreamSweepJob, BehindUpstreamSweepMetadata,
+};
 use crate::handlers::pull_requests_assignment_update::PullRequestAssignmentUpdate;
+use crate::handlers::relnotes::RelnotesMilestoneJob;
+use crate::handlers::unassigned_prs_digest::{UnassignedPrsDigestJob, UnassignedPrsDigestMetadata};
 use crate::{
     db::jobs::JobSchedule,
     handlers::{
@@ -70,12 +77,18 @@ pub fn jobs() -> Vec<Box<dyn Job + Send + Sync>> {
         Box::new(RustcCommitsJob),
         Box::new(PullRequestAssignmentUpdate),
         Box::new(MajorChangeAcceptenceJob),
+        Box::new(UnassignedPrsDigestJob),
+        Box::new(BehindUpstreamSweepJob),
+        Box::new(ReviewProposalTimeoutJob),
+        Box::new(AutoReassignJob),
+        Box::new(RelnotesMilestoneJob),
+        Box::new(AssignmentEventsPruneJob),
     ]
 }
 
 // Definition of the schedule repetition for the jobs we want to run.
 pub fn default_jobs() -> Vec<JobSchedule> {
-    vec![
+    let mut jobs = vec![
         JobSchedule {
             name: DocsUpdateJob.name(),
             // Around 9am Pacific time on every Monday.
@@ -94,7 +107,38 @@ pub fn default_jobs() -> Vec<JobSchedule> {
             schedule: Schedule::from_str("* 0,30 * * * * *").unwrap(),
             metadata: serde_json::Value::Null,
         },
-    ]
+        JobSchedule {
+            name: BehindUpstreamSweepJob.name(),
+            // Every day around 6am UTC.
+            schedule: Schedule::from_str("0 00 6 * * * *").unwrap(),
+            metadata: serde_json::value::to_value(BehindUpstreamSweepMetadata {
+                repos: vec!["rust-lang/rust".to_string()],
+            })
+            .unwrap(),
+        },
+        JobSchedule {
+            name: AutoReassignJob.name(),
+            // Every day around 7am UTC.
+            schedule: Schedule::from_str("0 00 7 * * * *").unwrap(),
+            metadata: serde_json::value::to_value(AutoReassignMetadata::from_env()).unwrap(),
+        },
+        JobSchedule {
+            name: AssignmentEventsPruneJob.name(),
+            // Every day around 5am UTC.
+            schedule: Schedule::from_str("0 00 5 * * * *").unwrap(),
+            metadata: serde_json::Value::Null,
+        },
+    ];
+    // The digest is only posted once its Zulip destination is configured.
+    if let Some(metadata) = UnassignedPrsDigestMetadata::from_env() {
+        jobs.push(JobSchedule {
+            name: UnassignedPrsDigestJob.name(),
+            // Around 8am Pacific time on every Monday.
+            schedule: Schedule::from_str("0 00 16 * * Mon *").unwrap(),
+            metadata: serde_json::value::to_value(metadata).unwrap(),
+        });
+    }
+    jobs
 }
 
 #[async_trait]
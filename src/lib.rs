@@ -2,6 +2,7 @@
 
 mod actions;
 pub mod agenda;
+pub mod assignment_events;
 pub mod bors;
 mod changelogs;
 mod config;
@@ -16,6 +17,7 @@ mod rfcbot;
 pub mod team_data;
 pub mod triage;
 mod utils;
+pub mod vacation;
 pub mod zulip;
 
 #[cfg(test)]
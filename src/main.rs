@@ -20,8 +20,10 @@ use tower_http::compression::CompressionLayer;
 use tower_http::request_id::{MakeRequestUuid, PropagateRequestIdLayer, SetRequestIdLayer};
 use tower_http::trace::TraceLayer;
 use tracing::{self as log, info_span};
+use triagebot::db::workqueue::load_workqueue_snapshot;
 use triagebot::gha_logs::GitHubActionLogsCache;
 use triagebot::handlers::Context;
+use triagebot::handlers::concurrency::RepoConcurrencyLimiter;
 use triagebot::handlers::pr_tracking::ReviewerWorkqueue;
 use triagebot::handlers::pr_tracking::load_workqueue;
 use triagebot::jobs::{
@@ -31,6 +33,22 @@ use triagebot::team_data::TeamClient;
 use triagebot::zulip::client::ZulipClient;
 use triagebot::{db, github};
 
+/// Loads the workqueue snapshot saved in the DB, or an empty workqueue if
+/// there is none. The snapshot table may not exist yet, as the migrations
+/// have not been run at this point.
+async fn load_workqueue_snapshot_or_default(pool: &db::ClientPool) -> ReviewerWorkqueue {
+    match load_workqueue_snapshot(&*pool.get().await).await {
+        Ok(workqueue) => {
+            tracing::info!("Workqueue loaded from the DB snapshot");
+            workqueue
+        }
+        Err(error) => {
+            tracing::error!("Cannot load the workqueue snapshot: {error:?}");
+            ReviewerWorkqueue::default()
+        }
+    }
+}
+
 async fn run_server(addr: SocketAddr) -> anyhow::Result<()> {
     let gh = github::GithubClient::new_from_env();
     let zulip = ZulipClient::new_from_env();
@@ -47,13 +65,16 @@ async fn run_server(addr: SocketAddr) -> anyhow::Result<()> {
         .map(|v| v == "1")
         .unwrap_or(false);
 
+    let db_url = std::env::var("DATABASE_URL").expect("needs DATABASE_URL");
+    let pool = db::ClientPool::new(db_url.clone());
+
     // Load the initial workqueue state from GitHub
     // In case this fails, we do not want to block triagebot, instead
-    // we use an empty workqueue and let it be updated later through
-    // webhooks and the `PullRequestAssignmentUpdate` cron job.
+    // we use the last snapshot saved in the DB and let it be updated later
+    // through webhooks and the `PullRequestAssignmentUpdate` cron job.
     let workqueue = if skip_loading_workqueue {
-        tracing::warn!("Skipping workqueue loading");
-        ReviewerWorkqueue::default()
+        tracing::warn!("Skipping workqueue loading from GitHub");
+        load_workqueue_snapshot_or_default(&pool).await
     } else {
         tracing::info!("Loading reviewer workqueue for rust-lang/rust");
         let workqueue =
@@ -61,11 +82,11 @@ async fn run_server(addr: SocketAddr) -> anyhow::Result<()> {
                 Ok(Ok(workqueue)) => workqueue,
                 Ok(Err(error)) => {
                     tracing::error!("Cannot load initial workqueue: {error:?}");
-                    ReviewerWorkqueue::default()
+                    load_workqueue_snapshot_or_default(&pool).await
                 }
                 Err(_) => {
                     tracing::error!("Cannot load initial workqueue, timeouted after a minute");
-                    ReviewerWorkqueue::default()
+                    load_workqueue_snapshot_or_default(&pool).await
                 }
             };
         tracing::info!("Workqueue loaded");
@@ -77,8 +98,6 @@ async fn run_server(addr: SocketAddr) -> anyhow::Result<()> {
     // On AWS ECS, triagebot shortly runs in two instances at once.
     // We thus want to minimize the time where migrations have been executed
     // and the old instance potentially runs on an newer database schema.
-    let db_url = std::env::var("DATABASE_URL").expect("needs DATABASE_URL");
-    let pool = db::ClientPool::new(db_url.clone());
     if !std::env::var("SKIP_DB_MIGRATIONS").is_ok_and(|value| value == "1") {
         db::run_migrations(&mut *pool.get().await)
             .await
@@ -96,6 +115,8 @@ async fn run_server(addr: SocketAddr) -> anyhow::Result<()> {
         octocrab: oc,
         workqueue: Arc::new(RwLock::new(workqueue)),
         gha_logs: Arc::new(RwLock::new(GitHubActionLogsCache::default())),
+        repo_limiter: Arc::new(RepoConcurrencyLimiter::new_from_env()),
+        owners_files: Arc::new(Default::default()),
         zulip,
     });
 
@@ -183,6 +204,14 @@ async fn run_server(addr: SocketAddr) -> anyhow::Result<()> {
         )
         .nest("/agenda", agenda)
         .route("/bors-commit-list", get(triagebot::bors::bors_commit_list))
+        .route(
+            "/vacation/{owner}/{repo}",
+            get(triagebot::vacation::vacation_status),
+        )
+        .route(
+            "/assignment-events/{owner}/{repo}",
+            get(triagebot::assignment_events::assignment_events),
+        )
         .route(
             "/notifications",
             get(triagebot::notification_listing::notifications),
@@ -1,9 +1,10 @@
 use reqwest::Client;
-use rust_team_data::v1::{BASE_URL, People, Repos, Teams, ZulipMapping};
+use rust_team_data::v1::{BASE_URL, Bot, People, Repos, Teams, ZulipMapping};
 use serde::de::DeserializeOwned;
 use std::sync::Arc;
 use std::time::{Duration, Instant};
 use tokio::sync::RwLock;
+use tracing as log;
 
 #[derive(Clone)]
 pub struct TeamClient {
@@ -108,10 +109,37 @@ impl TeamClient {
     pub async fn people(&self) -> anyhow::Result<People> {
         self.people.get(&self.client, &self.base_url).await
     }
+
+    /// Returns whether `owner/repo` is a public repository that triagebot is
+    /// installed on, according to its bots in the team data.
+    ///
+    /// This is meant for the public endpoints, which must neither reveal data
+    /// of private repositories nor query arbitrary repositories.
+    pub async fn is_public_bot_repo(&self, owner: &str, repo: &str) -> anyhow::Result<bool> {
+        let repos = self.repos().await?;
+        Ok(repos.repos.get(owner).is_some_and(|repos| {
+            repos
+                .iter()
+                .any(|r| r.name == repo && !r.private && r.bots.contains(&Bot::Rustbot))
+        }))
+    }
+
+    /// Drops the cached team data, so that it is downloaded again on next use.
+    pub async fn invalidate(&self) {
+        self.teams.invalidate().await;
+        self.repos.invalidate().await;
+        self.people.invalidate().await;
+        self.zulip_mapping.invalidate().await;
+    }
 }
 
 /// How long should downloaded team data items be cached in memory.
-const CACHE_DURATION: Duration = Duration::from_secs(2 * 60);
+/// `work sync` can be used on Zulip to pick up team changes earlier.
+const CACHE_DURATION: Duration = Duration::from_secs(10 * 60);
+
+/// How long can an expired team data item still be used when downloading a
+/// fresh copy fails, so that brief team data outages don't break the bot.
+const STALE_CACHE_DURATION: Duration = Duration::from_secs(60 * 60);
 
 #[derive(Clone)]
 struct CachedTeamItem<T> {
@@ -127,6 +155,10 @@ impl<T: DeserializeOwned + Clone> CachedTeamItem<T> {
         }
     }
 
+    async fn invalidate(&self) {
+        *self.value.write().await = CachedValue::Empty;
+    }
+
     async fn get(&self, client: &Client, base_url: &str) -> anyhow::Result<T> {
         let now = Instant::now();
         {
@@ -150,7 +182,23 @@ impl<T: DeserializeOwned + Clone> CachedTeamItem<T> {
                 };
                 Ok(v)
             }
-            Err(e) => Err(e),
+            Err(e) => {
+                let value = self.value.read().await;
+                if let CachedValue::Present {
+                    value,
+                    last_download,
+                } = &*value
+                    && *last_download + STALE_CACHE_DURATION > now
+                {
+                    log::warn!(
+                        "failed to download {}, using the cached copy from {}s ago: {e:?}",
+                        self.url_path,
+                        last_download.elapsed().as_secs()
+                    );
+                    return Ok(value.clone());
+                }
+                Err(e)
+            }
         }
     }
 }
@@ -184,3 +232,56 @@ async fn download<T: DeserializeOwned>(
 
     Err(anyhow::anyhow!("Failed to retrieve {url} in 3 requests"))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::mock_github::MockGithub;
+    use axum::http::Method;
+
+    /// Pretends that the cached copy of `item` was downloaded `age` ago.
+    async fn set_age<T>(item: &CachedTeamItem<T>, age: Duration) {
+        if let CachedValue::Present { last_download, .. } = &mut *item.value.write().await {
+            *last_download = Instant::now() - age;
+        }
+    }
+
+    #[tokio::test]
+    async fn teams_are_cached() {
+        let mock = MockGithub::start().await;
+        mock.set_teams(&[("compiler", &["martin"])]);
+        let team = TeamClient::new(mock.url().to_string());
+        let downloads = || {
+            mock.requests()
+                .iter()
+                .filter(|req| req.path == "/teams.json")
+                .count()
+        };
+
+        team.teams().await.unwrap();
+        let teams = team.teams().await.unwrap();
+        assert!(teams.teams.contains_key("compiler"));
+        assert_eq!(downloads(), 1);
+
+        team.invalidate().await;
+        team.teams().await.unwrap();
+        assert_eq!(downloads(), 2);
+    }
+
+    #[tokio::test]
+    async fn stale_teams_are_used_when_download_fails() {
+        let mock = MockGithub::start().await;
+        mock.set_teams(&[("compiler", &["martin"])]);
+        let team = TeamClient::new(mock.url().to_string());
+        team.teams().await.unwrap();
+
+        set_age(&team.teams, CACHE_DURATION + Duration::from_secs(1)).await;
+        mock.fail(Method::GET, "/teams.json", 2);
+        let teams = team.teams().await.unwrap();
+        assert!(teams.teams.contains_key("compiler"));
+
+        // Copies older than `STALE_CACHE_DURATION` are not used anymore.
+        set_age(&team.teams, STALE_CACHE_DURATION + Duration::from_secs(1)).await;
+        assert!(team.teams().await.is_err());
+    }
+}
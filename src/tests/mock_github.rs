@@ -0,0 +1,254 @@
+//! A fake GitHub (and team data) API server for end-to-end handler tests.
+//!
+//! The server records every request it receives, so that tests can assert
+//! which API calls a handler made, e.g. which comments it posted or which
+//! assignees it set. Requests without an explicit response registered with
+//! [`MockGithub::respond`] get a minimal default response.
+
+use axum::Json;
+use axum::Router;
+use axum::body::Bytes;
+use axum::extract::State;
+use axum::http::{Method, StatusCode, Uri};
+use axum::response::{IntoResponse, Response};
+use serde_json::{Value, json};
+use std::sync::{Arc, Mutex};
+
+/// A request received by the [`MockGithub`] server.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct RecordedRequest {
+    pub(crate) method: Method,
+    pub(crate) path: String,
+    /// The JSON body of the request, or `Null` if it had none.
+    pub(crate) body: Value,
+}
+
+struct MockState {
+    requests: Vec<RecordedRequest>,
+    responses: Vec<(Method, String, Value)>,
+    /// Number of upcoming requests with a method and path that fail.
+    failures: Vec<(Method, String, usize)>,
+    teams: Value,
+}
+
+#[derive(Clone)]
+pub(crate) struct MockGithub {
+    url: String,
+    state: Arc<Mutex<MockState>>,
+}
+
+impl MockGithub {
+    /// Starts the server on a random local port.
+    pub(crate) async fn start() -> Self {
+        let state = Arc::new(Mutex::new(MockState {
+            requests: vec![],
+            responses: vec![],
+            failures: vec![],
+            teams: teams_json(&[]),
+        }));
+        let app = Router::new()
+            .fallback(handle_request)
+            .with_state(state.clone());
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0")
+            .await
+            .expect("Cannot bind the mock GitHub server");
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        Self { url, state }
+    }
+
+    /// The base URL of the server, to be used as the API URL of the clients.
+    pub(crate) fn url(&self) -> &str {
+        &self.url
+    }
+
+    /// Responds with `response` to requests with the given method and path.
+    pub(crate) fn respond(&self, method: Method, path: &str, response: Value) {
+        self.state
+            .lock()
+            .unwrap()
+            .responses
+            .push((method, path.to_string(), response));
+    }
+
+    /// Fails the next `count` requests with the given method and path with an
+    /// internal server error, e.g. to test retries.
+    pub(crate) fn fail(&self, method: Method, path: &str, count: usize) {
+        self.state
+            .lock()
+            .unwrap()
+            .failures
+            .push((method, path.to_string(), count));
+    }
+
+    /// Sets the teams returned by the team data API, as `(team name, members)` pairs.
+    /// All members are also part of the `all` team.
+    pub(crate) fn set_teams(&self, teams: &[(&str, &[&str])]) {
+        self.state.lock().unwrap().teams = teams_json(teams);
+    }
+
+    /// Returns all requests received so far.
+    pub(crate) fn requests(&self) -> Vec<RecordedRequest> {
+        self.state.lock().unwrap().requests.clone()
+    }
+
+    /// Returns the bodies of all posted comments.
+    pub(crate) fn posted_comments(&self) -> Vec<String> {
+        self.request_bodies(Method::POST, "/comments")
+            .into_iter()
+            .filter_map(|body| body["body"].as_str().map(|s| s.to_string()))
+            .collect()
+    }
+
+    /// Returns the usernames of all added assignees.
+    pub(crate) fn added_assignees(&self) -> Vec<String> {
+        self.request_array_items(Method::POST, "/assignees", "assignees")
+    }
+
+    /// Returns the names of all added labels.
+    pub(crate) fn added_labels(&self) -> Vec<String> {
+        self.request_array_items(Method::POST, "/labels", "labels")
+    }
+
+    fn request_bodies(&self, method: Method, path_suffix: &str) -> Vec<Value> {
+        self.requests()
+            .into_iter()
+            .filter(|req| req.method == method && req.path.ends_with(path_suffix))
+            .map(|req| req.body)
+            .collect()
+    }
+
+    fn request_array_items(&self, method: Method, path_suffix: &str, key: &str) -> Vec<String> {
+        self.request_bodies(method, path_suffix)
+            .iter()
+            .filter_map(|body| body[key].as_array())
+            .flatten()
+            .filter_map(|item| item.as_str().map(|s| s.to_string()))
+            .collect()
+    }
+}
+
+async fn handle_request(
+    State(state): State<Arc<Mutex<MockState>>>,
+    method: Method,
+    uri: Uri,
+    body: Bytes,
+) -> Response {
+    let path = uri.path().to_string();
+    let body: Value = serde_json::from_slice(&body).unwrap_or(Value::Null);
+
+    let mut state = state.lock().unwrap();
+    state.requests.push(RecordedRequest {
+        method: method.clone(),
+        path: path.clone(),
+        body: body.clone(),
+    });
+
+    if let Some((_, _, count)) = state
+        .failures
+        .iter_mut()
+        .find(|(m, p, count)| *m == method && *p == path && *count > 0)
+    {
+        *count -= 1;
+        return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+    }
+
+    if let Some((_, _, response)) = state
+        .responses
+        .iter()
+        .find(|(m, p, _)| *m == method && *p == path)
+    {
+        return Json(response.clone()).into_response();
+    }
+
+    let response = if path == "/teams.json" {
+        state.teams.clone()
+    } else if method == Method::POST && path.ends_with("/comments") {
+        comment_json(&path, &body)
+    } else if method == Method::POST && path.ends_with("/assignees") {
+        issue_with_assignees_json(&path, &body)
+    } else {
+        // Other requests (e.g. checking that a label exists, or deleting assignees)
+        // only need a successful status.
+        json!({})
+    };
+    Json(response).into_response()
+}
+
+fn teams_json(teams: &[(&str, &[&str])]) -> Value {
+    let team_json = |name: &str, members: &[&str]| {
+        let members: Vec<Value> = members
+            .iter()
+            .map(|member| {
+                json!({"name": member, "github": member, "github_id": 100, "is_lead": false})
+            })
+            .collect();
+        json!({
+            "name": name,
+            "kind": "team",
+            "members": members,
+            "alumni": [],
+            "discord": [],
+            "roles": [],
+        })
+    };
+    let mut all_members: Vec<&str> = teams
+        .iter()
+        .flat_map(|(_, members)| *members)
+        .copied()
+        .collect();
+    all_members.sort();
+    all_members.dedup();
+
+    let mut json = json!({ "all": team_json("all", &all_members) });
+    for (name, members) in teams {
+        json[*name] = team_json(name, members);
+    }
+    json
+}
+
+fn comment_json(path: &str, body: &Value) -> Value {
+    json!({
+        "id": 1,
+        "node_id": "IC_mock",
+        "body": body["body"],
+        "html_url": format!("https://github.com{path}#issuecomment-1"),
+        "user": { "login": "triagebot-test", "id": 0 },
+        "author_association": "NONE",
+    })
+}
+
+/// Returns the issue that `POST /repos/{owner}/{repo}/issues/{number}/assignees` would
+/// return, with the requested users as assignees.
+fn issue_with_assignees_json(path: &str, body: &Value) -> Value {
+    let issue_path = path.trim_end_matches("/assignees");
+    let number: u64 = issue_path
+        .rsplit('/')
+        .next()
+        .and_then(|n| n.parse().ok())
+        .unwrap_or(1);
+    let assignees: Vec<Value> = body["assignees"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .map(|login| json!({ "login": login, "id": 0 }))
+        .collect();
+    json!({
+        "number": number,
+        "body": "",
+        "created_at": "2025-01-01T00:00:00Z",
+        "updated_at": "2025-01-01T00:00:00Z",
+        "title": format!("Issue #{number}"),
+        "html_url": format!("https://github.com{issue_path}"),
+        "user": { "login": "triagebot-tester", "id": 1 },
+        "labels": [],
+        "assignees": assignees,
+        "pull_request": null,
+        "comments": null,
+        "comments_url": format!("https://api.github.com{issue_path}/comments"),
+        "state": "open",
+        "milestone": null,
+        "mergeable": null,
+        "author_association": "NONE",
+    })
+}
@@ -4,6 +4,7 @@ use crate::db::{ClientPool, PooledClient, make_client};
 use crate::github::GithubClient;
 use crate::handlers::Context;
 use crate::team_data::TeamClient;
+use crate::tests::mock_github::MockGithub;
 use crate::zulip::client::ZulipClient;
 use octocrab::Octocrab;
 use std::future::Future;
@@ -13,6 +14,7 @@ use tokio_postgres::Config;
 use tokio_postgres::config::Host;
 
 pub(crate) mod github;
+pub(crate) mod mock_github;
 
 /// Represents a connection to a Postgres database that can be
 /// used in integration tests to test logic that interacts with
@@ -83,6 +85,8 @@ impl TestContext {
             octocrab,
             workqueue: Arc::new(RwLock::new(Default::default())),
             gha_logs: Arc::new(RwLock::new(Default::default())),
+            repo_limiter: Arc::new(Default::default()),
+            owners_files: Arc::new(Default::default()),
         };
 
         Self {
@@ -94,12 +98,26 @@ impl TestContext {
     }
 
     /// Returns a fake handler context.
-    /// We currently do not mock outgoing nor incoming GitHub API calls,
-    /// so the API endpoints will not be actually working.
+    /// Unless [`TestContext::mock_github`] is used, outgoing GitHub API calls
+    /// are not mocked, so the API endpoints will not be actually working.
     pub(crate) fn handler_ctx(&self) -> &Context {
         &self.ctx
     }
 
+    /// Starts a [`MockGithub`] server and redirects the GitHub and team data
+    /// API clients of the handler context to it.
+    pub(crate) async fn mock_github(&mut self) -> MockGithub {
+        let mock = MockGithub::start().await;
+        self.ctx.github = GithubClient::new(
+            "gh-test-fake-token".to_string(),
+            mock.url().to_string(),
+            format!("{}/graphql", mock.url()),
+            mock.url().to_string(),
+        );
+        self.ctx.team = TeamClient::new(mock.url().to_string());
+        mock
+    }
+
     pub(crate) fn db_client(&self) -> &PooledClient {
         &self.client
     }
@@ -0,0 +1,144 @@
+//! Read-only endpoint listing the reviewers that are currently unavailable
+//! for a repository.
+//!
+//! This is meant to help debugging why someone is not being assigned: it
+//! shows the `users_on_vacation` of the repository `[assign]` config together
+//! with the users whose review preferences currently keep them from being
+//! assigned (vacation, off rotation, away or outside of their office hours).
+
+use std::sync::Arc;
+
+use anyhow::Context as _;
+use axum::{
+    Json,
+    extract::{Path, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+};
+use chrono::{DateTime, Utc};
+
+use crate::{
+    config::{self, AssignConfig},
+    db::review_prefs::{Unavailability, get_unavailable_users},
+    handlers::Context,
+    utils::AppError,
+};
+
+#[derive(Debug, PartialEq, serde::Serialize)]
+pub struct VacationStatus {
+    /// Users listed in `users_on_vacation` of the `[assign]` config.
+    pub users_on_vacation: Vec<String>,
+    /// Users whose review preferences keep them from being assigned.
+    pub unavailable: Vec<UnavailableUser>,
+}
+
+#[derive(Debug, PartialEq, serde::Serialize)]
+pub struct UnavailableUser {
+    pub username: String,
+    pub reason: Unavailability,
+}
+
+pub async fn vacation_status(
+    Path((owner, repo)): Path<(String, String)>,
+    State(ctx): State<Arc<Context>>,
+) -> axum::response::Result<Response, AppError> {
+    // Only look up the repositories triagebot is used on, so that this
+    // public endpoint can't be used to fetch arbitrary repositories.
+    if !ctx.team.is_public_bot_repo(&owner, &repo).await? {
+        return Ok((
+            StatusCode::NOT_FOUND,
+            format!("triagebot is not used on the public repository `{owner}/{repo}`"),
+        )
+            .into_response());
+    }
+    let repo = ctx.github.repository(&format!("{owner}/{repo}")).await?;
+    let config = config::get(&ctx.github, &repo)
+        .await
+        .with_context(|| format!("failed to load the config of {}", repo.full_name))?;
+
+    let status = compute_vacation_status(
+        &*ctx.db.get().await,
+        config.assign.as_ref(),
+        chrono::Utc::now(),
+    )
+    .await?;
+    Ok(Json(status).into_response())
+}
+
+async fn compute_vacation_status(
+    db: &tokio_postgres::Client,
+    assign: Option<&AssignConfig>,
+    now: DateTime<Utc>,
+) -> anyhow::Result<VacationStatus> {
+    let mut users_on_vacation: Vec<String> = assign
+        .map(|assign| assign.users_on_vacation.iter().cloned().collect())
+        .unwrap_or_default();
+    users_on_vacation.sort_by_key(|user| user.to_lowercase());
+
+    let unavailable = get_unavailable_users(db, now)
+        .await?
+        .into_iter()
+        .map(|(username, reason)| UnavailableUser { username, reason })
+        .collect();
+
+    Ok(VacationStatus {
+        users_on_vacation,
+        unavailable,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{UnavailableUser, VacationStatus, compute_vacation_status};
+    use crate::config::AssignConfig;
+    use crate::db::review_prefs::{
+        OfficeHours, RotationMode, Unavailability, set_office_hours, upsert_review_prefs,
+    };
+    use crate::tests::github::user;
+    use crate::tests::run_db_test;
+    use chrono::{TimeZone, Utc};
+
+    #[tokio::test]
+    async fn lists_config_and_review_prefs() {
+        run_db_test(|ctx| async {
+            let db = ctx.db_client();
+            let now = Utc.with_ymd_and_hms(2025, 6, 2, 20, 0, 0).unwrap();
+
+            upsert_review_prefs(&db, user("martin", 1), None, RotationMode::OffRotation).await?;
+            upsert_review_prefs(&db, user("diana", 2), None, RotationMode::OnRotation).await?;
+            set_office_hours(
+                &db,
+                user("diana", 2),
+                Some(OfficeHours {
+                    start: 9,
+                    end: 17,
+                    utc_offset: 0,
+                }),
+            )
+            .await?;
+            upsert_review_prefs(&db, user("jana", 3), None, RotationMode::OnRotation).await?;
+
+            let config: AssignConfig = toml::toml!(users_on_vacation = ["Jyn514", "adam"])
+                .try_into()
+                .unwrap();
+            assert_eq!(
+                compute_vacation_status(&db, Some(&config), now).await?,
+                VacationStatus {
+                    users_on_vacation: vec!["adam".to_string(), "Jyn514".to_string()],
+                    unavailable: vec![
+                        UnavailableUser {
+                            username: "diana".to_string(),
+                            reason: Unavailability::OutsideOfficeHours,
+                        },
+                        UnavailableUser {
+                            username: "martin".to_string(),
+                            reason: Unavailability::OffRotation,
+                        },
+                    ],
+                }
+            );
+            Ok(ctx)
+        })
+        .await;
+    }
+}
@@ -5,12 +5,13 @@ mod commands;
 use crate::db::notifications::add_metadata;
 use crate::db::notifications::{self, Identifier, delete_ping, move_indices, record_ping};
 use crate::db::review_prefs::{
-    RotationMode, get_review_prefs, get_review_prefs_batch, upsert_review_prefs,
+    OfficeHours, RotationMode, get_review_prefs, get_review_prefs_batch, set_approver_only,
+    set_blocked_authors, set_office_hours, upsert_review_prefs,
 };
-use crate::github::User;
+use crate::github::{IssueRepository, Selection, User};
 use crate::handlers::Context;
 use crate::handlers::docs_update::docs_update;
-use crate::handlers::pr_tracking::get_assigned_prs;
+use crate::handlers::pr_tracking::{WORKQUEUE_REPOSITORY, get_assigned_prs, sync_workqueue};
 use crate::handlers::project_goals::{self, ping_project_goals_owners};
 use crate::interactions::ErrorComment;
 use crate::utils::pluralize;
@@ -471,6 +472,12 @@ fn is_sensitive_command(cmd: &ChatCommand) -> bool {
             WorkqueueCmd::Show => false,
             WorkqueueCmd::SetPrLimit { .. } => true,
             WorkqueueCmd::SetRotationMode { .. } => true,
+            WorkqueueCmd::Transfer { .. } => true,
+            WorkqueueCmd::BlockAuthor { .. } => true,
+            WorkqueueCmd::UnblockAuthor { .. } => true,
+            WorkqueueCmd::Sync => false,
+            WorkqueueCmd::SetOfficeHours { .. } => true,
+            WorkqueueCmd::SetApproverOnly { .. } => true,
         },
     }
 }
@@ -542,6 +549,15 @@ async fn workqueue_commands(
 
             writeln!(response, "Review capacity: `{capacity}`\n")?;
             writeln!(response, "Rotation mode: *{rotation_mode}*\n")?;
+            if let Some(office_hours) = review_prefs.as_ref().and_then(|p| p.office_hours) {
+                writeln!(response, "Office hours: `{office_hours}`\n")?;
+            }
+            if review_prefs.as_ref().is_some_and(|p| p.approver_only) {
+                writeln!(
+                    response,
+                    "Approver only: *on* (you are only assigned when requested directly)\n"
+                )?;
+            }
             writeln!(
                 response,
                 "*Note that only certain PRs that are assigned to you are included in your review queue.*"
@@ -589,11 +605,211 @@ async fn workqueue_commands(
                 }
             )
         }
+        WorkqueueCmd::Transfer { from, to } => transfer_workqueue(ctx, gh_id, from, to).await?,
+        WorkqueueCmd::SetOfficeHours { hours, utc_offset } => {
+            let office_hours = hours.0.map(|(start, end)| OfficeHours {
+                start,
+                end,
+                utc_offset: utc_offset.0,
+            });
+            set_office_hours(&db_client, user, office_hours)
+                .await
+                .context("Error occurred while setting review preferences.")?;
+            tracing::info!("Setting office hours of `{gh_username}` to {office_hours:?}");
+            match office_hours {
+                Some(office_hours) => format!(
+                    "Office hours set to `{office_hours}`. Outside of them, PRs will only be \
+                     assigned to you if no other reviewer is available."
+                ),
+                None => "Office hours cleared, PRs can be assigned to you at any time.".to_string(),
+            }
+        }
+        WorkqueueCmd::SetApproverOnly { approver_only } => {
+            let approver_only = approver_only.0;
+            set_approver_only(&db_client, user, approver_only)
+                .await
+                .context("Error occurred while setting review preferences.")?;
+            tracing::info!("Setting approver only mode of `{gh_username}` to {approver_only}");
+            if approver_only {
+                "Approver only mode set to *on*, you will only be assigned PRs when you are \
+                 requested directly."
+                    .to_string()
+            } else {
+                "Approver only mode set to *off*, you can be assigned PRs through teams and \
+                 assign groups again."
+                    .to_string()
+            }
+        }
+        WorkqueueCmd::Sync => {
+            // Team changes, e.g. new reviewers, are picked up right away too.
+            ctx.team.invalidate().await;
+            let workqueue = sync_workqueue(&ctx.github)
+                .await
+                .context("Cannot synchronize the review queues.")?;
+            *ctx.workqueue.write().await = workqueue;
+            let assigned_prs = get_assigned_prs(ctx, gh_id).await.len();
+            tracing::info!("Review queues synchronized by `{gh_username}`");
+            format!(
+                "Review queues synchronized with GitHub, your review queue has {assigned_prs} {}.",
+                pluralize("PR", assigned_prs)
+            )
+        }
+        WorkqueueCmd::BlockAuthor { author } | WorkqueueCmd::UnblockAuthor { author } => {
+            let author = author.trim_start_matches('@');
+            let mut blocked_authors = review_prefs.map(|p| p.blocked_authors).unwrap_or_default();
+            blocked_authors.retain(|blocked| !blocked.eq_ignore_ascii_case(author));
+            let block = matches!(cmd, WorkqueueCmd::BlockAuthor { .. });
+            if block {
+                blocked_authors.push(author.to_string());
+            }
+            set_blocked_authors(&db_client, user, &blocked_authors)
+                .await
+                .context("Error occurred while setting review preferences.")?;
+            tracing::info!("Setting blocked authors of `{gh_username}`");
+            if block {
+                format!("PRs opened by `{author}` will not be assigned to you anymore.")
+            } else {
+                format!("PRs opened by `{author}` can be assigned to you again.")
+            }
+        }
     };
 
     Ok(Some(response))
 }
 
+/// Reassigns the PRs in the review queue of `from` to `to`, as long as the
+/// review capacity of `to` allows it.
+///
+/// Only `from` themselves or a lead of one of their teams can do it.
+async fn transfer_workqueue(
+    ctx: &Context,
+    caller_gh_id: u64,
+    from: &str,
+    to: &str,
+) -> anyhow::Result<String> {
+    let from = find_team_member(ctx, from).await?;
+    let to = find_team_member(ctx, to).await?;
+    if caller_gh_id != from.id && !is_team_lead_of(ctx, caller_gh_id, from.id).await? {
+        anyhow::bail!(
+            "Only `{}` or a lead of one of their teams can transfer their review queue",
+            from.login
+        );
+    }
+
+    let (organization, repository) = WORKQUEUE_REPOSITORY;
+    let repo = IssueRepository {
+        organization: organization.to_string(),
+        repository: repository.to_string(),
+    };
+    let mut assigned_prs = get_assigned_prs(ctx, from.id)
+        .await
+        .into_iter()
+        .collect::<Vec<_>>();
+    if assigned_prs.is_empty() {
+        return Ok(format!(
+            "There are no PRs in the `{repo}` review queue of `{}`",
+            from.login
+        ));
+    }
+    assigned_prs.sort_by_key(|(pr_number, _)| *pr_number);
+
+    let capacity = get_review_prefs(&*ctx.db.get().await, to.id)
+        .await
+        .context("cannot get review preferences")?
+        .and_then(|p| p.max_assigned_prs);
+    let mut assigned_count = ctx.workqueue.read().await.assigned_pr_count(to.id);
+
+    let mut moved = vec![];
+    let mut skipped = vec![];
+    for (pr_number, pr) in assigned_prs {
+        let pr_link = format!(
+            "- [#{pr_number}](https://github.com/{repo}/pull/{pr_number}) {}",
+            pr.title
+        );
+        if capacity.is_some_and(|capacity| assigned_count >= capacity as u64) {
+            skipped.push(format!("{pr_link} (review capacity reached)"));
+            continue;
+        }
+        if let Err(e) = reassign_pr(ctx, &repo, pr_number, &from, &to).await {
+            log::warn!("failed to transfer {repo}#{pr_number}: {e:?}");
+            skipped.push(format!("{pr_link} (error: {e})"));
+            continue;
+        }
+        ctx.workqueue
+            .write()
+            .await
+            .transfer_pr(from.id, to.id, pr_number);
+        assigned_count += 1;
+        moved.push(pr_link);
+    }
+
+    let mut response = format!(
+        "Transferred {} {} from `{}` to `{}`",
+        moved.len(),
+        pluralize("PR", moved.len()),
+        from.login,
+        to.login
+    );
+    if !moved.is_empty() {
+        write!(response, ":\n{}", moved.join("\n"))?;
+    }
+    if !skipped.is_empty() {
+        write!(
+            response,
+            "\n\nSkipped {} {}:\n{}",
+            skipped.len(),
+            pluralize("PR", skipped.len()),
+            skipped.join("\n")
+        )?;
+    }
+    Ok(response)
+}
+
+/// Returns true if the user with the GitHub id `lead` leads a team that the
+/// user with the GitHub id `member` is part of.
+async fn is_team_lead_of(ctx: &Context, lead: u64, member: u64) -> anyhow::Result<bool> {
+    let teams = ctx
+        .team
+        .teams()
+        .await
+        .context("cannot load team information")?;
+    Ok(teams.teams.values().any(|team| {
+        team.members
+            .iter()
+            .any(|m| m.github_id == lead && m.is_lead)
+            && team.members.iter().any(|m| m.github_id == member)
+    }))
+}
+
+/// Returns the GitHub user of the Rust team member with the given GitHub username.
+async fn find_team_member(ctx: &Context, username: &str) -> anyhow::Result<User> {
+    let login = username.strip_prefix('@').unwrap_or(username);
+    let id = ctx
+        .team
+        .get_gh_id_from_username(login)
+        .await?
+        .ok_or_else(|| anyhow::anyhow!("`{login}` is not a member of any Rust team"))?;
+    Ok(User {
+        login: login.to_string(),
+        id,
+    })
+}
+
+/// Assigns `to` instead of `from` to the given PR.
+async fn reassign_pr(
+    ctx: &Context,
+    repo: &IssueRepository,
+    pr_number: u64,
+    from: &User,
+    to: &User,
+) -> anyhow::Result<()> {
+    let pr = ctx.github.get_issue(repo, pr_number).await?;
+    pr.add_assignee(&ctx.github, &to.login).await?;
+    pr.remove_assignees(&ctx.github, Selection::One(&from.login))
+        .await?;
+    Ok(())
+}
+
 /// The `whoami` command displays the user's membership in Rust teams.
 async fn whoami_cmd(ctx: &Context, gh_id: u64) -> anyhow::Result<Option<String>> {
     let gh_username =
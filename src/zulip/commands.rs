@@ -77,6 +77,42 @@ pub enum WorkqueueCmd {
         /// Rotation mode
         rotation_mode: RotationModeCli,
     },
+    /// Reassign all PRs in the review queue of a user to another user.
+    /// Only the user or a lead of one of their teams can do it.
+    Transfer {
+        /// GitHub username of the current reviewer
+        from: String,
+        /// GitHub username of the new reviewer
+        to: String,
+    },
+    /// Stop being assigned PRs opened by the given author.
+    BlockAuthor {
+        /// GitHub username of the PR author
+        author: String,
+    },
+    /// Allow being assigned PRs opened by the given author again.
+    UnblockAuthor {
+        /// GitHub username of the PR author
+        author: String,
+    },
+    /// Rebuild the review queues from the PRs currently open on GitHub, and
+    /// reload the team data.
+    Sync,
+    /// Only be assigned PRs automatically during your office hours, unless no other
+    /// reviewer is available.
+    SetOfficeHours {
+        /// Hours of the day (e.g. `9-17`), or `none` to be assigned PRs at any time
+        hours: OfficeHoursCli,
+        /// Your time zone, as an offset from UTC (e.g. `UTC+2` or `UTC-5:30`)
+        #[clap(default_value = "UTC")]
+        utc_offset: UtcOffsetCli,
+    },
+    /// Set whether you are only an approver (`on` or `off`). Approvers are not picked
+    /// from teams and assign groups, but can still be requested with `r? @you`.
+    SetApproverOnly {
+        /// Approver only mode
+        approver_only: ApproverOnlyCli,
+    },
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -115,6 +151,74 @@ impl FromStr for RotationModeCli {
     }
 }
 
+#[derive(Debug, Clone, PartialEq)]
+pub struct ApproverOnlyCli(pub bool);
+
+impl FromStr for ApproverOnlyCli {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "on" => Ok(Self(true)),
+            "off" => Ok(Self(false)),
+            _ => Err("Invalid value for approver only mode. Must be `on` or `off`.".to_string()),
+        }
+    }
+}
+
+/// Office hours as `(start, end)` hours, or `None` to clear them.
+#[derive(Debug, Clone, PartialEq)]
+pub struct OfficeHoursCli(pub Option<(u32, u32)>);
+
+impl FromStr for OfficeHoursCli {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s == "none" {
+            return Ok(Self(None));
+        }
+        let error = || {
+            "Wrong parameter format. Must be a range of hours (e.g. `9-17`) or `none`.".to_string()
+        };
+        let (start, end) = s.split_once('-').ok_or_else(error)?;
+        let start: u32 = start.parse().map_err(|_| error())?;
+        let end: u32 = end.parse().map_err(|_| error())?;
+        if start > 23 || end > 24 || start == end {
+            return Err(error());
+        }
+        Ok(Self(Some((start, end))))
+    }
+}
+
+/// Offset of a time zone from UTC, in minutes.
+#[derive(Debug, Clone, PartialEq)]
+pub struct UtcOffsetCli(pub i32);
+
+impl FromStr for UtcOffsetCli {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let error =
+            || "Wrong time zone format. Must be e.g. `UTC`, `UTC+2` or `UTC-5:30`.".to_string();
+        let offset = s.strip_prefix("UTC").ok_or_else(error)?;
+        if offset.is_empty() {
+            return Ok(Self(0));
+        }
+        let (sign, offset) = match offset.split_at(1) {
+            ("+", offset) => (1, offset),
+            ("-", offset) => (-1, offset),
+            _ => return Err(error()),
+        };
+        let (hours, minutes) = offset.split_once(':').unwrap_or((offset, "0"));
+        let hours: i32 = hours.parse().map_err(|_| error())?;
+        let minutes: i32 = minutes.parse().map_err(|_| error())?;
+        if hours > 14 || minutes >= 60 {
+            return Err(error());
+        }
+        Ok(Self(sign * (hours * 60 + minutes)))
+    }
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub enum IdentifierCli {
     Url(String),
@@ -257,6 +361,49 @@ mod tests {
                 limit: WorkqueueLimit::Unlimited
             })
         );
+
+        assert_eq!(
+            parse_chat(&["work", "transfer", "@martin", "diana"]),
+            ChatCommand::Work(WorkqueueCmd::Transfer {
+                from: "@martin".to_string(),
+                to: "diana".to_string()
+            })
+        );
+
+        assert_eq!(
+            parse_chat(&["work", "block-author", "@martin"]),
+            ChatCommand::Work(WorkqueueCmd::BlockAuthor {
+                author: "@martin".to_string()
+            })
+        );
+
+        assert_eq!(
+            parse_chat(&["work", "sync"]),
+            ChatCommand::Work(WorkqueueCmd::Sync)
+        );
+
+        assert_eq!(
+            parse_chat(&["work", "set-office-hours", "9-17", "UTC-5:30"]),
+            ChatCommand::Work(WorkqueueCmd::SetOfficeHours {
+                hours: OfficeHoursCli(Some((9, 17))),
+                utc_offset: UtcOffsetCli(-330),
+            })
+        );
+
+        assert_eq!(
+            parse_chat(&["work", "set-approver-only", "on"]),
+            ChatCommand::Work(WorkqueueCmd::SetApproverOnly {
+                approver_only: ApproverOnlyCli(true),
+            })
+        );
+
+        assert_eq!(
+            parse_chat(&["work", "set-office-hours", "none"]),
+            ChatCommand::Work(WorkqueueCmd::SetOfficeHours {
+                hours: OfficeHoursCli(None),
+                utc_offset: UtcOffsetCli(0),
+            })
+        );
     }
 
     #[test]